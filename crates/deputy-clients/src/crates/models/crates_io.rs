@@ -28,6 +28,10 @@ pub struct CrateData {
     pub links: CrateDataLinks,
     #[serde(flatten)]
     pub downloads: CrateDataDownloads,
+    #[serde(default)]
+    pub keywords: Option<Vec<String>>,
+    #[serde(default)]
+    pub categories: Option<Vec<String>>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -56,10 +60,18 @@ pub struct CrateDataVersion {
     pub updated_at: String,
     pub downloads: u64,
     pub features: HashMap<String, Vec<String>>,
+    #[serde(default)]
+    pub license: Option<String>,
+    #[serde(default)]
+    pub yanked: bool,
 }
 
 impl Versioned for CrateDataVersion {
     fn raw_version_string(&self) -> String {
         self.version.to_string()
     }
+
+    fn yanked(&self) -> bool {
+        self.yanked
+    }
 }
@@ -23,6 +23,10 @@ impl Versioned for IndexMetadata {
     fn raw_version_string(&self) -> String {
         self.version.to_string()
     }
+
+    fn yanked(&self) -> bool {
+        self.yanked
+    }
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -5,3 +5,6 @@ pub const GITHUB_API_VERSION_VALUE: &str = "2022-11-28";
 
 pub const GITHUB_API_CONTENT_TYPE: &str = "application/vnd.github.v3+json";
 pub const GITHUB_API_CONTENT_TYPE_RAW: &str = "application/vnd.github.raw";
+
+pub const GITHUB_API_PAGE_SIZE: usize = 100;
+pub const GITHUB_API_MAXIMUM_TAG_PAGES: usize = 10;
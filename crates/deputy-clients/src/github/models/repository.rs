@@ -26,6 +26,23 @@ impl Versioned for RepositoryRelease {
     }
 }
 
+#[derive(Debug, Clone, Deserialize)]
+pub struct RepositoryTag {
+    pub name: String,
+    pub commit: RepositoryTagCommit,
+}
+
+impl Versioned for RepositoryTag {
+    fn raw_version_string(&self) -> String {
+        self.name.trim_start_matches('v').to_string()
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RepositoryTagCommit {
+    pub sha: String,
+}
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct RepositoryReleaseAsset {
     pub name: String,
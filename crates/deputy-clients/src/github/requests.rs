@@ -1,10 +1,10 @@
 use tracing::debug;
 
 use super::consts::{
-    GITHUB_API_BASE_URL, GITHUB_API_CONTENT_TYPE_RAW, GITHUB_API_VERSION_NAME,
-    GITHUB_API_VERSION_VALUE,
+    GITHUB_API_BASE_URL, GITHUB_API_CONTENT_TYPE_RAW, GITHUB_API_MAXIMUM_TAG_PAGES,
+    GITHUB_API_PAGE_SIZE, GITHUB_API_VERSION_NAME, GITHUB_API_VERSION_VALUE,
 };
-use super::models::{GitTreeRoot, RepositoryMetrics, RepositoryRelease};
+use super::models::{GitTreeRoot, RepositoryMetrics, RepositoryRelease, RepositoryTag};
 use super::{GithubClient, Request, RequestResult};
 
 impl GithubClient {
@@ -76,6 +76,54 @@ impl GithubClient {
             .await
     }
 
+    /**
+        Fetches the tags of a repository, newest first, following pagination
+        for up to 1000 tags - repositories may have more tags than that.
+    */
+    #[allow(clippy::missing_errors_doc)]
+    pub async fn get_repository_tags(
+        &self,
+        owner: &str,
+        repository: &str,
+    ) -> RequestResult<Vec<RepositoryTag>> {
+        let owner_low = owner.to_ascii_lowercase();
+        let repository_low = repository.to_ascii_lowercase();
+
+        let tags_url = format!(
+            "{GITHUB_API_BASE_URL}/repos/{owner_low}/{repository_low}/tags?per_page={GITHUB_API_PAGE_SIZE}"
+        );
+
+        let fut = async move {
+            debug!("Fetching GitHub tags for {owner}/{repository}");
+
+            // NOTE: We make this inner scope so that
+            // we can catch and emit all errors at once
+            let inner = async {
+                let mut tags = Vec::new();
+                for page in 1..=GITHUB_API_MAXIMUM_TAG_PAGES {
+                    let bytes = self.request_get(format!("{tags_url}&page={page}")).await?;
+                    let page_tags = serde_json::from_slice::<Vec<RepositoryTag>>(&bytes)?;
+                    let is_last_page = page_tags.len() < GITHUB_API_PAGE_SIZE;
+                    tags.extend(page_tags);
+                    if is_last_page {
+                        break;
+                    }
+                }
+                Ok(tags)
+            }
+            .await;
+
+            self.emit_result(&inner);
+
+            inner
+        };
+
+        self.cache
+            .repository_tags
+            .with_caching(format!("{owner_low}/{repository_low}"), fut)
+            .await
+    }
+
     #[allow(clippy::missing_errors_doc)]
     pub async fn get_repository_tree(
         &self,
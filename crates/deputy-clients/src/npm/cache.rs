@@ -1,16 +1,24 @@
 use crate::shared::{RequestCacheMap, RequestResult};
 
-use super::models::RegistryMetadata;
+use super::models::{
+    RegistryMetadata, RegistryMetadataAbbreviated, RegistryMetadataPackageVersion, SearchResults,
+};
 
 #[derive(Debug, Clone)]
 pub(super) struct NpmCache {
     pub registry_metadatas: RequestCacheMap<RequestResult<RegistryMetadata>>,
+    pub registry_metadatas_abbreviated: RequestCacheMap<RequestResult<RegistryMetadataAbbreviated>>,
+    pub registry_versions: RequestCacheMap<RequestResult<RegistryMetadataPackageVersion>>,
+    pub package_search: RequestCacheMap<RequestResult<SearchResults>>,
 }
 
 impl NpmCache {
     pub fn new() -> Self {
         Self {
             registry_metadatas: RequestCacheMap::new(60, 15),
+            registry_metadatas_abbreviated: RequestCacheMap::new(60, 15),
+            registry_versions: RequestCacheMap::new(60, 15),
+            package_search: RequestCacheMap::new(480, 240),
         }
     }
 }
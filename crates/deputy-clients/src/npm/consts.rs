@@ -1 +1,10 @@
 pub const BASE_URL_REGISTRY: &str = "https://registry.npmjs.org/";
+pub const BASE_URL_SEARCH: &str = "https://registry.npmjs.org/-/v1/search";
+
+// Same as what npm itself sends, falling back to the full document for registries without support
+pub const CONTENT_TYPE_ABBREVIATED: &str =
+    "application/vnd.npm.install-v1+json; q=1.0, application/json; q=0.8";
+
+pub const QUERY_STRING_SEARCH: &str = "?size=32"; // First page only, and a reasonable amount
+
+pub const SEARCH_DEBOUNCE_MILLISECONDS: u64 = 250; // Wait for the user to stop typing before searching
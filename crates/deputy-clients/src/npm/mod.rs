@@ -1,18 +1,32 @@
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::Duration,
+};
+
+use serde::de::DeserializeOwned;
+use tokio::time::sleep;
 use tracing::error;
 
-use crate::shared::{Request, RequestResult};
+use crate::shared::{Request, RequestError, RequestResult};
 
 mod cache;
 use cache::NpmCache;
 
 mod consts;
+mod registry;
 mod requests;
 
 pub mod models;
 
+pub use registry::NpmRegistry;
+
 #[derive(Debug, Clone)]
 pub struct NpmClient {
     cache: NpmCache,
+    search_generation: Arc<AtomicU64>,
 }
 
 impl NpmClient {
@@ -20,6 +34,7 @@ impl NpmClient {
     pub fn new() -> Self {
         Self {
             cache: NpmCache::new(),
+            search_generation: Arc::new(AtomicU64::new(0)),
         }
     }
 
@@ -27,11 +42,48 @@ impl NpmClient {
         Request::get(url).send().await
     }
 
+    async fn request_get_registry(
+        &self,
+        registry: &NpmRegistry,
+        url: impl Into<String>,
+    ) -> RequestResult<Vec<u8>> {
+        Request::get(url)
+            .with_header_opt("Authorization", registry.authorization())
+            .send()
+            .await
+    }
+
+    async fn request_get_registry_abbreviated<T>(
+        &self,
+        registry: &NpmRegistry,
+        url: impl Into<String>,
+    ) -> RequestResult<T>
+    where
+        T: DeserializeOwned + Send + 'static,
+    {
+        Request::get(url)
+            .with_header("Accept", consts::CONTENT_TYPE_ABBREVIATED)
+            .with_header_opt("Authorization", registry.authorization())
+            .send_json()
+            .await
+    }
+
     fn emit_result<T>(result: &RequestResult<T>) {
         if let Err(e) = &result {
             error!("NPM error: {e}");
         }
     }
+
+    /**
+        Waits for a short while before searching, returning `false` if
+        another search was started in the meantime, meaning that this
+        search is outdated and should not be sent to the registry.
+    */
+    async fn debounce_search(&self) -> bool {
+        let generation = self.search_generation.fetch_add(1, Ordering::SeqCst) + 1;
+        sleep(Duration::from_millis(consts::SEARCH_DEBOUNCE_MILLISECONDS)).await;
+        self.search_generation.load(Ordering::SeqCst) == generation
+    }
 }
 
 impl Default for NpmClient {
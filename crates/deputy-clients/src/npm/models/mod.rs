@@ -1,3 +1,5 @@
 mod registry;
+mod search;
 
 pub use registry::*;
+pub use search::*;
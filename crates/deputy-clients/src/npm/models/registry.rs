@@ -1,24 +1,49 @@
 use std::collections::HashMap;
 
-use serde::Deserialize;
+use serde::{Deserialize, Deserializer, de::DeserializeOwned};
+use serde_json::Value;
 
 use deputy_versioning::Versioned;
 
+/**
+    The full metadata document for a package, also known as a packument.
+
+    This can be tens of megabytes for packages with many versions,
+    so prefer [`RegistryMetadataAbbreviated`] unless package
+    information such as the description or repository is needed.
+*/
 #[derive(Debug, Clone, Deserialize)]
 pub struct RegistryMetadata {
     #[serde(flatten)]
-    pub current_version: RegistryMetadataVersion,
+    pub package: RegistryMetadataPackage,
     #[serde(default, rename = "time")]
     pub timestamps: HashMap<String, String>,
+    #[serde(default, rename = "dist-tags")]
+    pub dist_tags: HashMap<String, String>,
     #[serde(default)]
-    pub versions: HashMap<String, RegistryMetadataVersion>,
+    pub versions: HashMap<String, RegistryMetadataPackageVersion>,
 }
 
+/**
+    The abbreviated metadata document for a package, which is what package
+    managers use during installs, and only contains data needed to resolve
+    versions - tags, versions, deprecations and engines.
+*/
 #[derive(Debug, Clone, Deserialize)]
-pub struct RegistryMetadataVersion {
+pub struct RegistryMetadataAbbreviated {
     pub name: String,
+    #[serde(default, rename = "dist-tags")]
+    pub dist_tags: HashMap<String, String>,
     #[serde(default)]
-    pub version: String,
+    pub versions: HashMap<String, RegistryMetadataVersion>,
+}
+
+/**
+    Information about a package as a whole, taken from its latest version.
+*/
+#[derive(Debug, Clone, Deserialize)]
+pub struct RegistryMetadataPackage {
+    pub name: String,
     #[serde(default)]
     pub description: Option<String>,
     #[serde(default)]
@@ -31,13 +56,142 @@ pub struct RegistryMetadataVersion {
     pub author: Option<RegistryMetadataHumanVariant>,
     #[serde(default)]
     pub maintainers: Vec<RegistryMetadataHumanVariant>,
+}
+
+/**
+    A single published version of a package.
+
+    Only contains fields that are also present in the abbreviated metadata
+    document, since there may be thousands of versions for a single package.
+*/
+#[derive(Debug, Clone, Deserialize)]
+pub struct RegistryMetadataVersion {
+    pub name: String,
+    #[serde(default)]
+    pub version: String,
     #[serde(default)]
     pub deprecated: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_lenient_map")]
+    pub engines: HashMap<String, String>,
+    #[serde(
+        default,
+        rename = "peerDependencies",
+        deserialize_with = "deserialize_lenient_map"
+    )]
+    pub peer_dependencies: HashMap<String, String>,
+    #[serde(
+        default,
+        rename = "peerDependenciesMeta",
+        deserialize_with = "deserialize_lenient_map"
+    )]
+    pub peer_dependencies_meta: HashMap<String, RegistryMetadataPeerMeta>,
+    #[serde(default)]
+    pub dist: Option<RegistryMetadataDist>,
+}
+
+impl RegistryMetadataVersion {
+    /**
+        Checks if the peer dependency with the given name is optional,
+        meaning that it does not need to be installed alongside this version.
+    */
+    #[must_use]
+    pub fn is_optional_peer(&self, name: &str) -> bool {
+        self.peer_dependencies_meta
+            .get(name)
+            .is_some_and(|meta| meta.optional)
+    }
 }
 
 impl Versioned for RegistryMetadataVersion {
     fn raw_version_string(&self) -> String {
-        self.version.to_string()
+        self.version.clone()
+    }
+
+    fn deprecated(&self) -> bool {
+        self.deprecated.is_some()
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct RegistryMetadataPeerMeta {
+    #[serde(default)]
+    pub optional: bool,
+}
+
+/**
+    Checksums of the tarball for a published version, where `integrity`
+    is a subresource integrity string, such as `sha512-<base64>`, and
+    `shasum` is the hexadecimal SHA-1 checksum used by older clients.
+*/
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct RegistryMetadataDist {
+    #[serde(default)]
+    pub integrity: Option<String>,
+    #[serde(default)]
+    pub shasum: Option<String>,
+}
+
+/**
+    A single published version of a package, along with information
+    about the package at that version, such as its license and authors.
+
+    Only available in the full metadata document, or
+    in the metadata document for that single version.
+*/
+#[derive(Debug, Clone, Deserialize)]
+pub struct RegistryMetadataPackageVersion {
+    pub name: String,
+    #[serde(default)]
+    pub version: String,
+    #[serde(default)]
+    pub deprecated: Option<String>,
+    #[serde(default)]
+    pub license: Option<RegistryMetadataLicenseVariant>,
+    #[serde(default)]
+    pub author: Option<RegistryMetadataHumanVariant>,
+    #[serde(default)]
+    pub maintainers: Vec<RegistryMetadataHumanVariant>,
+    #[serde(default)]
+    pub types: Option<String>,
+    #[serde(default)]
+    pub typings: Option<String>,
+    #[serde(default)]
+    pub exports: Option<Value>,
+}
+
+impl RegistryMetadataPackageVersion {
+    /**
+        Checks if this version ships its own TypeScript declarations, either using
+        the `types` or `typings` fields, or a declaration file in its `exports`.
+
+        Declaration files that TypeScript finds without any of these, such
+        as an `index.d.ts` next to `index.js`, can not be detected here.
+    */
+    #[must_use]
+    pub fn has_type_declarations(&self) -> bool {
+        self.types.is_some()
+            || self.typings.is_some()
+            || self.exports.as_ref().is_some_and(exports_have_declarations)
+    }
+}
+
+fn exports_have_declarations(exports: &Value) -> bool {
+    match exports {
+        Value::String(path) => [".d.ts", ".d.mts", ".d.cts"]
+            .iter()
+            .any(|extension| path.ends_with(extension)),
+        Value::Array(values) => values.iter().any(exports_have_declarations),
+        Value::Object(map) => map.iter().any(|(condition, value)| {
+            (matches!(condition.as_str(), "types" | "typings") && !value.is_null())
+                || exports_have_declarations(value)
+        }),
+        _ => false,
+    }
+}
+
+impl Versioned for RegistryMetadataPackageVersion {
+    fn raw_version_string(&self) -> String {
+        self.version.clone()
     }
 
     fn deprecated(&self) -> bool {
@@ -59,6 +213,16 @@ pub enum RegistryMetadataLicenseVariant {
     Full(RegistryMetadataLicense),
 }
 
+impl RegistryMetadataLicenseVariant {
+    #[must_use]
+    pub fn kind(&self) -> &str {
+        match self {
+            Self::String(s) => s.as_ref(),
+            Self::Full(f) => f.kind.as_ref(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct RegistryMetadataHuman {
     pub name: String,
@@ -137,3 +301,62 @@ impl RegistryMetadataRepositoryVariant {
         }
     }
 }
+
+/*
+    Some older versions were published with fields that are now objects
+    as arrays or strings, such as `"engines": ["node >= 0.4"]`, and a single
+    one of those must not make the whole metadata document fail to parse.
+
+    Anything that is not an object is treated as empty, and any entries
+    with values that do not have the expected type are skipped.
+*/
+fn deserialize_lenient_map<'de, D, T>(deserializer: D) -> Result<HashMap<String, T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: DeserializeOwned,
+{
+    let Value::Object(map) = Value::deserialize(deserializer)? else {
+        return Ok(HashMap::new());
+    };
+    Ok(map
+        .into_iter()
+        .filter_map(|(key, value)| Some((key, serde_json::from_value(value).ok()?)))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lenient_engines_and_peers() {
+        let metadata = serde_json::from_str::<RegistryMetadataAbbreviated>(
+            r#"{
+                "name": "legacy",
+                "dist-tags": { "latest": "1.0.0" },
+                "versions": {
+                    "0.1.0": { "name": "legacy", "version": "0.1.0", "engines": ["node >= 0.4"] },
+                    "0.2.0": { "name": "legacy", "version": "0.2.0", "engines": "node >= 0.6" },
+                    "1.0.0": {
+                        "name": "legacy",
+                        "version": "1.0.0",
+                        "engines": { "node": ">=18", "npm": 9 },
+                        "peerDependencies": { "react": "^18.0.0", "broken": null },
+                        "peerDependenciesMeta": { "react": { "optional": true }, "broken": true }
+                    }
+                }
+            }"#,
+        )
+        .unwrap();
+
+        assert!(metadata.versions["0.1.0"].engines.is_empty());
+        assert!(metadata.versions["0.2.0"].engines.is_empty());
+
+        let latest = &metadata.versions["1.0.0"];
+        assert_eq!(latest.engines.len(), 1);
+        assert_eq!(latest.engines["node"], ">=18");
+        assert_eq!(latest.peer_dependencies.len(), 1);
+        assert!(latest.is_optional_peer("react"));
+        assert!(!latest.is_optional_peer("broken"));
+    }
+}
@@ -0,0 +1,32 @@
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SearchResults {
+    #[serde(default)]
+    pub objects: Vec<SearchResult>,
+    #[serde(default)]
+    pub total: u64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SearchResult {
+    pub package: SearchResultPackage,
+    #[serde(default)]
+    pub downloads: Option<SearchResultDownloads>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SearchResultPackage {
+    pub name: String,
+    pub version: String,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SearchResultDownloads {
+    #[serde(default)]
+    pub weekly: u64,
+    #[serde(default)]
+    pub monthly: u64,
+}
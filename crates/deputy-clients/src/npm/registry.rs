@@ -0,0 +1,75 @@
+use super::consts::BASE_URL_REGISTRY;
+
+/**
+    A registry to fetch npm packages from, along with its authorization, if any.
+
+    Defaults to the public npm registry, without any authorization.
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NpmRegistry {
+    url: String,
+    authorization: Option<String>,
+}
+
+impl NpmRegistry {
+    #[must_use]
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            authorization: None,
+        }
+    }
+
+    /**
+        Authorizes requests using a bearer token, set using
+        `_authToken` for the registry in an `.npmrc` file.
+    */
+    #[must_use]
+    pub fn with_auth_token(mut self, token: impl AsRef<str>) -> Self {
+        self.authorization = Some(format!("Bearer {}", token.as_ref()));
+        self
+    }
+
+    /**
+        Authorizes requests using basic authentication, with credentials
+        that are already base64-encoded, set using `_auth` in an `.npmrc` file.
+    */
+    #[must_use]
+    pub fn with_basic_auth(mut self, credentials: impl AsRef<str>) -> Self {
+        self.authorization = Some(format!("Basic {}", credentials.as_ref()));
+        self
+    }
+
+    #[must_use]
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /**
+        Checks if this is the public npm registry, regardless of authorization.
+    */
+    #[must_use]
+    pub fn is_default(&self) -> bool {
+        self.url.trim_end_matches('/') == BASE_URL_REGISTRY.trim_end_matches('/')
+    }
+
+    pub(super) fn authorization(&self) -> Option<String> {
+        self.authorization.clone()
+    }
+
+    /**
+        Returns the URL for the metadata document of a package in this registry.
+
+        Scoped package names have their slash encoded, same as npm itself.
+    */
+    pub(super) fn package_url(&self, name: &str) -> String {
+        let name_low = name.to_ascii_lowercase().replace('/', "%2f");
+        format!("{}/{name_low}", self.url.trim_end_matches('/'))
+    }
+}
+
+impl Default for NpmRegistry {
+    fn default() -> Self {
+        Self::new(BASE_URL_REGISTRY)
+    }
+}
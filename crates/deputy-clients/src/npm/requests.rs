@@ -1,14 +1,33 @@
 use tracing::debug;
 
-use super::consts::BASE_URL_REGISTRY;
-use super::models::RegistryMetadata;
-use super::{NpmClient, RequestResult};
+use url::form_urlencoded::byte_serialize;
+
+use super::consts::{BASE_URL_SEARCH, QUERY_STRING_SEARCH};
+use super::models::{
+    RegistryMetadata, RegistryMetadataAbbreviated, RegistryMetadataPackageVersion, SearchResults,
+};
+use super::{NpmClient, NpmRegistry, RequestError, RequestResult};
 
 impl NpmClient {
+    /**
+        Fetches the full metadata document for a package from the given registry.
+
+        This document includes package information such as the description
+        and repository, but can be very large for packages with many versions.
+        Prefer [`get_registry_metadata_abbreviated`] when only versions are needed.
+
+        ### Caching
+
+        This method caches its result for the given `name` and
+        registry with a duration of *one hour or longer*.
+    */
     #[allow(clippy::missing_errors_doc)]
-    pub async fn get_registry_metadata(&self, name: &str) -> RequestResult<RegistryMetadata> {
-        let name_low = name.to_ascii_lowercase();
-        let registry_url = format!("{BASE_URL_REGISTRY}/{name_low}");
+    pub async fn get_registry_metadata(
+        &self,
+        registry: &NpmRegistry,
+        name: &str,
+    ) -> RequestResult<RegistryMetadata> {
+        let registry_url = registry.package_url(name);
 
         let fut = async {
             debug!("Fetching npm package registry metadatas for '{name}'");
@@ -16,10 +35,9 @@ impl NpmClient {
             // NOTE: We make this inner scope so that
             // we can catch and emit all errors at once
             let inner = async {
-                let bytes = self.request_get(&registry_url).await?;
-                let text = String::from_utf8(bytes.clone())?;
+                let bytes = self.request_get_registry(registry, &registry_url).await?;
 
-                let mut meta = RegistryMetadata::try_from_json(&text)?;
+                let mut meta = serde_json::from_slice::<RegistryMetadata>(&bytes)?;
                 for (key, value) in &mut meta.versions {
                     value.version.clone_from(key);
                 }
@@ -38,4 +56,152 @@ impl NpmClient {
             .with_caching(registry_url.clone(), fut)
             .await
     }
+
+    /**
+        Fetches the abbreviated metadata document for a package from the given
+        registry, which only contains tags, versions, deprecations and engines.
+
+        This is the same document that package managers use during installs,
+        and is much smaller than the full metadata document. It is also
+        deserialized while being received, skipping any unused fields.
+
+        ### Caching
+
+        This method caches its result for the given `name` and
+        registry with a duration of *one hour or longer*.
+    */
+    #[allow(clippy::missing_errors_doc)]
+    pub async fn get_registry_metadata_abbreviated(
+        &self,
+        registry: &NpmRegistry,
+        name: &str,
+    ) -> RequestResult<RegistryMetadataAbbreviated> {
+        let registry_url = registry.package_url(name);
+
+        let fut = async {
+            debug!("Fetching abbreviated npm package registry metadatas for '{name}'");
+
+            // NOTE: We make this inner scope so that
+            // we can catch and emit all errors at once
+            let inner = async {
+                let mut meta: RegistryMetadataAbbreviated = self
+                    .request_get_registry_abbreviated(registry, &registry_url)
+                    .await?;
+
+                for (key, value) in &mut meta.versions {
+                    value.version.clone_from(key);
+                }
+
+                Ok(meta)
+            }
+            .await;
+
+            NpmClient::emit_result(&inner);
+
+            inner
+        };
+
+        self.cache
+            .registry_metadatas_abbreviated
+            .with_caching(registry_url.clone(), fut)
+            .await
+    }
+
+    /**
+        Fetches the metadata document for a single version of a package from
+        the given registry, which contains the same information about that
+        version as the full metadata document, without any other versions.
+
+        ### Caching
+
+        This method caches its result for the given `name`, `version`
+        and registry with a duration of *one hour or longer*.
+    */
+    #[allow(clippy::missing_errors_doc)]
+    pub async fn get_registry_version(
+        &self,
+        registry: &NpmRegistry,
+        name: &str,
+        version: &str,
+    ) -> RequestResult<RegistryMetadataPackageVersion> {
+        let registry_url = format!("{}/{version}", registry.package_url(name));
+
+        let fut = async {
+            debug!("Fetching npm package registry metadata for '{name}@{version}'");
+
+            // NOTE: We make this inner scope so that
+            // we can catch and emit all errors at once
+            let inner = async {
+                let bytes = self.request_get_registry(registry, &registry_url).await?;
+                Ok(serde_json::from_slice::<RegistryMetadataPackageVersion>(
+                    &bytes,
+                )?)
+            }
+            .await;
+
+            NpmClient::emit_result(&inner);
+
+            inner
+        };
+
+        self.cache
+            .registry_versions
+            .with_caching(registry_url.clone(), fut)
+            .await
+    }
+
+    /**
+        Searches the npm registry for packages matching the given query.
+
+        This allows us to search for and list packages for autocomplete,
+        including scoped packages that are not in the bundled list.
+
+        ### Caching
+
+        This method caches its result for the given `query` with a
+        duration of *one hour or longer*. For more up-to-date info
+        on versions of a package, please use [`get_registry_metadata_abbreviated`].
+
+        ### Debouncing
+
+        Searches are only sent after a short delay, and searches that are
+        superseded by a newer search during this delay are never sent, and
+        instead return an error that can be checked using `is_superseded_error`.
+    */
+    #[allow(clippy::missing_errors_doc)]
+    pub async fn search_packages(&self, query: &str) -> RequestResult<SearchResults> {
+        let search_query = query.trim().to_ascii_lowercase();
+        let search_url = format!(
+            "{BASE_URL_SEARCH}{QUERY_STRING_SEARCH}&text={}",
+            byte_serialize(search_query.as_bytes()).collect::<String>()
+        );
+
+        if let Some(cached) = self.cache.package_search.get(&search_url).await {
+            return cached;
+        }
+        if !self.debounce_search().await {
+            return Err(RequestError::Superseded);
+        }
+
+        let fut = async {
+            debug!("Searching npm packages for '{search_query}'");
+
+            // NOTE: We make this inner scope so that
+            // we can catch and emit all errors at once
+            let inner = async {
+                let bytes = self.request_get(&search_url).await?;
+                Ok(serde_json::from_slice::<SearchResults>(&bytes)?)
+            }
+            .await;
+
+            NpmClient::emit_result(&inner);
+
+            inner
+        };
+
+        self.cache
+            .package_search
+            .with_caching(search_url.clone(), fut)
+            .await
+    }
 }
@@ -50,6 +50,13 @@ impl<T: Clone + Send + Sync + 'static> RequestCacheMap<T> {
         self.map.invalidate_all();
     }
 
+    /**
+        Gets a cached value, if one exists, without running any request.
+    */
+    pub async fn get(&self, key: &str) -> Option<T> {
+        self.map.get(key).await
+    }
+
     /**
         Run a future with caching and single concurrency limit.
 
@@ -58,6 +58,8 @@ pub enum RequestError {
     Client(String),
     #[error("json error - {0}")]
     Json(String),
+    #[error("superseded by a newer request")]
+    Superseded,
     #[error("unknown error")]
     #[default]
     Unknown,
@@ -72,6 +74,10 @@ impl RequestError {
         }
     }
 
+    pub fn is_superseded_error(&self) -> bool {
+        matches!(self, RequestError::Superseded)
+    }
+
     pub fn is_rate_limit_error(&self) -> bool {
         if let RequestError::Response(e) = self {
             if e.status == StatusCode::TOO_MANY_REQUESTS {
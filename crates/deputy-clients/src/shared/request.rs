@@ -1,11 +1,18 @@
 #![allow(dead_code)]
 
-use std::{collections::HashMap, str::FromStr, sync::LazyLock};
+use std::{
+    collections::HashMap,
+    io::{BufReader, Cursor, Read},
+    str::FromStr,
+    sync::LazyLock,
+};
 
 use reqwest::{
-    Client, Method,
+    Client, Method, Response,
     header::{HeaderName, HeaderValue, USER_AGENT},
 };
+use serde::de::DeserializeOwned;
+use tokio::{sync::mpsc, task};
 use tracing::trace;
 use url::Url;
 
@@ -20,6 +27,8 @@ const USER_AGENT_VALUE: &str = concat!(
     " )"
 );
 
+const JSON_CHUNKS_BUFFERED: usize = 16;
+
 static CLIENT: LazyLock<Client> = LazyLock::new(Client::new);
 
 #[derive(Clone, Debug)]
@@ -77,6 +86,60 @@ impl Request {
     }
 
     pub async fn send(self) -> RequestResult<Vec<u8>> {
+        let response = self.execute().await?;
+        let body = response
+            .bytes()
+            .await
+            .map(|b| b.to_vec())
+            .map_err(|e| RequestError::Client(e.to_string()))?;
+        Ok(body)
+    }
+
+    /**
+        Sends the request and deserializes the response body as JSON while it
+        is still being received, without ever buffering the full body.
+
+        This should be preferred over [`Request::send`] for large documents
+        where only some of the fields are needed, since any fields that are
+        not part of `T` are skipped over instead of being kept in memory.
+    */
+    pub async fn send_json<T>(self) -> RequestResult<T>
+    where
+        T: DeserializeOwned + Send + 'static,
+    {
+        let mut response = self.execute().await?;
+
+        let (sender, receiver) = mpsc::channel(JSON_CHUNKS_BUFFERED);
+        let parser = task::spawn_blocking(move || {
+            serde_json::from_reader::<_, T>(BufReader::new(ChunkReader::new(receiver)))
+        });
+
+        let mut received = Ok(());
+        loop {
+            match response.chunk().await {
+                Ok(Some(chunk)) => {
+                    // The parser stops receiving early if the document is invalid
+                    if sender.send(chunk.to_vec()).await.is_err() {
+                        break;
+                    }
+                }
+                Ok(None) => break,
+                Err(e) => {
+                    received = Err(RequestError::from(e));
+                    break;
+                }
+            }
+        }
+        drop(sender);
+
+        let parsed = parser
+            .await
+            .map_err(|e| RequestError::Client(e.to_string()))?;
+        received?;
+        Ok(parsed?)
+    }
+
+    async fn execute(self) -> RequestResult<Response> {
         let mut request = reqwest::Request::new(self.method, Url::parse(&self.url)?);
 
         // Set headers
@@ -99,15 +162,15 @@ impl Request {
         trace!("Sending request:\n{request:#?}");
         let response = CLIENT.execute(request).await?;
         trace!("Got response:\n{response:#?}");
-        let status = response.status();
-        let body = response
-            .bytes()
-            .await
-            .map(|b| b.to_vec())
-            .map_err(|e| RequestError::Client(e.to_string()))?;
 
         // Return Err enum if the request has a non-200 status code
+        let status = response.status();
         if status.is_client_error() || status.is_server_error() {
+            let body = response
+                .bytes()
+                .await
+                .map(|b| b.to_vec())
+                .map_err(|e| RequestError::Client(e.to_string()))?;
             let e = ResponseError {
                 status,
                 bytes: body,
@@ -115,6 +178,39 @@ impl Request {
             return Err(e.into());
         }
 
-        Ok(body)
+        Ok(response)
+    }
+}
+
+/*
+    Blocking reader over the chunks of a response body, which are
+    sent from an async task while the response is being received
+*/
+struct ChunkReader {
+    receiver: mpsc::Receiver<Vec<u8>>,
+    current: Cursor<Vec<u8>>,
+}
+
+impl ChunkReader {
+    fn new(receiver: mpsc::Receiver<Vec<u8>>) -> Self {
+        Self {
+            receiver,
+            current: Cursor::new(Vec::new()),
+        }
+    }
+}
+
+impl Read for ChunkReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        loop {
+            let read = self.current.read(buf)?;
+            if read > 0 || buf.is_empty() {
+                return Ok(read);
+            }
+            let Some(chunk) = self.receiver.blocking_recv() else {
+                return Ok(0);
+            };
+            self.current = Cursor::new(chunk);
+        }
     }
 }
@@ -1,5 +1,7 @@
 use std::{collections::HashMap, str::FromStr};
 
+use glob::{MatchOptions, Pattern};
+
 use async_language_server::{
     lsp_types::Position,
     server::Document,
@@ -9,6 +11,8 @@ use async_language_server::{
 
 use super::utils::{table_key_parts, unquote};
 
+pub use super::shared::IncompleteDependency as CargoIncompleteDependency;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum DependencyKind {
     Dependency,
@@ -114,6 +118,46 @@ pub fn find_all_dependencies(doc: &Document) -> Vec<TsNode> {
     deps
 }
 
+#[must_use]
+pub fn find_all_workspace_dependencies(doc: &Document) -> Vec<TsNode<'_>> {
+    find_all_dependencies(doc)
+        .into_iter()
+        .filter(|node| is_workspace_dependency(doc, *node))
+        .collect()
+}
+
+/**
+    Checks if the given dependency pair or table is declared
+    in `[workspace.dependencies]`, meaning it may be inherited
+    by workspace members using `workspace = true`.
+*/
+#[must_use]
+pub fn is_workspace_dependency(doc: &Document, pair_or_table: TsNode) -> bool {
+    let table = if pair_or_table.kind() == "pair" {
+        pair_or_table.parent()
+    } else {
+        Some(pair_or_table)
+    };
+    table.is_some_and(|t| {
+        table_key_parts(doc, t)
+            .first()
+            .is_some_and(|p| p == "workspace")
+    })
+}
+
+/**
+    Finds the kind of dependency table that the given
+    dependency pair or table is declared in.
+*/
+#[must_use]
+pub fn parse_dependency_kind(doc: &Document, pair_or_table: TsNode) -> Option<DependencyKind> {
+    if pair_or_table.kind() == "pair" {
+        check_dependencies_table_multi(doc, pair_or_table.parent()?)
+    } else {
+        check_dependencies_table_single(doc, pair_or_table).map(|(kind, _)| kind)
+    }
+}
+
 #[must_use]
 pub fn find_dependency_at(doc: &Document, pos: Position) -> Option<TsNode> {
     let node = doc.node_at_position(pos)?; // either the key or value
@@ -134,13 +178,120 @@ pub fn find_dependency_at(doc: &Document, pos: Position) -> Option<TsNode> {
     }
 }
 
+/**
+    Finds a dependency that has only had its key typed out so far, such as a
+    lone `ser` in a `[dependencies]` table, which can not be parsed as a pair.
+*/
+#[must_use]
+pub fn find_incomplete_dependency_at(
+    doc: &Document,
+    pos: Position,
+) -> Option<CargoIncompleteDependency<'_>> {
+    let incomplete = CargoIncompleteDependency::at(doc, pos, &["bare_key", "quoted_key"])?;
+
+    // Lone keys may end up either inside of the table or right after it
+    let error = incomplete.error;
+    let table = error
+        .parent()
+        .filter(|p| p.kind() == "table")
+        .or_else(|| error.prev_named_sibling().filter(|s| s.kind() == "table"))?;
+    check_dependencies_table_multi(doc, table)?;
+
+    Some(incomplete)
+}
+
+/**
+    Finds the key of a dependency, as it was written in the manifest.
+
+    This is the name that the dependency will be referenced by, which
+    differs from the package name if the dependency has been renamed.
+*/
+#[must_use]
+pub fn parse_dependency_key(pair_or_table: TsNode<'_>) -> Option<TsNode<'_>> {
+    if pair_or_table.kind() == "pair" {
+        pair_or_table.named_child(0)
+    } else if pair_or_table.kind() == "table" {
+        let key = pair_or_table.named_child(0)?;
+        key.named_children(&mut key.walk()).last()
+    } else {
+        None
+    }
+}
+
+/**
+    Finds the names of all fields in a dependency specification, such as
+    `version`, `features`, or `path`, in the order they were written in.
+
+    Returns an empty list for dependencies that are specified
+    using a single version string, such as `name = "1.0"`.
+*/
+#[must_use]
+pub fn parse_dependency_fields(doc: &Document, pair_or_table: TsNode) -> Vec<String> {
+    let container = if pair_or_table.kind() == "pair" {
+        pair_or_table
+            .named_child(1)
+            .filter(|value| value.kind() == "inline_table")
+    } else if pair_or_table.kind() == "table" {
+        Some(pair_or_table)
+    } else {
+        None
+    };
+
+    let mut fields = Vec::new();
+    if let Some(container) = container {
+        let mut cursor = container.walk();
+        for child in container.children(&mut cursor) {
+            if let Some(key) = child.named_child(0).filter(|_| child.kind() == "pair") {
+                fields.push(unquote(doc.node_text(key)));
+            }
+        }
+    }
+    fields
+}
+
+/**
+    Finds the value of a single field in a dependency specification,
+    such as the `"../path"` string node in `path = "../path"`.
+
+    Always returns `None` for dependencies that are specified
+    using a single version string, such as `name = "1.0"`.
+*/
+#[must_use]
+pub fn parse_dependency_field<'tree>(
+    doc: &Document,
+    pair_or_table: TsNode<'tree>,
+    field: &str,
+) -> Option<TsNode<'tree>> {
+    let container = if pair_or_table.kind() == "pair" {
+        pair_or_table
+            .named_child(1)
+            .filter(|value| value.kind() == "inline_table")?
+    } else if pair_or_table.kind() == "table" {
+        pair_or_table
+    } else {
+        return None;
+    };
+
+    let mut cursor = container.walk();
+    container
+        .children(&mut cursor)
+        .filter(|child| child.kind() == "pair")
+        .find(|child| {
+            child
+                .named_child(0)
+                .is_some_and(|key| unquote(doc.node_text(key)) == field)
+        })
+        .and_then(|pair| pair.named_child(1))
+}
+
 #[must_use]
 pub fn parse_dependency<'tree>(
     doc: &Document,
     pair_or_table: TsNode<'tree>,
 ) -> Option<CargoDependency<'tree>> {
+    let key = parse_dependency_key(pair_or_table)?;
     if pair_or_table.kind() == "pair" {
-        let mut name = pair_or_table.named_child(0)?;
+        let mut name = key;
         let value = pair_or_table.named_child(1)?;
 
         // version is either `name = "version"` or `name = { version = "version" }`
@@ -170,14 +321,14 @@ pub fn parse_dependency<'tree>(
         }
 
         Some(CargoDependency {
+            key,
             name,
             version: version?,
             features,
         })
     } else if pair_or_table.kind() == "table" {
         // alias is last part in [dependencies."abcdef"."ghijkl".name]
-        let key = pair_or_table.named_child(0)?;
-        let mut name = key.named_children(&mut key.walk()).last()?;
+        let mut name = key;
 
         let mut pairs = HashMap::new();
         let mut cursor = pair_or_table.walk();
@@ -200,6 +351,7 @@ pub fn parse_dependency<'tree>(
         }
 
         Some(CargoDependency {
+            key,
             name,
             version: version?,
             features,
@@ -212,6 +364,7 @@ pub fn parse_dependency<'tree>(
 #[allow(dead_code)]
 #[derive(Debug, Clone, Copy)]
 pub struct CargoDependency<'tree> {
+    pub key: TsNode<'tree>,
     pub name: TsNode<'tree>,
     pub version: TsNode<'tree>,
     pub features: Option<TsNode<'tree>>,
@@ -239,3 +392,165 @@ impl CargoDependency<'_> {
         nodes
     }
 }
+
+/**
+    The `members` and `exclude` globs from a `[workspace]` table.
+
+    Paths are relative to the directory containing the workspace
+    root manifest, and use forward slashes as separators.
+*/
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CargoWorkspaceGlobs {
+    pub members: Vec<String>,
+    pub exclude: Vec<String>,
+}
+
+impl CargoWorkspaceGlobs {
+    /**
+        Checks if the given path, relative to the workspace root,
+        is matched by any of the member globs and not excluded.
+    */
+    #[must_use]
+    pub fn is_member(&self, relative_path: &str) -> bool {
+        let path = normalize_workspace_path(relative_path);
+        self.members.iter().any(|m| glob_matches(m, &path)) && !self.is_excluded(&path)
+    }
+
+    /**
+        Checks if the given path, relative to the workspace root, is excluded.
+
+        Same as Cargo, an excluded directory also excludes
+        any packages that are nested further inside of it.
+    */
+    #[must_use]
+    pub fn is_excluded(&self, relative_path: &str) -> bool {
+        let path = normalize_workspace_path(relative_path);
+        self.exclude.iter().any(|e| {
+            let e = normalize_workspace_path(e);
+            glob_matches(&e, &path)
+                || path
+                    .strip_prefix(e.as_str())
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+        })
+    }
+}
+
+fn normalize_workspace_path(path: &str) -> String {
+    let path = path.trim().replace('\\', "/");
+    path.trim_start_matches("./")
+        .trim_end_matches('/')
+        .to_string()
+}
+
+fn glob_matches(pattern: &str, path: &str) -> bool {
+    let options = MatchOptions {
+        require_literal_separator: true,
+        ..MatchOptions::new()
+    };
+    Pattern::new(&normalize_workspace_path(pattern))
+        .is_ok_and(|pattern| pattern.matches_with(path, options))
+}
+
+#[must_use]
+pub fn find_workspace_globs(doc: &Document) -> Option<CargoWorkspaceGlobs> {
+    let root = doc.node_at_root()?;
+
+    let mut cursor = root.walk();
+    let table = root
+        .children(&mut cursor)
+        .find(|top_level| table_key_parts(doc, *top_level) == ["workspace"])?;
+
+    let mut globs = CargoWorkspaceGlobs::default();
+    let mut table_cursor = table.walk();
+    for pair in table.children(&mut table_cursor) {
+        if pair.kind() != "pair" {
+            continue;
+        }
+        let (Some(key), Some(value)) = (pair.named_child(0), pair.named_child(1)) else {
+            continue;
+        };
+
+        // members = ["crates/*"] or exclude = ["crates/ignored"]
+        let list = match unquote(doc.node_text(key)).as_str() {
+            "members" => &mut globs.members,
+            "exclude" => &mut globs.exclude,
+            _ => continue,
+        };
+
+        let mut value_cursor = value.walk();
+        for item in value.children(&mut value_cursor) {
+            if item.kind() == "string" {
+                list.push(unquote(doc.node_text(item)));
+            }
+        }
+    }
+
+    Some(globs)
+}
+
+/**
+    Finds the `[package]` table in a manifest, if it has one.
+*/
+#[must_use]
+pub fn find_package_table(doc: &Document) -> Option<TsNode<'_>> {
+    let root = doc.node_at_root()?;
+
+    let mut cursor = root.walk();
+    root.children(&mut cursor)
+        .find(|top_level| table_key_parts(doc, *top_level) == ["package"])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn globs(members: &[&str], exclude: &[&str]) -> CargoWorkspaceGlobs {
+        CargoWorkspaceGlobs {
+            members: members.iter().map(ToString::to_string).collect(),
+            exclude: exclude.iter().map(ToString::to_string).collect(),
+        }
+    }
+
+    #[test]
+    fn members() {
+        let globs = globs(&["crates/*", "tools/cli", "./examples/basic/"], &[]);
+        assert!(globs.is_member("crates/app"));
+        assert!(globs.is_member("./crates/app/"));
+        assert!(globs.is_member("crates\\app"));
+        assert!(globs.is_member("tools/cli"));
+        assert!(globs.is_member("examples/basic"));
+        assert!(!globs.is_member("crates"));
+        assert!(!globs.is_member("crates/app/nested"));
+        assert!(!globs.is_member("tools/other"));
+        assert!(!globs.is_member(""));
+    }
+
+    #[test]
+    fn recursive_members() {
+        let globs = globs(&["crates/**"], &[]);
+        assert!(globs.is_member("crates/app"));
+        assert!(globs.is_member("crates/app/nested"));
+        assert!(!globs.is_member("tools/app"));
+    }
+
+    #[test]
+    fn exclude() {
+        let globs = globs(&["crates/*"], &["crates/ignored", "crates/legacy-*"]);
+        assert!(globs.is_member("crates/app"));
+        assert!(!globs.is_member("crates/ignored"));
+        assert!(!globs.is_member("crates/legacy-cli"));
+        assert!(globs.is_excluded("crates/ignored"));
+        assert!(globs.is_excluded("crates/legacy-cli"));
+        assert!(!globs.is_excluded("crates/app"));
+    }
+
+    #[test]
+    fn exclude_nested() {
+        let globs = globs(&["crates/*", "crates/vendor/*"], &["crates/vendor/"]);
+        assert!(globs.is_excluded("crates/vendor"));
+        assert!(globs.is_excluded("crates/vendor/serde"));
+        assert!(!globs.is_excluded("crates/vendored"));
+        assert!(!globs.is_member("crates/vendor/serde"));
+        assert!(globs.is_member("crates/vendored"));
+    }
+}
@@ -7,12 +7,32 @@ use async_language_server::{
 
 use super::utils::unquote;
 
+pub use super::shared::IncompleteDependency as NpmIncompleteDependency;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum DependencyKind {
     Dependency,
     DevDependency,
     PeerDependency,
     OptionalDependency,
+    Override,
+    Resolution,
+    PnpmOverride,
+}
+
+impl DependencyKind {
+    /**
+        Checks if this kind of dependency overrides the versions of other
+        dependencies - using npm `overrides`, yarn `resolutions` or `pnpm.overrides` -
+        instead of being a dependency of the package itself.
+    */
+    #[must_use]
+    pub fn is_override(self) -> bool {
+        matches!(
+            self,
+            DependencyKind::Override | DependencyKind::Resolution | DependencyKind::PnpmOverride
+        )
+    }
 }
 
 impl FromStr for DependencyKind {
@@ -23,11 +43,93 @@ impl FromStr for DependencyKind {
             "devDependencies" => Ok(DependencyKind::DevDependency),
             "peerDependencies" => Ok(DependencyKind::PeerDependency),
             "optionalDependencies" => Ok(DependencyKind::OptionalDependency),
+            "overrides" => Ok(DependencyKind::Override),
+            "resolutions" => Ok(DependencyKind::Resolution),
             _ => Err(()),
         }
     }
 }
 
+/*
+    Finds the kind of dependency section that the given pair is, which is
+    either a top-level pair such as `"dependencies"`, or `"overrides"` in pnpm:
+
+    ```json
+    {
+        "pnpm": {
+            "overrides": { ... }
+        }
+    }
+    ```
+*/
+fn section_kind(doc: &Document, section: TsNode) -> Option<DependencyKind> {
+    let key = unquote(doc.node_text(section.child_by_field_name("key")?));
+    if is_top_level(section) {
+        return DependencyKind::from_str(&key).ok();
+    }
+
+    let pnpm = parent_pair(section).filter(|p| is_top_level(*p))?;
+    let pnpm_key = unquote(doc.node_text(pnpm.child_by_field_name("key")?));
+    (pnpm_key == "pnpm" && key == "overrides").then_some(DependencyKind::PnpmOverride)
+}
+
+/*
+    Finds the kind of section that the given dependency pair belongs to.
+
+    Only npm `overrides` may be nested, any other dependency must be directly in its section:
+
+    ```json
+    "overrides": {
+        "react-dom": {
+            ".": "18.2.0",
+            "scheduler": "0.23.0"
+        }
+    }
+    ```
+*/
+fn find_section(doc: &Document, pair: TsNode) -> Option<DependencyKind> {
+    let mut current = pair;
+    loop {
+        let section = parent_pair(current)?;
+        if let Some(kind) = section_kind(doc, section) {
+            let is_direct = current == pair;
+            return (is_direct || kind == DependencyKind::Override).then_some(kind);
+        }
+        current = section;
+    }
+}
+
+fn is_top_level(pair: TsNode) -> bool {
+    pair.parent()
+        .and_then(|object| object.parent())
+        .is_some_and(|root| root.kind() == "document")
+}
+
+fn parent_pair(pair: TsNode) -> Option<TsNode> {
+    pair.parent()
+        .filter(|p| p.kind() == "object")?
+        .parent()
+        .filter(|p| p.kind() == "pair")
+}
+
+/*
+    Overrides only apply to dependencies with string values - pairs
+    with object values are nested npm overrides, containing more pairs.
+*/
+fn collect_overrides<'tree>(object: TsNode<'tree>, deps: &mut Vec<TsNode<'tree>>) {
+    let mut cursor = object.walk();
+    for pair in object.children(&mut cursor) {
+        if pair.kind() != "pair" {
+            continue;
+        }
+        match pair.child_by_field_name("value") {
+            Some(value) if value.kind() == "string" => deps.push(pair),
+            Some(value) if value.kind() == "object" => collect_overrides(value, deps),
+            _ => {}
+        }
+    }
+}
+
 #[allow(clippy::missing_panics_doc)]
 #[must_use]
 pub fn find_all_dependencies(doc: &Document) -> Vec<TsNode> {
@@ -48,9 +150,24 @@ pub fn find_all_dependencies(doc: &Document) -> Vec<TsNode> {
             let val = top_level.child_by_field_name("value").expect("valid pair");
 
             let key_str = unquote(doc.node_text(key));
-            let Ok(_kind) = DependencyKind::from_str(&key_str) else {
+            if key_str == "pnpm" && val.kind() == "object" {
+                let mut pnpm_cursor = val.walk();
+                for pnpm_pair in val.children(&mut pnpm_cursor) {
+                    if section_kind(doc, pnpm_pair) == Some(DependencyKind::PnpmOverride) {
+                        let overrides = pnpm_pair.child_by_field_name("value").expect("valid pair");
+                        collect_overrides(overrides, &mut deps);
+                    }
+                }
+                continue;
+            }
+
+            let Ok(kind) = DependencyKind::from_str(&key_str) else {
                 continue;
             };
+            if kind.is_override() {
+                collect_overrides(val, &mut deps);
+                continue;
+            }
 
             let mut val_cursor = val.walk();
             for dependency in val.children(&mut val_cursor) {
@@ -64,42 +181,210 @@ pub fn find_all_dependencies(doc: &Document) -> Vec<TsNode> {
     deps
 }
 
+/**
+    Finds the top-level pair for the given kind of dependency section, such as
+    `"devDependencies": { ... }`, which is useful for adding new dependencies to it.
+
+    Always returns `None` for `pnpm.overrides`, which is not a top-level section.
+*/
+#[must_use]
+pub fn find_dependency_section(doc: &Document, kind: DependencyKind) -> Option<TsNode<'_>> {
+    let root = doc.node_at_root()?.named_child(0)?;
+
+    let mut cursor = root.walk();
+    root.children(&mut cursor).find(|pair| {
+        pair.kind() == "pair"
+            && pair.child_by_field_name("key").is_some_and(|key| {
+                DependencyKind::from_str(&unquote(doc.node_text(key))) == Ok(kind)
+            })
+    })
+}
+
+/**
+    Finds the value of the top-level `packageManager` field used by Corepack,
+    such as `"pnpm@8.6.0"`, as long as it is a string.
+*/
+#[must_use]
+pub fn find_package_manager(doc: &Document) -> Option<TsNode<'_>> {
+    let root = doc.node_at_root()?.named_child(0)?;
+
+    let mut cursor = root.walk();
+    let pair = root.children(&mut cursor).find(|pair| {
+        pair.kind() == "pair"
+            && pair
+                .child_by_field_name("key")
+                .is_some_and(|key| unquote(doc.node_text(key)) == "packageManager")
+    })?;
+
+    pair.child_by_field_name("value")
+        .filter(|value| value.kind() == "string")
+}
+
 #[allow(clippy::missing_panics_doc)]
 #[must_use]
 pub fn find_dependency_at(doc: &Document, pos: Position) -> Option<TsNode> {
     let node = doc.node_at_position(pos)?; // either the key or value
     let pair = find_ancestor(node, |a| a.kind() == "pair")?; // "package": "spec"
 
-    let deps_obj = find_ancestor(pair, |a| a.kind() == "object")?;
-    let deps_pair = find_ancestor(deps_obj, |a| a.kind() == "pair")?;
-
-    let key = deps_pair.child_by_field_name("key").expect("valid pair");
-    let key_str = unquote(doc.node_text(key));
-    DependencyKind::from_str(&key_str).ok()?;
+    let kind = find_section(doc, pair)?;
+    if kind.is_override() {
+        pair.child_by_field_name("value")
+            .filter(|value| value.kind() == "string")?;
+    }
 
     Some(pair)
 }
 
+/**
+    Finds a dependency that has only had its name typed out so far, such as
+    a lone `"rea"` in a `"dependencies"` object, which can not be parsed as a pair.
+*/
+#[must_use]
+pub fn find_incomplete_dependency_at(
+    doc: &Document,
+    pos: Position,
+) -> Option<NpmIncompleteDependency<'_>> {
+    let incomplete = NpmIncompleteDependency::at(doc, pos, &["string", "string_content"])?;
+
+    let deps_obj = incomplete.error.parent().filter(|p| p.kind() == "object")?;
+    let deps_pair = deps_obj.parent().filter(|p| p.kind() == "pair")?;
+    section_kind(doc, deps_pair)?;
+
+    Some(incomplete)
+}
+
 #[must_use]
-pub fn parse_dependency(pair: TsNode) -> Option<NpmDependency> {
+pub fn parse_dependency<'tree>(
+    doc: &Document,
+    pair: TsNode<'tree>,
+) -> Option<NpmDependency<'tree>> {
+    let kind = find_section(doc, pair)?;
     Some(NpmDependency {
+        kind,
         name: pair.child_by_field_name("key")?,
         spec: pair.child_by_field_name("value")?,
     })
 }
 
+/*
+    Splits the key of an override into the package that it is scoped to,
+    if any, and the package that it overrides, without version selectors:
+
+    - `react` and `react@^18` override `react` everywhere
+    - `react-dom>scheduler` overrides `scheduler` in `react-dom` (pnpm)
+    - `react-dom/scheduler` overrides `scheduler` in `react-dom` (yarn)
+    - globs, such as a leading `**` before `scheduler`, are skipped (yarn)
+*/
+fn split_override_key(key: &str) -> (Option<&str>, &str) {
+    if let Some((parent, target)) = key.rsplit_once('>') {
+        let parent = parent.rsplit('>').next().unwrap_or(parent);
+        return (
+            Some(strip_version_selector(parent)),
+            strip_version_selector(target),
+        );
+    }
+
+    // Scoped package names contain a slash, so scopes are joined with the next segment
+    let mut names = Vec::new();
+    let mut scope_start = None;
+    let mut offset = 0;
+    for segment in key.split('/') {
+        let start = scope_start.take().unwrap_or(offset);
+        let end = offset + segment.len();
+        offset = end + 1;
+        if segment.starts_with('@') {
+            scope_start = Some(start);
+        } else if !segment.is_empty() && !segment.contains('*') {
+            names.push(&key[start..end]);
+        }
+    }
+
+    match names.as_slice() {
+        [] => (None, strip_version_selector(key)),
+        [target] => (None, strip_version_selector(target)),
+        [.., parent, target] => (
+            Some(strip_version_selector(parent)),
+            strip_version_selector(target),
+        ),
+    }
+}
+
+fn strip_version_selector(name: &str) -> &str {
+    // Scoped package names start with `@`, so skip it when looking for the version
+    match name.get(1..).and_then(|n| n.find('@')) {
+        Some(index) => &name[..=index],
+        None => name,
+    }
+}
+
 #[allow(dead_code)]
 #[derive(Debug, Clone, Copy)]
 pub struct NpmDependency<'tree> {
+    pub kind: DependencyKind,
     pub name: TsNode<'tree>,
     pub spec: TsNode<'tree>,
 }
 
 impl NpmDependency<'_> {
+    /**
+        Returns the name of the package and its spec, without quotes.
+
+        For overrides, the name is the package being overridden, without
+        any selectors - `react` for both `react@^18` and `react-dom>react`.
+    */
     #[must_use]
     pub fn text(&self, doc: &Document) -> (String, String) {
-        let name = doc.node_text(self.name);
-        let spec = doc.node_text(self.spec);
-        (unquote(name), unquote(spec))
+        let name = unquote(doc.node_text(self.name));
+        let spec = unquote(doc.node_text(self.spec));
+        if !self.kind.is_override() {
+            return (name, spec);
+        }
+
+        // The special `.` key overrides the package of the object it is in
+        let name = if name == "." {
+            self.name
+                .parent()
+                .and_then(parent_pair)
+                .and_then(|pair| pair.child_by_field_name("key"))
+                .map(|key| unquote(doc.node_text(key)))
+                .unwrap_or(name)
+        } else {
+            name
+        };
+        let (_, target) = split_override_key(&name);
+        (target.to_string(), spec)
+    }
+
+    /**
+        Returns the name of the package that this override is scoped to, if any,
+        meaning that it only applies to dependencies of that package.
+
+        Always returns `None` for dependencies that are not overrides.
+    */
+    #[must_use]
+    pub fn override_parent(&self, doc: &Document) -> Option<String> {
+        if !self.kind.is_override() {
+            return None;
+        }
+
+        let key = unquote(doc.node_text(self.name));
+        let (key_parent, _) = split_override_key(&key);
+        if let Some(parent) = key_parent.filter(|_| key != ".") {
+            return Some(parent.to_string());
+        }
+
+        // Nested npm overrides are scoped to the package of the object they are in
+        let mut pair = self.name.parent()?;
+        if key == "." {
+            pair = parent_pair(pair)?;
+        }
+        let enclosing = parent_pair(pair)?;
+        if section_kind(doc, enclosing).is_some() {
+            return None;
+        }
+
+        let enclosing_key = unquote(doc.node_text(enclosing.child_by_field_name("key")?));
+        let (_, parent) = split_override_key(&enclosing_key);
+        Some(parent.to_string())
     }
 }
@@ -3,8 +3,8 @@
 use async_language_server::{lsp_types::Position, server::Document, tree_sitter::Node as TsNode};
 
 pub use super::shared::{
-    TriDependency as RokitDependency, TriDependencySpecRanges as RokitDependencySpecRanges,
-    parse_dependency,
+    IncompleteDependency as RokitIncompleteDependency, TriDependency as RokitDependency,
+    TriDependencySpecRanges as RokitDependencySpecRanges, parse_dependency,
 };
 
 #[must_use]
@@ -16,3 +16,11 @@ pub fn find_all_dependencies(doc: &Document) -> Vec<TsNode> {
 pub fn find_dependency_at(doc: &Document, pos: Position) -> Option<TsNode> {
     super::shared::find_dependency_at(doc, pos, super::shared::TableNames::Rokit)
 }
+
+#[must_use]
+pub fn find_incomplete_dependency_at(
+    doc: &Document,
+    pos: Position,
+) -> Option<RokitIncompleteDependency<'_>> {
+    super::shared::find_incomplete_dependency_at(doc, pos, super::shared::TableNames::Rokit)
+}
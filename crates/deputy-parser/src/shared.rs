@@ -6,6 +6,8 @@ use async_language_server::{
     tree_sitter_utils::{find_ancestor, find_child},
 };
 
+use super::utils::unquote;
+
 #[derive(Debug, Clone, Copy)]
 pub enum TableNames {
     Rokit,
@@ -66,6 +68,79 @@ pub(super) fn find_dependency_at(
     Some(pair)
 }
 
+pub(super) fn find_incomplete_dependency_at(
+    doc: &Document,
+    pos: Position,
+    table_names: TableNames,
+) -> Option<IncompleteDependency<'_>> {
+    let incomplete = IncompleteDependency::at(doc, pos, &["bare_key"])?;
+
+    // Lone keys may end up either inside of the table or right after it
+    let error = incomplete.error;
+    let table = error
+        .parent()
+        .filter(|p| p.kind() == "table")
+        .or_else(|| error.prev_named_sibling().filter(|s| s.kind() == "table"))?;
+    let key = find_child(table, |c| c.kind() == "bare_key")?;
+    if !check_table_name(table_names, doc.node_text(key).as_str()) {
+        return None;
+    }
+
+    Some(incomplete)
+}
+
+/**
+    A dependency that has only had its key typed out so far, such as
+    a lone `ser` or `ser =` in a TOML table, or `"rea"` in a JSON object.
+
+    Tree-sitter can not parse these, and wraps them in an error node.
+*/
+#[derive(Debug, Clone, Copy)]
+pub struct IncompleteDependency<'tree> {
+    pub key: TsNode<'tree>,
+    pub error: TsNode<'tree>,
+}
+
+impl<'tree> IncompleteDependency<'tree> {
+    pub(super) fn at(doc: &'tree Document, pos: Position, key_kinds: &[&str]) -> Option<Self> {
+        let node = doc.node_at_position(pos)?;
+        let error = if node.is_error() {
+            node
+        } else {
+            node.parent().filter(TsNode::is_error)?
+        };
+        let key = find_child(error, |c| key_kinds.contains(&c.kind()))?;
+        Some(Self { key, error })
+    }
+
+    #[must_use]
+    pub fn text(&self, doc: &Document) -> String {
+        unquote(doc.node_text(self.key))
+    }
+
+    /**
+        The range of the key and anything typed after it, such
+        as a trailing `=`, which a full entry should replace.
+    */
+    #[must_use]
+    pub fn range(&self) -> TsRange {
+        let mut range = self.key.range();
+
+        // Unterminated strings have their opening quote outside of the key
+        if let Some(quote) = self.key.prev_sibling().filter(|s| s.kind() == "\"") {
+            range.start_byte = quote.start_byte();
+            range.start_point = quote.start_position();
+        }
+
+        let mut cursor = self.error.walk();
+        if let Some(last) = self.error.children(&mut cursor).last() {
+            range.end_byte = last.end_byte();
+            range.end_point = last.end_position();
+        }
+        range
+    }
+}
+
 #[must_use]
 pub fn parse_dependency(pair: TsNode) -> Option<TriDependency> {
     Some(TriDependency {
@@ -0,0 +1,152 @@
+/*
+    Precomputes static indexes for the bundled lists of top packages,
+    so that they do not need to be parsed and bucketed at runtime.
+
+    The text files in `assets` are the source of truth - this script
+    generates Rust source code with `StaticIndex` values from them,
+    which is then included in the respective `constants` modules.
+*/
+
+use std::{
+    collections::{BTreeMap, HashSet},
+    env,
+    fmt::Write as _,
+    fs,
+    path::Path,
+};
+
+struct Package {
+    name: String,
+    popularity: u64,
+    description: String,
+}
+
+fn main() {
+    let manifest_dir = env::var("CARGO_MANIFEST_DIR").expect("missing CARGO_MANIFEST_DIR");
+    let out_dir = env::var("OUT_DIR").expect("missing OUT_DIR");
+    let assets = Path::new(&manifest_dir).join("assets");
+
+    generate(
+        &assets.join("top-crates-io-packages.txt"),
+        &Path::new(&out_dir).join("top_crates_io_packages.rs"),
+        "TOP_CRATES_IO_PACKAGES",
+        parse_crates_io_packages,
+    );
+    generate(
+        &assets.join("top-npm-packages.txt"),
+        &Path::new(&out_dir).join("top_npm_packages.rs"),
+        "TOP_NPM_PACKAGES",
+        parse_npm_packages,
+    );
+}
+
+fn generate(source: &Path, target: &Path, static_name: &str, parse: fn(&str) -> Vec<Package>) {
+    println!("cargo:rerun-if-changed={}", source.display());
+
+    let contents = fs::read_to_string(source)
+        .unwrap_or_else(|e| panic!("failed to read {}: {e}", source.display()));
+
+    // Deduplicate the same way that CompletionMap does, keeping the first occurrence
+    let mut seen = HashSet::new();
+    let packages = parse(&contents)
+        .into_iter()
+        .filter(|p| seen.insert(p.name.clone()))
+        .collect::<Vec<_>>();
+
+    // Bucket package indices by their lowercase one and two character prefixes
+    let mut prefixes = BTreeMap::<String, Vec<usize>>::new();
+    for (index, package) in packages.iter().enumerate() {
+        let lower = package.name.to_ascii_lowercase();
+        let mut chars = lower.chars();
+        let Some(first) = chars.next() else {
+            continue;
+        };
+        prefixes.entry(first.to_string()).or_default().push(index);
+        if let Some(second) = chars.next() {
+            prefixes
+                .entry(format!("{first}{second}"))
+                .or_default()
+                .push(index);
+        }
+    }
+
+    let mut code = String::new();
+    writeln!(code, "#[allow(clippy::unreadable_literal)]").unwrap();
+    writeln!(
+        code,
+        "pub static {static_name}: StaticIndex = StaticIndex {{"
+    )
+    .unwrap();
+    writeln!(code, "    packages: &[").unwrap();
+    for package in &packages {
+        writeln!(
+            code,
+            "        IndexedPackage {{ name: {:?}, popularity: {}, description: {:?} }},",
+            package.name, package.popularity, package.description
+        )
+        .unwrap();
+    }
+    writeln!(code, "    ],").unwrap();
+    writeln!(code, "    prefixes: &[").unwrap();
+    for (prefix, indices) in &prefixes {
+        let indices = indices
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(",");
+        writeln!(code, "        ({prefix:?}, &[{indices}]),").unwrap();
+    }
+    writeln!(code, "    ],").unwrap();
+    writeln!(code, "}};").unwrap();
+
+    fs::write(target, code).unwrap_or_else(|e| panic!("failed to write {}: {e}", target.display()));
+}
+
+/*
+    Stored as:
+
+    ```
+    name:downloads:"description"
+    ```
+*/
+fn parse_crates_io_packages(contents: &str) -> Vec<Package> {
+    contents
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            let (name, rest) = line.split_once(':').expect("missing name");
+            let (downloads, rest) = rest.split_once(':').expect("missing downloads");
+            let description = rest
+                .strip_prefix('"')
+                .and_then(|d| d.strip_suffix('"'))
+                .expect("unquoted description");
+            Package {
+                name: name.trim().to_string(),
+                popularity: downloads.parse().expect("invalid downloads"),
+                description: description.to_string(),
+            }
+        })
+        .collect()
+}
+
+/*
+    Stored as one name per line, most popular first,
+    so the popularity is derived from the line order.
+*/
+fn parse_npm_packages(contents: &str) -> Vec<Package> {
+    let names = contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>();
+    let count = names.len();
+    names
+        .into_iter()
+        .enumerate()
+        .map(|(index, name)| Package {
+            name: name.to_string(),
+            popularity: (count - index) as u64,
+            description: String::new(),
+        })
+        .collect()
+}
@@ -1,3 +1,5 @@
+use std::collections::HashSet;
+
 use tracing::debug;
 
 use async_language_server::{
@@ -12,14 +14,19 @@ use async_language_server::{
 };
 
 use deputy_clients::Clients;
-use deputy_parser::cargo;
+use deputy_parser::cargo::{self, CargoIncompleteDependency};
 use deputy_parser::utils::unquote;
-use deputy_versioning::Versioned;
+use deputy_versioning::{Version, Versioned};
 
 use crate::cargo::constants::CratesIoPackage;
 use crate::cargo::util::get_features;
+use crate::shared::{
+    ENTRY_VERSION_PLACEHOLDER, EntryCompletion, EntryMetadata, MatchQuality,
+    entry_completion_response, fetch_entry_versions, is_entry_version_missing, rank_matches,
+    resolve_entry_version,
+};
 
-use super::constants::top_crates_io_packages_prefixed;
+use super::constants::top_crates_io_packages_matching;
 
 const MAXIMUM_PACKAGES_SHOWN: usize = 64;
 const MINIMUM_PACKAGES_BEFORE_FETCH: usize = 16; // Less than 16 packages found statically = fetch dynamically
@@ -79,17 +86,111 @@ pub async fn get_cargo_completions(
     Ok(None)
 }
 
-async fn complete_name(
+/**
+    Completes a dependency that only has its name typed out so far,
+    inserting the whole entry, with or without a table for features.
+*/
+pub async fn get_cargo_entry_completions(
     clients: &Clients,
-    name: &str,
-    range: Range,
+    doc: &Document,
+    dep: CargoIncompleteDependency<'_>,
 ) -> ServerResult<Option<CompletionResponse>> {
-    let mut packages = top_crates_io_packages_prefixed(name, MAXIMUM_PACKAGES_SHOWN)
+    let name = dep.text(doc);
+    let range = ts_range_to_lsp_range(dep.range());
+
+    debug!("Completing entry: {dep:?}");
+
+    let packages = find_packages(clients, name.as_str()).await;
+    let versions = fetch_entry_versions(&packages, |package| {
+        latest_crate_version(clients, &package.name)
+    })
+    .await;
+
+    let items = packages
         .into_iter()
-        .cloned()
+        .zip(versions)
+        .enumerate()
+        .flat_map(|(index, (package, version))| {
+            let entry = EntryCompletion {
+                label: package.name.to_string(),
+                label_detail: None,
+                detail: Some(package.description.to_string()),
+                snippet: format!("{} = \"{ENTRY_VERSION_PLACEHOLDER}\"", package.name),
+                range,
+                sort_text: Some(format!("{index:0>5}")),
+                version: version.clone(),
+            };
+            let entry_with_features = EntryCompletion {
+                label: package.name.to_string(),
+                label_detail: Some(String::from(" = { features }")),
+                detail: Some(package.description.to_string()),
+                snippet: format!(
+                    "{} = {{ version = \"{ENTRY_VERSION_PLACEHOLDER}\", features = [$2] }}",
+                    package.name
+                ),
+                range,
+                sort_text: Some(format!("{index:0>5}")),
+                version,
+            };
+            [entry, entry_with_features].map(|entry| {
+                entry.into_completion_item(
+                    doc.url().clone(),
+                    EntryMetadata::Cargo {
+                        name: package.name.to_string(),
+                    },
+                )
+            })
+        })
         .collect::<Vec<_>>();
+    Ok(Some(entry_completion_response(items)))
+}
 
-    if packages.len() < MINIMUM_PACKAGES_BEFORE_FETCH {
+/**
+    Adds the latest non-yanked version of a crate to a dependency
+    entry that did not get its version filled in with the list.
+*/
+pub async fn resolve_cargo_entry(
+    clients: &Clients,
+    mut item: CompletionItem,
+    name: &str,
+) -> ServerResult<CompletionItem> {
+    if !is_entry_version_missing(&item) {
+        return Ok(item);
+    }
+    if let Some(latest) = latest_crate_version(clients, name).await {
+        resolve_entry_version(&mut item, &latest);
+    }
+    Ok(item)
+}
+
+async fn latest_crate_version(clients: &Clients, name: &str) -> Option<String> {
+    let metadatas = clients
+        .crates
+        .get_sparse_index_crate_metadatas(name)
+        .await
+        .ok()?;
+    let latest =
+        Version::new(0, 0, 0).extract_latest_version_filtered(metadatas, |v| !v.item.yanked)?;
+    Some(latest.item_version.to_string())
+}
+
+/**
+    Finds crates matching the given name, ranked by match quality and downloads.
+
+    Searches crates.io if there are only a few good matches in the bundled list
+    of top crates, since fuzzy matches alone are usually not what the user wants.
+*/
+async fn find_packages(clients: &Clients, name: &str) -> Vec<CratesIoPackage> {
+    let mut packages = top_crates_io_packages_matching(name, MAXIMUM_PACKAGES_SHOWN)
+        .into_iter()
+        .map(CratesIoPackage::from)
+        .collect::<Vec<_>>();
+
+    let good_matches = packages
+        .iter()
+        .filter(|p| MatchQuality::of(&p.name, name).is_some_and(|q| q <= MatchQuality::Substring))
+        .count();
+    if good_matches < MINIMUM_PACKAGES_BEFORE_FETCH {
         if let Ok(crates) = clients.crates.search_crates(name).await {
             let count_prev = packages.len();
 
@@ -99,25 +200,37 @@ async fn complete_name(
                 description: m.description.to_string().into(),
             }));
 
-            packages.sort_by_key(|package| package.name.to_ascii_lowercase());
-            packages.dedup_by_key(|p| p.name.to_ascii_lowercase());
-            packages.truncate(MINIMUM_PACKAGES_BEFORE_FETCH);
+            let mut seen = HashSet::new();
+            packages.retain(|p| seen.insert(p.name.to_ascii_lowercase()));
+            packages = rank_matches(packages, name, |p| &p.name, |p| p.downloads);
+            packages.truncate(MAXIMUM_PACKAGES_SHOWN);
 
             let count_after = packages.len();
             if count_after > count_prev {
                 debug!(
-                    "Found {} additional crates for prefix '{name}'",
+                    "Found {} additional crates for '{name}'",
                     count_after.saturating_sub(count_prev),
                 );
             }
         }
     }
 
-    let items = packages
+    packages
+}
+
+async fn complete_name(
+    clients: &Clients,
+    name: &str,
+    range: Range,
+) -> ServerResult<Option<CompletionResponse>> {
+    let items = find_packages(clients, name)
+        .await
         .into_iter()
-        .map(|package| CompletionItem {
+        .enumerate()
+        .map(|(index, package)| CompletionItem {
             label: package.name.to_string(),
             kind: Some(CompletionItemKind::VALUE),
+            sort_text: Some(format!("{index:0>5}")),
             detail: Some(package.description.to_string()),
             text_edit: Some(CompletionTextEdit::Edit(TextEdit {
                 new_text: package.name.to_string(),
@@ -149,6 +262,10 @@ async fn complete_version(
             label: potential_version.item_version_raw.to_string(),
             kind: Some(CompletionItemKind::VALUE),
             sort_text: Some(format!("{index:0>5}")),
+            detail: potential_version
+                .item
+                .yanked
+                .then(|| String::from("Yanked")),
             deprecated: Some(potential_version.item.yanked),
             text_edit: Some(CompletionTextEdit::Edit(TextEdit {
                 new_text: potential_version.item_version_raw.to_string(),
@@ -1,18 +1,10 @@
-use std::{
-    str::FromStr,
-    sync::{Arc, OnceLock},
-};
+use std::sync::Arc;
 
-use crate::shared::CompletionMap;
+use crate::shared::{IndexedPackage, StaticIndex};
 
 /**
-    A statically stored package from the crates.io index.
-
-    Stored in a text file as:
-
-    ```
-    name:downloads:"description"
-    ```
+    A package from the crates.io index, either from the
+    bundled index of top packages or from a search.
 */
 #[allow(dead_code)]
 #[derive(Debug, Clone)]
@@ -22,25 +14,13 @@ pub struct CratesIoPackage {
     pub description: Arc<str>,
 }
 
-impl FromStr for CratesIoPackage {
-    type Err = String;
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let Some((name, rest)) = s.split_once(':') else {
-            return Err("missing name".to_string());
-        };
-        let Some((downloads, rest)) = rest.split_once(':') else {
-            return Err("missing downloads".to_string());
-        };
-        let description = rest
-            .strip_prefix('"')
-            .ok_or_else(|| "unquoted description".to_string())?
-            .strip_suffix('"')
-            .ok_or_else(|| "unquoted description".to_string())?;
-        Ok(Self {
-            name: name.into(),
-            downloads: downloads.parse().unwrap(),
-            description: description.into(),
-        })
+impl From<&IndexedPackage> for CratesIoPackage {
+    fn from(package: &IndexedPackage) -> Self {
+        Self {
+            name: package.name.into(),
+            downloads: package.popularity,
+            description: package.description.into(),
+        }
     }
 }
 
@@ -51,21 +31,13 @@ impl AsRef<str> for CratesIoPackage {
 }
 
 /*
-    We bundle about 10,000 top crates.io packages in a text file,
-    and pre-compute them here for fast autocomplete - see the
-    implementation for `PrefixOrderedMap` for more details on this.
+    We bundle about 10,000 top crates.io packages in a text file, which the
+    build script turns into a static index for fast autocomplete - see the
+    implementation for `StaticIndex` for more details on this.
 */
 
-static TOP_PACKAGES_CRATES_IO: &str = include_str!("../../assets/top-crates-io-packages.txt");
-static TOP_PACKAGES: OnceLock<CompletionMap<CratesIoPackage>> = OnceLock::new();
-
-pub fn top_crates_io_packages_prefixed(prefix: &str, limit: usize) -> Vec<&CratesIoPackage> {
-    let top = TOP_PACKAGES.get_or_init(|| {
-        TOP_PACKAGES_CRATES_IO
-            .lines()
-            .map(|s| s.parse().unwrap())
-            .collect::<CompletionMap<_>>()
-    });
+include!(concat!(env!("OUT_DIR"), "/top_crates_io_packages.rs"));
 
-    top.iter(prefix).take(limit).collect()
+pub fn top_crates_io_packages_matching(query: &str, limit: usize) -> Vec<&'static IndexedPackage> {
+    TOP_CRATES_IO_PACKAGES.search(query, limit)
 }
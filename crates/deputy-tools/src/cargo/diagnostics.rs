@@ -1,7 +1,9 @@
+use std::path::Path;
+
 use tracing::debug;
 
 use async_language_server::{
-    lsp_types::{Diagnostic, DiagnosticSeverity},
+    lsp_types::{Diagnostic, DiagnosticSeverity, DiagnosticTag, Position, Range},
     server::{Document, ServerResult},
     text_utils::RangeExt,
     tree_sitter::Node,
@@ -12,14 +14,19 @@ use deputy_versioning::{VersionReq, VersionReqExt, Versioned};
 
 use deputy_clients::crates::models::IndexMetadata;
 use deputy_parser::{
-    cargo::{self, CargoDependency},
+    cargo::{self, CargoDependency, DependencyKind},
     utils::unquote,
 };
 
 use crate::shared::{CodeActionMetadata, ResolveContext, did_you_mean};
 
 use super::Clients;
-use super::util::get_features;
+use super::manifest::{CRATES_IO, Manifest, ManifestDependency};
+use super::sources::SourceCache;
+use super::util::{
+    find_nearest_unyanked_version, find_version, get_features, get_required_version,
+};
+use super::workspace::{Workspace, WorkspaceCache};
 
 pub async fn get_cargo_diagnostics(
     clients: &Clients,
@@ -50,7 +57,11 @@ pub async fn get_cargo_diagnostics(
 
     let mut diagnostics = Vec::new();
     diagnostics.extend(get_cargo_diagnostics_version(clients, doc, &dep, &metas)?);
+    diagnostics.extend(get_cargo_diagnostics_yanked(clients, doc, &dep, &metas)?);
     diagnostics.extend(get_cargo_diagnostics_features(clients, doc, &dep, &metas).await?);
+    diagnostics.extend(get_cargo_diagnostics_minimal_versions(
+        clients, doc, &dep, &metas,
+    )?);
     Ok(diagnostics)
 }
 
@@ -128,6 +139,50 @@ fn get_cargo_diagnostics_version(
     Ok(Vec::new())
 }
 
+fn get_cargo_diagnostics_yanked(
+    _clients: &Clients,
+    doc: &Document,
+    dep: &CargoDependency<'_>,
+    metas: &[IndexMetadata],
+) -> ServerResult<Vec<Diagnostic>> {
+    let (name, version) = dep.text(doc);
+
+    let Ok(version_req) = VersionReq::parse(&version) else {
+        return Ok(Vec::new());
+    };
+
+    // Yanked versions are still used if pinned with = or if they are the lower
+    // bound of a requirement and already in a lockfile, so we warn about both
+    let required_version = get_required_version(&version_req);
+    if !find_version(metas, &required_version).is_some_and(|m| m.yanked) {
+        return Ok(Vec::new());
+    }
+
+    let data = find_nearest_unyanked_version(metas, &required_version).map(|unyanked| {
+        let metadata = CodeActionMetadata::UnyankedVersion {
+            edit_range: ts_range_to_lsp_range(dep.version.range().shrink(1, 1)),
+            source_uri: doc.url().clone(),
+            source_text: version.clone(),
+            version_unyanked: unyanked.to_string(),
+        };
+        ResolveContext {
+            uri: doc.url().clone(),
+            value: metadata,
+        }
+        .into()
+    });
+
+    Ok(vec![Diagnostic {
+        source: Some(String::from("Cargo")),
+        range: ts_range_to_lsp_range(dep.version.range()),
+        message: format!("Version `{required_version}` of `{name}` has been yanked"),
+        severity: Some(DiagnosticSeverity::WARNING),
+        tags: Some(vec![DiagnosticTag::DEPRECATED]),
+        data,
+        ..Default::default()
+    }])
+}
+
 async fn get_cargo_diagnostics_features(
     clients: &Clients,
     doc: &Document,
@@ -161,3 +216,468 @@ async fn get_cargo_diagnostics_features(
 
     Ok(diagnostics)
 }
+
+fn get_cargo_diagnostics_minimal_versions(
+    _clients: &Clients,
+    doc: &Document,
+    dep: &CargoDependency<'_>,
+    metas: &[IndexMetadata],
+) -> ServerResult<Vec<Diagnostic>> {
+    let (name, version) = dep.text(doc);
+
+    let Ok(version_req) = VersionReq::parse(&version) else {
+        return Ok(Vec::new());
+    };
+    let version_min = version_req.minimum_version();
+
+    // Sort all usable versions, lowest first, which is the
+    // order that Cargo uses when resolving minimal versions
+    let mut matching = metas
+        .iter()
+        .filter(|m| !m.yanked)
+        .filter_map(|m| m.parse_version().ok().map(|v| (v, m)))
+        .filter(|(v, _)| version_req.matches(v))
+        .collect::<Vec<_>>();
+    matching.sort_by(|(a, _), (b, _)| a.cmp(b));
+    let Some((lowest_version, lowest_meta)) = matching.first() else {
+        return Ok(Vec::new());
+    };
+
+    let raise_to = |new_version: String| CodeActionMetadata::RaiseMinimumVersion {
+        edit_range: ts_range_to_lsp_range(dep.version.range().shrink(1, 1)),
+        source_uri: doc.url().clone(),
+        source_text: version.clone(),
+        version_minimum: new_version,
+    };
+
+    let mut diagnostics = Vec::new();
+
+    // The lower bound of the requirement itself may not exist, yanked
+    // versions are reported separately, since they are still usable
+    if find_version(metas, &version_min).is_none() {
+        diagnostics.push(Diagnostic {
+            source: Some(String::from("Cargo")),
+            range: ts_range_to_lsp_range(dep.version.range()),
+            message: format!(
+                "Version `{version_min}` of `{name}` does not exist.\
+                \nThe lowest version matching `{version}` is `{lowest_version}`"
+            ),
+            severity: Some(DiagnosticSeverity::HINT),
+            data: Some(
+                ResolveContext {
+                    uri: doc.url().clone(),
+                    value: raise_to(lowest_version.to_string()),
+                }
+                .into(),
+            ),
+            ..Default::default()
+        });
+    }
+
+    // Features must exist at the lowest version, not just the newest one
+    let lowest_features = lowest_meta.all_features();
+    for feat_node in dep.feature_nodes() {
+        let feat = unquote(doc.node_text(feat_node));
+        if lowest_features.contains(&feat.as_str()) {
+            continue;
+        }
+
+        // Features that don't exist at all are reported elsewhere
+        let Some((first_version, _)) = matching
+            .iter()
+            .find(|(_, m)| m.all_features().contains(&feat.as_str()))
+        else {
+            continue;
+        };
+
+        diagnostics.push(Diagnostic {
+            source: Some(String::from("Cargo")),
+            range: ts_range_to_lsp_range(feat_node.range()),
+            message: format!(
+                "Feature `{feat}` does not exist in version `{lowest_version}` of `{name}`.\
+                \nThe first version with this feature is `{first_version}`"
+            ),
+            severity: Some(DiagnosticSeverity::WARNING),
+            data: Some(
+                ResolveContext {
+                    uri: doc.url().clone(),
+                    value: raise_to(first_version.to_string()),
+                }
+                .into(),
+            ),
+            ..Default::default()
+        });
+    }
+
+    Ok(diagnostics)
+}
+
+pub fn get_cargo_workspace_diagnostics(
+    doc: &Document,
+    workspaces: &WorkspaceCache,
+) -> ServerResult<Vec<Diagnostic>> {
+    let Some(dir) = doc
+        .url()
+        .to_file_path()
+        .ok()
+        .and_then(|path| path.parent().map(Path::to_path_buf))
+    else {
+        return Ok(Vec::new());
+    };
+
+    // NOTE: The manifest may be invalid while typing, we
+    // can't reason about the workspace until it is valid
+    let Ok(manifest) = doc.text().to_string().parse::<Manifest>() else {
+        return Ok(Vec::new());
+    };
+
+    let workspace = if let Some(globs) = cargo::find_workspace_globs(doc) {
+        Some(Workspace::from_root(
+            &dir,
+            manifest.clone(),
+            &globs,
+            workspaces,
+        ))
+    } else if manifest.package.is_some() {
+        Workspace::discover(&dir, &manifest, workspaces)
+    } else {
+        None
+    };
+    let Some(workspace) = workspace else {
+        return Ok(Vec::new());
+    };
+
+    let mut diagnostics = Vec::new();
+    if workspace.root_dir == dir {
+        diagnostics.extend(get_cargo_diagnostics_unused_workspace(doc, &workspace));
+    }
+    if manifest.package.is_some() {
+        diagnostics.extend(get_cargo_diagnostics_inheritable(doc, &workspace));
+    }
+    Ok(diagnostics)
+}
+
+fn get_cargo_diagnostics_unused_workspace(
+    doc: &Document,
+    workspace: &Workspace,
+) -> Vec<Diagnostic> {
+    // Globs that match nothing would make every dependency seem unused
+    if workspace.members.is_empty() {
+        return Vec::new();
+    }
+
+    let mut diagnostics = Vec::new();
+    for node in cargo::find_all_workspace_dependencies(doc) {
+        let Some(key_node) = cargo::parse_dependency_key(node) else {
+            continue;
+        };
+
+        let key = unquote(doc.node_text(key_node));
+        if workspace.is_inherited(&key) {
+            continue;
+        }
+
+        let metadata = CodeActionMetadata::RemoveDependency {
+            edit_range: full_line_range(node),
+            source_uri: doc.url().clone(),
+            name: key.clone(),
+        };
+
+        diagnostics.push(Diagnostic {
+            source: Some(String::from("Cargo")),
+            range: ts_range_to_lsp_range(key_node.range()),
+            message: format!("Workspace dependency `{key}` is not used by any member"),
+            severity: Some(DiagnosticSeverity::HINT),
+            tags: Some(vec![DiagnosticTag::UNNECESSARY]),
+            data: Some(
+                ResolveContext {
+                    uri: doc.url().clone(),
+                    value: metadata,
+                }
+                .into(),
+            ),
+            ..Default::default()
+        });
+    }
+
+    diagnostics
+}
+
+fn get_cargo_diagnostics_inheritable(doc: &Document, workspace: &Workspace) -> Vec<Diagnostic> {
+    let Some(workspace_deps) = workspace
+        .root_manifest
+        .workspace
+        .as_ref()
+        .map(|w| &w.dependencies)
+    else {
+        return Vec::new();
+    };
+
+    let mut diagnostics = Vec::new();
+    for node in cargo::find_all_dependencies(doc) {
+        if cargo::is_workspace_dependency(doc, node) {
+            continue;
+        }
+        let Some(dep) = cargo::parse_dependency(doc, node) else {
+            continue;
+        };
+
+        // Only versions, features, and optional may be combined with `workspace = true`
+        let key = unquote(doc.node_text(dep.key));
+        let fields = cargo::parse_dependency_fields(doc, node);
+        if !fields
+            .iter()
+            .all(|f| matches!(f.as_str(), "version" | "features" | "optional"))
+        {
+            continue;
+        }
+
+        let (_, version) = dep.text(doc);
+        let Some(workspace_version) = workspace_deps
+            .get(&key)
+            .and_then(ManifestDependency::plain_version)
+        else {
+            continue;
+        };
+        let (Ok(version_req), Ok(workspace_version_req)) = (
+            VersionReq::parse(&version),
+            VersionReq::parse(workspace_version),
+        ) else {
+            continue;
+        };
+        if version_req != workspace_version_req {
+            continue;
+        }
+
+        // name = "1.0" becomes name = { workspace = true }, but if the
+        // version is one of several fields, we only replace that field
+        let (edit_node, new_text) = match dep.version.parent() {
+            Some(parent) if parent != node => (parent, "workspace = true"),
+            _ => (dep.version, "{ workspace = true }"),
+        };
+
+        let metadata = CodeActionMetadata::InheritWorkspaceDependency {
+            edit_range: ts_range_to_lsp_range(edit_node.range()),
+            source_uri: doc.url().clone(),
+            name: key.clone(),
+            new_text: new_text.to_string(),
+        };
+
+        diagnostics.push(Diagnostic {
+            source: Some(String::from("Cargo")),
+            range: ts_range_to_lsp_range(dep.version.range()),
+            message: format!(
+                "Version of `{key}` is the same as in the workspace.\
+                \nConsider using `workspace = true` instead"
+            ),
+            severity: Some(DiagnosticSeverity::HINT),
+            data: Some(
+                ResolveContext {
+                    uri: doc.url().clone(),
+                    value: metadata,
+                }
+                .into(),
+            ),
+            ..Default::default()
+        });
+    }
+
+    diagnostics
+}
+
+pub fn get_cargo_unused_diagnostics(
+    doc: &Document,
+    sources: &SourceCache,
+) -> ServerResult<Vec<Diagnostic>> {
+    let Some(dir) = doc
+        .url()
+        .to_file_path()
+        .ok()
+        .and_then(|path| path.parent().map(Path::to_path_buf))
+    else {
+        return Ok(Vec::new());
+    };
+
+    // Virtual manifests have no sources that could use any dependencies
+    let Ok(manifest) = doc.text().to_string().parse::<Manifest>() else {
+        return Ok(Vec::new());
+    };
+    if manifest.package.is_none() {
+        return Ok(Vec::new());
+    }
+
+    // A package without any sources is most likely not on disk yet,
+    // and reporting all of its dependencies as unused is not helpful
+    let references = sources.get(&dir);
+    if !references.has_sources {
+        return Ok(Vec::new());
+    }
+
+    let mut diagnostics = Vec::new();
+    for node in cargo::find_all_dependencies(doc) {
+        if cargo::is_workspace_dependency(doc, node) {
+            continue;
+        }
+        let (Some(key_node), Some(kind)) = (
+            cargo::parse_dependency_key(node),
+            cargo::parse_dependency_kind(doc, node),
+        ) else {
+            continue;
+        };
+
+        // The key is what the crate is referenced as, even
+        // if it has been renamed using the `package` field
+        let key = unquote(doc.node_text(key_node));
+        let crate_name = key.replace('-', "_");
+        let is_used = match kind {
+            DependencyKind::BuildDependency => references.build_script.contains(&crate_name),
+            _ => references.sources.contains(&crate_name),
+        };
+        if is_used {
+            continue;
+        }
+
+        let metadata = CodeActionMetadata::RemoveDependency {
+            edit_range: full_line_range(node),
+            source_uri: doc.url().clone(),
+            name: key.clone(),
+        };
+
+        diagnostics.push(Diagnostic {
+            source: Some(String::from("Cargo")),
+            range: ts_range_to_lsp_range(key_node.range()),
+            message: match kind {
+                DependencyKind::BuildDependency => {
+                    format!("Build dependency `{key}` is not used by the build script")
+                }
+                _ => format!("Dependency `{key}` is not used by any source file"),
+            },
+            severity: Some(DiagnosticSeverity::HINT),
+            tags: Some(vec![DiagnosticTag::UNNECESSARY]),
+            data: Some(
+                ResolveContext {
+                    uri: doc.url().clone(),
+                    value: metadata,
+                }
+                .into(),
+            ),
+            ..Default::default()
+        });
+    }
+
+    Ok(diagnostics)
+}
+
+pub fn get_cargo_publish_diagnostics(doc: &Document) -> ServerResult<Vec<Diagnostic>> {
+    let Ok(manifest) = doc.text().to_string().parse::<Manifest>() else {
+        return Ok(Vec::new());
+    };
+    let Some(package) = manifest.package.as_ref().filter(|p| p.has_publish_lints()) else {
+        return Ok(Vec::new());
+    };
+    let Some(registries) = package.publish_registries() else {
+        return Ok(Vec::new());
+    };
+    let publishes_to_crates_io = registries.iter().any(|r| r == CRATES_IO);
+
+    let warning = |node: Node<'_>, message: String| Diagnostic {
+        source: Some(String::from("Cargo")),
+        range: ts_range_to_lsp_range(node.range()),
+        message,
+        severity: Some(DiagnosticSeverity::WARNING),
+        ..Default::default()
+    };
+
+    let mut diagnostics = Vec::new();
+    if let Some(table) = cargo::find_package_table(doc) {
+        let header = table.named_child(0).unwrap_or(table);
+        if package.license.is_none() && package.license_file.is_none() {
+            diagnostics.push(warning(
+                header,
+                String::from("Package is missing a `license` or `license-file`"),
+            ));
+        }
+        if package.description.is_none() {
+            diagnostics.push(warning(
+                header,
+                String::from("Package is missing a `description`"),
+            ));
+        }
+    }
+
+    for node in cargo::find_all_dependencies(doc) {
+        if cargo::is_workspace_dependency(doc, node) {
+            continue;
+        }
+        let (Some(key_node), Some(kind)) = (
+            cargo::parse_dependency_key(node),
+            cargo::parse_dependency_kind(doc, node),
+        ) else {
+            continue;
+        };
+        let key = unquote(doc.node_text(key_node));
+
+        let version = match node.named_child(1).filter(|v| v.kind() == "string") {
+            Some(version) => Some(version),
+            None => cargo::parse_dependency_field(doc, node, "version"),
+        };
+
+        // Dev dependencies without a version are removed when publishing
+        let source = cargo::parse_dependency_field(doc, node, "path")
+            .map(|n| (n, "path"))
+            .or_else(|| cargo::parse_dependency_field(doc, node, "git").map(|n| (n, "git")))
+            .filter(|_| version.is_none() && kind != DependencyKind::DevDependency);
+        if let Some((source_node, source_field)) = source {
+            diagnostics.push(warning(
+                source_node,
+                format!(
+                    "Dependency `{key}` has a `{source_field}` but no `version`, \
+                    and can not be published"
+                ),
+            ));
+        }
+
+        let wildcard = version
+            .filter(|_| publishes_to_crates_io)
+            .filter(|v| unquote(doc.node_text(*v)).trim() == "*");
+        if let Some(version) = wildcard {
+            diagnostics.push(warning(
+                version,
+                format!("Dependency `{key}` uses a wildcard version, which crates.io rejects"),
+            ));
+        }
+
+        if let Some(registry) = cargo::parse_dependency_field(doc, node, "registry") {
+            let registry_name = unquote(doc.node_text(registry));
+            if !registries.is_empty() && !registries.contains(&registry_name) {
+                diagnostics.push(warning(
+                    registry,
+                    format!(
+                        "Dependency `{key}` is from registry `{registry_name}`, \
+                        but the package is published to `{}`",
+                        registries.join("`, `")
+                    ),
+                ));
+            }
+        }
+    }
+
+    Ok(diagnostics)
+}
+
+/**
+    Creates a range spanning all lines of the given node, including
+    the trailing newline, so that removing it leaves no empty line.
+*/
+fn full_line_range(node: Node<'_>) -> Range {
+    let start = node.start_position();
+    let end = node.end_position();
+    Range {
+        start: Position::new(start.row as u32, 0),
+        end: if end.column == 0 {
+            Position::new(end.row as u32, 0)
+        } else {
+            Position::new(end.row as u32 + 1, 0)
+        },
+    }
+}
@@ -1,20 +1,25 @@
 use tracing::trace;
 
 use async_language_server::{
-    lsp_types::{Hover, HoverContents, MarkupContent, MarkupKind},
+    lsp_types::{Hover, HoverContents, MarkupContent, MarkupKind, Position},
     server::{Document, ServerResult},
     tree_sitter::Node,
-    tree_sitter_utils::ts_range_to_lsp_range,
+    tree_sitter_utils::{ts_range_contains_lsp_position, ts_range_to_lsp_range},
 };
 
-use crate::shared::MarkdownBuilder;
+use deputy_clients::crates::models::{CrateDataSingle, CrateDataVersion};
 use deputy_parser::cargo;
+use deputy_versioning::{Version, VersionReq, VersionReqExt, Versioned};
+
+use crate::shared::{MarkdownBuilder, ReleaseNotes, format_count, format_date};
 
 use super::Clients;
+use super::util::{find_nearest_unyanked_version, find_version, get_required_version};
 
 pub async fn get_cargo_hover(
     clients: &Clients,
     doc: &Document,
+    pos: Position,
     node: Node<'_>,
 ) -> ServerResult<Option<Hover>> {
     let Some(dep) = cargo::parse_dependency(doc, node) else {
@@ -22,24 +27,132 @@ pub async fn get_cargo_hover(
     };
 
     let (dependency_name, dependency_version) = dep.text(doc);
+    let version_req = VersionReq::parse(&dependency_version).ok();
+
+    // Hovering the version string explains the requirement instead
+    if ts_range_contains_lsp_position(dep.version.range(), pos) {
+        trace!("Hovering version range: {dependency_name} version {dependency_version}");
+        let mut md = MarkdownBuilder::new();
+        md.h2(&dependency_name);
+        md.version(&dependency_version);
+        if let Some(range) = clients
+            .crates
+            .get_sparse_index_crate_metadatas(&dependency_name)
+            .await
+            .ok()
+            .and_then(|metas| dependency_version.extract_version_range(metas))
+        {
+            md.br();
+            md.version_range(&range);
+        }
+        return Ok(Some(Hover {
+            range: Some(ts_range_to_lsp_range(dep.version.range())),
+            contents: HoverContents::Markup(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value: md.build(),
+            }),
+        }));
+    }
 
     // Add basic hover information with version and name
     trace!("Hovering: {dependency_name} version {dependency_version}");
     let mut md = MarkdownBuilder::new();
     md.h2(&dependency_name);
-    md.version(dependency_version);
+    md.version(&dependency_version);
+
+    // Let the user know if the version they are using has been yanked
+    if let (Some(version_req), Ok(metas)) = (
+        version_req.as_ref(),
+        clients
+            .crates
+            .get_sparse_index_crate_metadatas(&dependency_name)
+            .await,
+    ) {
+        let required_version = get_required_version(version_req);
+        if find_version(&metas, &required_version).is_some_and(|m| m.yanked) {
+            md.br();
+            md.p(format!("Version `{required_version}` has been **yanked**"));
+            if let Some(unyanked) = find_nearest_unyanked_version(&metas, &required_version) {
+                md.extend_last(format!(" - consider using `{unyanked}` instead"));
+            }
+        }
+    }
 
     // Try to fetch additional information from the index - description, links
     trace!("Fetching crate data from crates.io");
-    if let Ok(crate_data) = clients
-        .crates
-        .get_crate_data(&dependency_name)
-        .await
-        .map(|c| c.inner)
-    {
+    if let Ok(crate_data_single) = clients.crates.get_crate_data(&dependency_name).await {
+        let release_notes = get_cargo_release_notes(clients, doc, node).await;
+        let resolved = version_req
+            .as_ref()
+            .and_then(|req| find_resolved_version(&crate_data_single, req));
+        let CrateDataSingle {
+            inner: crate_data,
+            versions,
+        } = crate_data_single;
+
         md.br();
         md.p(crate_data.description);
 
+        // Add information about the version that the requirement resolves to
+        if let Some((resolved_version, resolved_data)) = resolved {
+            md.br();
+            md.h3(format!("Version {resolved_version}"));
+            md.p(format!(
+                "- Published {} - {} downloads",
+                format_date(&resolved_data.created_at),
+                format_count(resolved_data.downloads)
+            ));
+            match count_newer_versions(&versions, &resolved_version) {
+                (0, _) => md.p("- This is the latest version"),
+                (1, Some(latest)) => md.p(format!("- 1 version behind latest `{latest}`")),
+                (n, Some(latest)) => md.p(format!("- {n} versions behind latest `{latest}`")),
+                (_, None) => {}
+            }
+            if let Some(license) = resolved_data.license.as_deref() {
+                md.p(format!("- License: {license}"));
+            }
+
+            // Add default and available features for the same version
+            let mut default_features = resolved_data
+                .features
+                .get("default")
+                .cloned()
+                .unwrap_or_default();
+            let mut other_features = resolved_data
+                .features
+                .keys()
+                .filter(|f| *f != "default" && !default_features.contains(f))
+                .cloned()
+                .collect::<Vec<_>>();
+            if !default_features.is_empty() || !other_features.is_empty() {
+                default_features.sort_unstable();
+                other_features.sort_unstable();
+                md.br();
+                md.h3("Features");
+                if !default_features.is_empty() {
+                    md.p(format!("- Default: {}", code_list(&default_features)));
+                }
+                if !other_features.is_empty() {
+                    md.p(format!("- Available: {}", code_list(&other_features)));
+                }
+            }
+        }
+
+        // Add total and recent downloads for the crate as a whole
+        md.br();
+        md.h3("Downloads");
+        md.p(format!(
+            "- {} total, {} recent",
+            format_count(crate_data.downloads.total_count),
+            format_count(crate_data.downloads.recent_count)
+        ));
+
+        // Add a summary of what changed since the current version
+        if let Some(notes) = release_notes {
+            md.br();
+            notes.add_hover_section(&mut md);
+        }
+
         // Ignore homepage or docs if it's the same as the repo
         let mut docs = crate_data.links.documentation.as_deref();
         let mut page = crate_data.links.homepage.as_deref();
@@ -73,6 +186,20 @@ pub async fn get_cargo_hover(
         if let Some(page) = page {
             md.a("Homepage", page);
         }
+
+        // Add keywords and categories last, these are the least important
+        let keywords = crate_data.keywords.unwrap_or_default();
+        let categories = crate_data.categories.unwrap_or_default();
+        if !keywords.is_empty() || !categories.is_empty() {
+            md.br();
+            if !keywords.is_empty() {
+                md.p(format!("Keywords: {}", code_list(&keywords)));
+            }
+            if !categories.is_empty() {
+                md.br();
+                md.p(format!("Categories: {}", code_list(&categories)));
+            }
+        }
     }
 
     Ok(Some(Hover {
@@ -83,3 +210,78 @@ pub async fn get_cargo_hover(
         }),
     }))
 }
+
+/**
+    Gets cached release notes for all versions between the minimum
+    version of the requirement and the latest non-yanked version.
+*/
+pub async fn get_cargo_release_notes(
+    clients: &Clients,
+    doc: &Document,
+    node: Node<'_>,
+) -> Option<ReleaseNotes> {
+    let dep = cargo::parse_dependency(doc, node)?;
+    let (name, version) = dep.text(doc);
+    let version_min = VersionReq::parse(&version).ok()?.minimum_version();
+
+    let metas = clients
+        .crates
+        .get_sparse_index_crate_metadatas(&name)
+        .await
+        .ok()?;
+    let latest = version_min.extract_latest_version_filtered(metas, |v| !v.item.yanked)?;
+
+    let crate_data = clients.crates.get_crate_data(&name).await.ok()?;
+    let repository = crate_data.inner.links.repository?;
+
+    ReleaseNotes::cached(
+        clients,
+        &name,
+        &repository,
+        &version_min,
+        &latest.item_version,
+    )
+}
+
+/**
+    Finds the newest non-yanked version that matches the given requirement,
+    which is the version that Cargo would resolve to without a lockfile.
+*/
+fn find_resolved_version(
+    crate_data: &CrateDataSingle,
+    version_req: &VersionReq,
+) -> Option<(Version, CrateDataVersion)> {
+    crate_data
+        .versions
+        .iter()
+        .filter(|v| !v.yanked)
+        .filter_map(|v| v.parse_version().ok().map(|version| (version, v)))
+        .filter(|(version, _)| version_req.matches(version))
+        .max_by(|(a, _), (b, _)| a.cmp(b))
+        .map(|(version, data)| (version, data.clone()))
+}
+
+/**
+    Counts the number of stable, non-yanked versions newer than
+    the given version, and returns the newest one of those.
+*/
+fn count_newer_versions(
+    versions: &[CrateDataVersion],
+    version: &Version,
+) -> (usize, Option<Version>) {
+    let newer = versions
+        .iter()
+        .filter(|v| !v.yanked)
+        .filter_map(|v| v.parse_version().ok())
+        .filter(|v| v.pre.is_empty() && v > version)
+        .collect::<Vec<_>>();
+    (newer.len(), newer.into_iter().max())
+}
+
+fn code_list(items: &[String]) -> String {
+    items
+        .iter()
+        .map(|item| format!("`{item}`"))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
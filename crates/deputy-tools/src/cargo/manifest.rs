@@ -0,0 +1,247 @@
+use std::{collections::BTreeMap, path::Path, str::FromStr};
+
+use serde::Deserialize;
+
+use deputy_parser::cargo::CargoWorkspaceGlobs;
+
+use crate::shared::FileCache;
+
+pub const CRATES_IO: &str = "crates-io";
+pub const MANIFEST_FILE_NAME: &str = "Cargo.toml";
+
+static MANIFESTS: FileCache<Option<Manifest>> = FileCache::new();
+
+/**
+    A minimal model of a `Cargo.toml` manifest.
+
+    This is used for manifests that are read from disk, which are not
+    necessarily open in the editor, and for reasoning about a manifest
+    as a whole instead of by individual syntax nodes.
+*/
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Manifest {
+    #[serde(default)]
+    pub package: Option<ManifestPackage>,
+    #[serde(default)]
+    pub workspace: Option<ManifestWorkspace>,
+    #[serde(default)]
+    pub dependencies: BTreeMap<String, ManifestDependency>,
+    #[serde(default, alias = "dev_dependencies")]
+    pub dev_dependencies: BTreeMap<String, ManifestDependency>,
+    #[serde(default, alias = "build_dependencies")]
+    pub build_dependencies: BTreeMap<String, ManifestDependency>,
+    #[serde(default)]
+    pub target: BTreeMap<String, ManifestTarget>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ManifestPackage {
+    #[serde(default)]
+    pub workspace: Option<String>,
+    #[serde(default)]
+    pub publish: Option<toml::Value>,
+    #[serde(default)]
+    pub license: Option<toml::Value>,
+    #[serde(default, alias = "license_file")]
+    pub license_file: Option<toml::Value>,
+    #[serde(default)]
+    pub description: Option<toml::Value>,
+    #[serde(default)]
+    pub metadata: Option<ManifestPackageMetadata>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ManifestPackageMetadata {
+    #[serde(default)]
+    pub deputy: Option<DeputyMetadata>,
+}
+
+/**
+    Settings for Deputy itself, from `[package.metadata.deputy]`.
+*/
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct DeputyMetadata {
+    #[serde(default)]
+    pub publish_lints: bool,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ManifestWorkspace {
+    #[serde(default)]
+    pub members: Vec<String>,
+    #[serde(default)]
+    pub exclude: Vec<String>,
+    #[serde(default)]
+    pub dependencies: BTreeMap<String, ManifestDependency>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ManifestTarget {
+    #[serde(default)]
+    pub dependencies: BTreeMap<String, ManifestDependency>,
+    #[serde(default, alias = "dev_dependencies")]
+    pub dev_dependencies: BTreeMap<String, ManifestDependency>,
+    #[serde(default, alias = "build_dependencies")]
+    pub build_dependencies: BTreeMap<String, ManifestDependency>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum ManifestDependency {
+    Simple(String),
+    Detailed(ManifestDependencyDetail),
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ManifestDependencyDetail {
+    #[serde(default)]
+    pub version: Option<String>,
+    #[serde(default)]
+    pub path: Option<String>,
+    #[serde(default)]
+    pub git: Option<String>,
+    #[serde(default)]
+    pub registry: Option<String>,
+    #[serde(default)]
+    pub package: Option<String>,
+    #[serde(default)]
+    pub workspace: bool,
+    #[serde(default)]
+    pub features: Vec<String>,
+    #[serde(default, alias = "default_features")]
+    pub default_features: Option<bool>,
+    #[serde(default)]
+    pub optional: bool,
+}
+
+impl Manifest {
+    /**
+        Reads and parses the `Cargo.toml` manifest in the given directory,
+        which is only parsed again once the manifest has been modified.
+
+        Returns `None` if there is no manifest, or if it is not valid.
+    */
+    pub fn read_from_dir(dir: &Path) -> Option<Self> {
+        let manifest = MANIFESTS.get_or_parse(&dir.join(MANIFEST_FILE_NAME), "", |contents| {
+            contents.parse().ok()
+        })?;
+        manifest.as_ref().clone()
+    }
+
+    /**
+        Iterates over all dependencies in the manifest, including dev,
+        build, and target-specific ones, but not workspace dependencies.
+    */
+    pub fn all_dependencies(&self) -> impl Iterator<Item = (&str, &ManifestDependency)> {
+        let target_tables = self.target.values().flat_map(|target| {
+            [
+                &target.dependencies,
+                &target.dev_dependencies,
+                &target.build_dependencies,
+            ]
+        });
+
+        [
+            &self.dependencies,
+            &self.dev_dependencies,
+            &self.build_dependencies,
+        ]
+        .into_iter()
+        .chain(target_tables)
+        .flat_map(|table| table.iter().map(|(key, dep)| (key.as_str(), dep)))
+    }
+}
+
+impl ManifestPackage {
+    /**
+        Returns the registries that this package may be published to,
+        or `None` if it can not be published at all.
+
+        An empty list means that the publish target is not known,
+        such as when the `publish` field is inherited from a workspace.
+    */
+    pub fn publish_registries(&self) -> Option<Vec<String>> {
+        match &self.publish {
+            None | Some(toml::Value::Boolean(true)) => Some(vec![String::from(CRATES_IO)]),
+            Some(toml::Value::Boolean(false)) => None,
+            Some(toml::Value::Array(registries)) => {
+                let registries = registries
+                    .iter()
+                    .filter_map(|r| r.as_str().map(ToString::to_string))
+                    .collect::<Vec<_>>();
+                if registries.is_empty() {
+                    None
+                } else {
+                    Some(registries)
+                }
+            }
+            Some(_) => Some(Vec::new()),
+        }
+    }
+
+    /**
+        Checks if the user has opted in to publish-readiness lints,
+        using `publish-lints = true` in `[package.metadata.deputy]`.
+    */
+    pub fn has_publish_lints(&self) -> bool {
+        self.metadata
+            .as_ref()
+            .and_then(|m| m.deputy.as_ref())
+            .is_some_and(|d| d.publish_lints)
+    }
+}
+
+impl FromStr for Manifest {
+    type Err = toml::de::Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        toml::from_str(s)
+    }
+}
+
+impl ManifestWorkspace {
+    pub fn globs(&self) -> CargoWorkspaceGlobs {
+        CargoWorkspaceGlobs {
+            members: self.members.clone(),
+            exclude: self.exclude.clone(),
+        }
+    }
+}
+
+impl ManifestDependency {
+    /**
+        Checks if this dependency is inherited from
+        the workspace, using `workspace = true`.
+    */
+    pub fn is_inherited(&self) -> bool {
+        matches!(self, Self::Detailed(detail) if detail.workspace)
+    }
+
+    /**
+        Returns the version requirement of this dependency, if it
+        is a plain registry dependency without any other fields.
+
+        Inheriting such a dependency from the workspace
+        is guaranteed to not change its meaning.
+    */
+    pub fn plain_version(&self) -> Option<&str> {
+        match self {
+            Self::Simple(version) => Some(version.as_str()),
+            Self::Detailed(detail) => {
+                let is_plain = detail.path.is_none()
+                    && detail.git.is_none()
+                    && detail.registry.is_none()
+                    && detail.package.is_none()
+                    && !detail.workspace
+                    && detail.features.is_empty()
+                    && detail.default_features.is_none()
+                    && !detail.optional;
+                detail.version.as_deref().filter(|_| is_plain)
+            }
+        }
+    }
+}
@@ -1,8 +1,12 @@
+use std::path::Path;
+
 use futures::future::try_join_all;
 use tracing::debug;
 
 use async_language_server::{
-    lsp_types::{CompletionResponse, Diagnostic, DocumentDiagnosticParams, Hover, Position},
+    lsp_types::{
+        CompletionItem, CompletionResponse, Diagnostic, DocumentDiagnosticParams, Hover, Position,
+    },
     server::{Document, ServerResult},
     tree_sitter::Node,
 };
@@ -10,24 +14,60 @@ use async_language_server::{
 use deputy_clients::Clients;
 use deputy_parser::cargo;
 
+use crate::shared::ReleaseNotes;
+
 mod completion;
 mod constants;
 mod diagnostics;
 mod hover;
+mod manifest;
+mod sources;
 mod util;
+mod workspace;
 
-use completion::get_cargo_completions;
-use diagnostics::get_cargo_diagnostics;
-use hover::get_cargo_hover;
+use completion::{get_cargo_completions, get_cargo_entry_completions, resolve_cargo_entry};
+use diagnostics::{
+    get_cargo_diagnostics, get_cargo_publish_diagnostics, get_cargo_unused_diagnostics,
+    get_cargo_workspace_diagnostics,
+};
+use hover::{get_cargo_hover, get_cargo_release_notes};
+use manifest::MANIFEST_FILE_NAME;
+use sources::SourceCache;
+use workspace::WorkspaceCache;
 
 #[derive(Debug, Clone)]
 pub struct Cargo {
     clients: Clients,
+    sources: SourceCache,
+    workspaces: WorkspaceCache,
 }
 
 impl Cargo {
     pub(super) fn new(clients: Clients) -> Self {
-        Self { clients }
+        Self {
+            clients,
+            sources: SourceCache::default(),
+            workspaces: WorkspaceCache::default(),
+        }
+    }
+
+    /**
+        Invalidates cached data derived from the given file, returning
+        `true` if diagnostics of open documents may have changed.
+    */
+    pub(super) fn did_change_file(&self, path: &Path) -> bool {
+        if path.extension().is_some_and(|ext| ext == "rs") {
+            self.sources.invalidate(path);
+            return true;
+        }
+        if path
+            .file_name()
+            .is_some_and(|name| name == MANIFEST_FILE_NAME)
+        {
+            self.workspaces.invalidate(path);
+            return true;
+        }
+        false
     }
 
     pub(super) async fn hover(
@@ -42,7 +82,16 @@ impl Cargo {
 
         debug!("Hovering: {dep:?}");
 
-        get_cargo_hover(&self.clients, doc, dep).await
+        get_cargo_hover(&self.clients, doc, pos, dep).await
+    }
+
+    pub(super) async fn release_notes(
+        &self,
+        doc: &Document,
+        pos: Position,
+    ) -> Option<ReleaseNotes> {
+        let dep = cargo::find_dependency_at(doc, pos)?;
+        get_cargo_release_notes(&self.clients, doc, dep).await
     }
 
     pub(super) async fn completion(
@@ -52,7 +101,10 @@ impl Cargo {
         _node: Node<'_>,
     ) -> ServerResult<Option<CompletionResponse>> {
         let Some(dep) = cargo::find_dependency_at(doc, pos) else {
-            return Ok(None);
+            return match cargo::find_incomplete_dependency_at(doc, pos) {
+                Some(dep) => get_cargo_entry_completions(&self.clients, doc, dep).await,
+                None => Ok(None),
+            };
         };
 
         debug!("Fetching completions: {dep:?}");
@@ -60,15 +112,28 @@ impl Cargo {
         get_cargo_completions(&self.clients, doc, pos, dep).await
     }
 
+    pub(super) async fn completion_resolve(
+        &self,
+        item: CompletionItem,
+        name: &str,
+    ) -> ServerResult<CompletionItem> {
+        resolve_cargo_entry(&self.clients, item, name).await
+    }
+
     pub(super) async fn diagnostics(
         &self,
         doc: &Document,
         _params: DocumentDiagnosticParams,
     ) -> ServerResult<Vec<Diagnostic>> {
+        // Check the workspace and sources first, these only need the filesystem
+        let mut diagnostics = get_cargo_workspace_diagnostics(doc, &self.workspaces)?;
+        diagnostics.extend(get_cargo_unused_diagnostics(doc, &self.sources)?);
+        diagnostics.extend(get_cargo_publish_diagnostics(doc)?);
+
         // Find all dependencies
         let dependencies = cargo::find_all_dependencies(doc);
         if dependencies.is_empty() {
-            return Ok(Vec::new());
+            return Ok(diagnostics);
         }
 
         // Fetch all diagnostics concurrently
@@ -80,6 +145,7 @@ impl Cargo {
         )
         .await?;
 
-        Ok(results.into_iter().flatten().collect())
+        diagnostics.extend(results.into_iter().flatten());
+        Ok(diagnostics)
     }
 }
@@ -0,0 +1,341 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use tracing::debug;
+
+const SOURCE_DIRS: [&str; 4] = ["src", "tests", "benches", "examples"];
+const BUILD_SCRIPT: &str = "build.rs";
+
+/**
+    Crate names referenced from the Rust sources of a single package.
+
+    References from the build script are kept separately, since
+    those may only use build dependencies, and vice versa.
+*/
+#[derive(Debug, Clone, Default)]
+pub struct SourceReferences {
+    pub has_sources: bool,
+    pub sources: HashSet<String>,
+    pub build_script: HashSet<String>,
+}
+
+impl SourceReferences {
+    /**
+        Scans all Rust sources in the package directory for crate references.
+    */
+    pub fn scan(package_dir: &Path) -> Self {
+        let mut files = Vec::new();
+        for dir in SOURCE_DIRS {
+            collect_rust_files(&package_dir.join(dir), &mut files);
+        }
+
+        let mut this = Self::default();
+        for file in files {
+            if let Ok(contents) = fs::read_to_string(&file) {
+                this.has_sources = true;
+                collect_crate_references(&contents, &mut this.sources);
+            }
+        }
+
+        if let Ok(contents) = fs::read_to_string(package_dir.join(BUILD_SCRIPT)) {
+            this.has_sources = true;
+            collect_crate_references(&contents, &mut this.build_script);
+        }
+
+        this
+    }
+}
+
+/**
+    Cache for source references, keyed by package directory.
+
+    Entries are scanned lazily and must be invalidated
+    whenever any Rust file inside of the package changes.
+*/
+#[derive(Debug, Clone, Default)]
+pub struct SourceCache {
+    packages: Arc<Mutex<HashMap<PathBuf, Arc<SourceReferences>>>>,
+}
+
+impl SourceCache {
+    pub fn get(&self, package_dir: &Path) -> Arc<SourceReferences> {
+        if let Some(cached) = self.packages.lock().unwrap().get(package_dir) {
+            return Arc::clone(cached);
+        }
+
+        debug!("Scanning Rust sources in {}", package_dir.display());
+        let scanned = Arc::new(SourceReferences::scan(package_dir));
+
+        self.packages
+            .lock()
+            .unwrap()
+            .insert(package_dir.to_path_buf(), Arc::clone(&scanned));
+        scanned
+    }
+
+    /**
+        Invalidates any cached packages that contain the given file.
+    */
+    pub fn invalidate(&self, file: &Path) {
+        self.packages
+            .lock()
+            .unwrap()
+            .retain(|package_dir, _| !file.starts_with(package_dir));
+    }
+}
+
+fn collect_rust_files(dir: &Path, files: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_rust_files(&path, files);
+        } else if path.extension().is_some_and(|ext| ext == "rs") {
+            files.push(path);
+        }
+    }
+}
+
+/**
+    Collects identifiers that are used as the root of a path in Rust source code,
+    such as `serde` in `serde::Deserialize`, `use serde;`, `use {serde, tokio};`
+    or `extern crate serde;`.
+
+    This is a heuristic and not a full parser - comments and string literals are
+    skipped, but some references, such as ones generated by macros, are missed.
+*/
+fn collect_crate_references(source: &str, references: &mut HashSet<String>) {
+    let bytes = source.as_bytes();
+    let mut previous: [&str; 2] = ["", ""];
+    let mut group_depth: Option<usize> = None; // Braces of a `use { ... }` group
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let c = bytes[i];
+        if source[i..].starts_with("//") {
+            i = source[i..].find('\n').map_or(bytes.len(), |n| i + n);
+        } else if source[i..].starts_with("/*") {
+            i = source[i + 2..]
+                .find("*/")
+                .map_or(bytes.len(), |n| i + n + 4);
+        } else if c == b'"' {
+            i += 1;
+            while i < bytes.len() && bytes[i] != b'"' {
+                i += if bytes[i] == b'\\' { 2 } else { 1 };
+            }
+            i += 1;
+        } else if c == b'\'' {
+            // Skip char literals such as '"' or '\'', but not lifetimes
+            if bytes.get(i + 1) == Some(&b'\\') {
+                i = source[i + 2..]
+                    .find('\'')
+                    .map_or(bytes.len(), |n| i + n + 3);
+            } else if let Some(n) = source[i + 1..]
+                .chars()
+                .next()
+                .map(char::len_utf8)
+                .filter(|n| bytes.get(i + 1 + n) == Some(&b'\''))
+            {
+                i += n + 2;
+            } else {
+                i += 1;
+            }
+        } else if c == b'_' || c.is_ascii_alphabetic() {
+            let start = i;
+            while i < bytes.len() && (bytes[i] == b'_' || bytes[i].is_ascii_alphanumeric()) {
+                i += 1;
+            }
+            let ident = &source[start..i];
+            if let Some(end) = raw_string_end(source, ident, i) {
+                i = end;
+                previous = ["", ""];
+                continue;
+            }
+            let is_path_root = source[i..].trim_start().starts_with("::")
+                && !source[..start].trim_end().ends_with("::");
+            let is_used = previous[1] == "use" || previous == ["extern", "crate"];
+            let is_grouped =
+                group_depth == Some(1) && source[..start].trim_end().ends_with(['{', ',']);
+            if is_path_root || is_used || is_grouped {
+                references.insert(ident.to_string());
+            }
+            previous = [previous[1], ident];
+        } else {
+            match c {
+                b'{' if previous[1] == "use" => group_depth = Some(1),
+                b'{' => group_depth = group_depth.map(|depth| depth + 1),
+                b'}' => group_depth = group_depth.filter(|depth| *depth > 1).map(|d| d - 1),
+                b';' => group_depth = None,
+                _ => {}
+            }
+            if !c.is_ascii_whitespace() {
+                previous = ["", ""];
+            }
+            i += source[i..].chars().next().map_or(1, char::len_utf8);
+        }
+    }
+}
+
+/*
+    Finds the end of a raw string literal, if the identifier that
+    ends at the given index is the prefix of one, such as `r` in:
+
+    ```
+    r#"use "quoted" tokio::spawn"#
+    ```
+*/
+fn raw_string_end(source: &str, prefix: &str, index: usize) -> Option<usize> {
+    if !matches!(prefix, "r" | "br" | "cr") {
+        return None;
+    }
+    let rest = &source[index..];
+    let hashes = rest.len() - rest.trim_start_matches('#').len();
+    if !rest[hashes..].starts_with('"') {
+        return None;
+    }
+    let closing = format!("\"{}", "#".repeat(hashes));
+    let body_start = index + hashes + 1;
+    Some(
+        source[body_start..]
+            .find(&closing)
+            .map_or(source.len(), |n| body_start + n + closing.len()),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::shared::TempDir;
+
+    use super::*;
+
+    fn references(source: &str) -> Vec<String> {
+        let mut references = HashSet::new();
+        collect_crate_references(source, &mut references);
+        let mut references = references.into_iter().collect::<Vec<_>>();
+        references.sort();
+        references
+    }
+
+    #[test]
+    fn paths() {
+        assert_eq!(
+            references("fn main() { let v = serde_json::to_string(&tokio::spawn(x)); }"),
+            ["serde_json", "tokio"]
+        );
+        assert_eq!(references("let x = a::b::c::d();"), ["a"]);
+        assert_eq!(references("impl anyhow :: Context for X {}"), ["anyhow"]);
+    }
+
+    #[test]
+    fn use_statements() {
+        assert_eq!(references("use serde;"), ["serde"]);
+        assert_eq!(references("pub use tracing as log;"), ["tracing"]);
+        assert_eq!(
+            references("use serde::{Deserialize, Serialize};"),
+            ["serde"]
+        );
+        assert_eq!(references("extern crate proc_macro;"), ["proc_macro"]);
+    }
+
+    #[test]
+    fn grouped_use_statements() {
+        assert_eq!(references("use {serde, tokio};"), ["serde", "tokio"]);
+        assert_eq!(
+            references("use {\n    serde::Deserialize,\n    tokio as rt,\n    url,\n};"),
+            ["serde", "tokio", "url"]
+        );
+        assert_eq!(
+            references("use {futures::{Stream, stream}, regex};"),
+            ["futures", "regex"]
+        );
+        assert_eq!(references("use {a, b}; let c = { d };"), ["a", "b"]);
+    }
+
+    #[test]
+    fn comments_and_strings() {
+        assert_eq!(references("// serde::Deserialize"), Vec::<String>::new());
+        assert_eq!(references("/* use tokio; */"), Vec::<String>::new());
+        assert_eq!(
+            references(r#"let s = "regex::Regex";"#),
+            Vec::<String>::new()
+        );
+        assert_eq!(references(r#"let s = "\"url::Url";"#), Vec::<String>::new());
+    }
+
+    #[test]
+    fn raw_strings() {
+        assert_eq!(
+            references(r##"let s = r#"a "quoted" regex::Regex"#; url::Url::parse(s);"##),
+            ["url"]
+        );
+        assert_eq!(
+            references(r#"let s = r"regex::Regex"; let b = br"serde::de"; url::x();"#),
+            ["url"]
+        );
+        assert_eq!(
+            references(r###"let s = r##"a "# b"##; url::x();"###),
+            ["url"]
+        );
+        assert_eq!(references("let r = 1; url::x(r);"), ["url"]);
+    }
+
+    #[test]
+    fn byte_and_char_literals() {
+        assert_eq!(references("let c = b'\"'; bytes::Bytes::new();"), ["bytes"]);
+        assert_eq!(
+            references("let c = b'\\''; bytes::Bytes::new();"),
+            ["bytes"]
+        );
+        assert_eq!(
+            references("let s = b\"\\\"regex::Regex\"; bytes::Bytes::new();"),
+            ["bytes"]
+        );
+    }
+
+    #[test]
+    fn char_literals_and_lifetimes() {
+        assert_eq!(references("let c = '\"'; bytes::Bytes::new();"), ["bytes"]);
+        assert_eq!(references("let c = '\\''; bytes::Bytes::new();"), ["bytes"]);
+        assert_eq!(
+            references("fn f<'a>(x: &'a str) -> std::borrow::Cow<'a, str> {}"),
+            ["std"]
+        );
+    }
+
+    #[test]
+    fn scan_package() {
+        let temp = TempDir::new("sources");
+        let dir = temp.path();
+        fs::create_dir_all(dir.join("src/nested")).unwrap();
+        fs::write(dir.join("src/main.rs"), "use anyhow::Result;").unwrap();
+        fs::write(dir.join("src/nested/mod.rs"), "use {serde, tokio};").unwrap();
+        fs::write(dir.join("src/notes.txt"), "use ignored;").unwrap();
+        fs::write(dir.join("build.rs"), "fn main() { cc::Build::new(); }").unwrap();
+
+        let scanned = SourceReferences::scan(dir);
+
+        let mut sources = scanned.sources.into_iter().collect::<Vec<_>>();
+        sources.sort();
+        assert!(scanned.has_sources);
+        assert_eq!(sources, ["anyhow", "serde", "tokio"]);
+        assert_eq!(scanned.build_script.into_iter().collect::<Vec<_>>(), ["cc"]);
+    }
+
+    #[test]
+    fn invalidate() {
+        let cache = SourceCache::default();
+        let temp = TempDir::new("cache");
+        let dir = temp.path();
+        cache.get(dir);
+        cache.invalidate(&dir.with_extension("other").join("src/lib.rs"));
+        assert_eq!(cache.packages.lock().unwrap().len(), 1);
+        cache.invalidate(&dir.join("src/lib.rs"));
+        assert!(cache.packages.lock().unwrap().is_empty());
+    }
+}
@@ -1,4 +1,5 @@
-use deputy_versioning::{VersionReq, Versioned};
+use deputy_clients::crates::models::IndexMetadata;
+use deputy_versioning::{Version, VersionReq, VersionReqExt, Versioned};
 
 use super::Clients;
 
@@ -30,3 +31,55 @@ pub async fn get_features(clients: &Clients, dname: &str, dver: &str) -> Option<
             .collect(),
     )
 }
+
+/**
+    Returns the version that a requirement will most directly resolve to - the
+    pinned version for requirements such as `=1.2.3`, otherwise the lower bound.
+*/
+pub fn get_required_version(req: &VersionReq) -> Version {
+    req.exact_version().unwrap_or_else(|| req.minimum_version())
+}
+
+/**
+    Finds the index metadata for the exact given version, if it exists.
+*/
+pub fn find_version<'a>(
+    metas: &'a [IndexMetadata],
+    version: &Version,
+) -> Option<&'a IndexMetadata> {
+    metas
+        .iter()
+        .find(|m| m.parse_version().is_ok_and(|v| v == *version))
+}
+
+/**
+    Finds the non-yanked version that is closest to the given version, while still
+    being semver compatible with it. Newer versions are preferred over older ones.
+*/
+pub fn find_nearest_unyanked_version(
+    metas: &[IndexMetadata],
+    version: &Version,
+) -> Option<Version> {
+    let mut compatible = metas
+        .iter()
+        .filter(|m| !m.yanked)
+        .filter_map(|m| m.parse_version().ok())
+        .filter(|v| v.pre.is_empty() && is_semver_compatible(v, version))
+        .collect::<Vec<_>>();
+    compatible.sort();
+
+    let newer = compatible.iter().find(|v| *v > version).cloned();
+    newer.or_else(|| compatible.into_iter().rev().find(|v| v < version))
+}
+
+fn is_semver_compatible(a: &Version, b: &Version) -> bool {
+    if a.major != b.major {
+        false
+    } else if a.major != 0 {
+        true
+    } else if a.minor != b.minor {
+        false
+    } else {
+        a.minor != 0 || a.patch == b.patch
+    }
+}
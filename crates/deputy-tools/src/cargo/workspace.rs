@@ -0,0 +1,390 @@
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Component, Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use glob::Pattern;
+use tracing::debug;
+
+use deputy_parser::cargo::CargoWorkspaceGlobs;
+
+use super::manifest::{Manifest, ManifestDependency, ManifestWorkspace};
+
+type MemberManifests = HashMap<String, Option<Manifest>>;
+
+#[derive(Debug)]
+struct CachedWorkspace {
+    globs: CargoWorkspaceGlobs,
+    member_dirs: Arc<Vec<String>>,
+    member_manifests: MemberManifests,
+}
+
+/**
+    Cache for the member directories matched by the globs of workspaces,
+    keyed by the workspace root directory, along with the globs used,
+    and for the manifests that have been read from those members.
+
+    Entries are expanded lazily and must be invalidated whenever
+    a manifest inside of the workspace is created, changed or removed.
+*/
+#[derive(Debug, Clone, Default)]
+pub struct WorkspaceCache {
+    workspaces: Arc<Mutex<HashMap<PathBuf, CachedWorkspace>>>,
+}
+
+impl WorkspaceCache {
+    fn member_dirs(&self, root_dir: &Path, globs: &CargoWorkspaceGlobs) -> Arc<Vec<String>> {
+        if let Some(cached) = self
+            .workspaces
+            .lock()
+            .unwrap()
+            .get(root_dir)
+            .filter(|cached| &cached.globs == globs)
+        {
+            return Arc::clone(&cached.member_dirs);
+        }
+
+        debug!("Expanding workspace members in {}", root_dir.display());
+        let expanded = Arc::new(expand_member_globs(root_dir, globs));
+
+        self.workspaces.lock().unwrap().insert(
+            root_dir.to_path_buf(),
+            CachedWorkspace {
+                globs: globs.clone(),
+                member_dirs: Arc::clone(&expanded),
+                member_manifests: MemberManifests::new(),
+            },
+        );
+        expanded
+    }
+
+    fn member_manifest(&self, root_dir: &Path, relative: &str) -> Option<Manifest> {
+        if let Some(cached) = self
+            .workspaces
+            .lock()
+            .unwrap()
+            .get(root_dir)
+            .and_then(|cached| cached.member_manifests.get(relative))
+        {
+            return cached.clone();
+        }
+
+        let manifest = Manifest::read_from_dir(&root_dir.join(relative));
+
+        // Manifests are only cached along with the member directories, so both are invalidated together
+        if let Some(cached) = self.workspaces.lock().unwrap().get_mut(root_dir) {
+            cached
+                .member_manifests
+                .insert(relative.to_string(), manifest.clone());
+        }
+        manifest
+    }
+
+    /**
+        Invalidates any cached workspaces that contain the given file.
+    */
+    pub fn invalidate(&self, file: &Path) {
+        self.workspaces
+            .lock()
+            .unwrap()
+            .retain(|root_dir, _| !file.starts_with(root_dir));
+    }
+}
+
+/**
+    A Cargo workspace, consisting of a root manifest and all of its members.
+
+    Member manifests are read from disk, or from the cache - the root manifest
+    is given directly, so that the contents of an open document may be used.
+*/
+#[derive(Debug, Clone)]
+pub struct Workspace {
+    pub root_dir: PathBuf,
+    pub root_manifest: Manifest,
+    pub members: Vec<Manifest>,
+}
+
+impl Workspace {
+    /**
+        Creates a workspace from its root directory and manifest,
+        expanding the given globs to find all of the members.
+
+        The root manifest is also considered a member, if it has a package,
+        and so are any of its path dependencies inside of the workspace.
+    */
+    pub fn from_root(
+        root_dir: &Path,
+        root_manifest: Manifest,
+        globs: &CargoWorkspaceGlobs,
+        cache: &WorkspaceCache,
+    ) -> Self {
+        let mut members = Vec::new();
+        if root_manifest.package.is_some() {
+            members.push(root_manifest.clone());
+        }
+
+        let member_dirs = cache.member_dirs(root_dir, globs);
+        let path_members = path_members(root_dir, &root_manifest, globs);
+        let mut visited = HashSet::new();
+        for relative in member_dirs.iter().chain(&path_members) {
+            if !visited.insert(relative) {
+                continue;
+            }
+            if let Some(manifest) = cache.member_manifest(root_dir, relative) {
+                members.push(manifest);
+            }
+        }
+
+        Self {
+            root_dir: root_dir.to_path_buf(),
+            root_manifest,
+            members,
+        }
+    }
+
+    /**
+        Discovers the workspace that the package in the given directory belongs to.
+
+        Same as Cargo, this uses the `package.workspace` field if it is
+        set, and otherwise searches upwards for the closest workspace root.
+    */
+    pub fn discover(
+        member_dir: &Path,
+        member_manifest: &Manifest,
+        cache: &WorkspaceCache,
+    ) -> Option<Self> {
+        if let Some(root) = member_manifest
+            .package
+            .as_ref()
+            .and_then(|package| package.workspace.as_deref())
+        {
+            let root_dir = member_dir.join(root);
+            let root_manifest = Manifest::read_from_dir(&root_dir)?;
+            let globs = root_manifest.workspace.as_ref()?.globs();
+            return Some(Self::from_root(&root_dir, root_manifest, &globs, cache));
+        }
+
+        for root_dir in member_dir.ancestors().skip(1) {
+            let Some(root_manifest) = Manifest::read_from_dir(root_dir) else {
+                continue;
+            };
+            let Some(globs) = root_manifest
+                .workspace
+                .as_ref()
+                .map(ManifestWorkspace::globs)
+            else {
+                continue;
+            };
+
+            // The closest workspace root must include the package, or it is not a member
+            let relative = relative_path(root_dir, member_dir)?;
+            if globs.is_member(&relative)
+                || path_members(root_dir, &root_manifest, &globs).contains(&relative)
+            {
+                return Some(Self::from_root(root_dir, root_manifest, &globs, cache));
+            }
+            return None;
+        }
+
+        None
+    }
+
+    /**
+        Checks if any member inherits the given workspace
+        dependency key using `workspace = true`.
+    */
+    pub fn is_inherited(&self, key: &str) -> bool {
+        self.members.iter().any(|member| {
+            member
+                .all_dependencies()
+                .any(|(k, dep)| k == key && dep.is_inherited())
+        })
+    }
+}
+
+fn expand_member_globs(root_dir: &Path, globs: &CargoWorkspaceGlobs) -> Vec<String> {
+    let root_escaped = Pattern::escape(&root_dir.to_string_lossy());
+    let mut member_dirs = Vec::new();
+    for member_glob in &globs.members {
+        let Ok(paths) = glob::glob(&format!("{root_escaped}/{member_glob}")) else {
+            continue;
+        };
+        for dir in paths.flatten() {
+            let Some(relative) = relative_path(root_dir, &dir) else {
+                continue;
+            };
+            if !relative.is_empty() && !globs.is_excluded(&relative) {
+                member_dirs.push(relative);
+            }
+        }
+    }
+    member_dirs
+}
+
+/*
+    Same as Cargo, path dependencies of the root package are implicitly members
+    of the workspace, as long as they are inside of it and not excluded:
+
+    ```toml
+    [package]
+    name = "app"
+
+    [dependencies]
+    app-macros = { path = "macros" }
+    ```
+*/
+fn path_members(
+    root_dir: &Path,
+    root_manifest: &Manifest,
+    globs: &CargoWorkspaceGlobs,
+) -> Vec<String> {
+    if root_manifest.package.is_none() {
+        return Vec::new();
+    }
+
+    root_manifest
+        .all_dependencies()
+        .filter_map(|(_, dep)| match dep {
+            ManifestDependency::Detailed(detail) => detail.path.as_deref(),
+            ManifestDependency::Simple(_) => None,
+        })
+        .filter_map(|path| relative_path(root_dir, &normalize_path(&root_dir.join(path))))
+        .filter(|relative| !relative.is_empty() && !globs.is_excluded(relative))
+        .collect()
+}
+
+/*
+    Resolves `.` and `..` components without touching the file system,
+    so that paths such as `crates/app/../macros` can be compared.
+*/
+fn normalize_path(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
+
+fn relative_path(root_dir: &Path, path: &Path) -> Option<String> {
+    let relative = path.strip_prefix(root_dir).ok()?;
+    let parts = relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>();
+    Some(parts.join("/"))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::shared::TempDir;
+
+    use super::*;
+
+    fn write_manifest(dir: &Path, contents: &str) {
+        fs::create_dir_all(dir).unwrap();
+        fs::write(dir.join("Cargo.toml"), contents).unwrap();
+    }
+
+    // The minimal manifest model has no package names, so members are told apart by description
+    fn member_names(workspace: &Workspace) -> Vec<String> {
+        let mut names = workspace
+            .members
+            .iter()
+            .filter_map(|member| {
+                let description = member.package.as_ref()?.description.as_ref()?;
+                Some(description.as_str()?.to_string())
+            })
+            .collect::<Vec<_>>();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn path_dependencies_are_members() {
+        let temp = TempDir::new("workspace");
+        let root = temp.path().to_path_buf();
+        let root_manifest = "
+            [package]
+            description = \"app\"
+
+            [dependencies]
+            app-macros = { path = \"macros\" }
+            app-nested = { path = \"./crates/../nested\" }
+            app-ignored = { path = \"ignored\" }
+            outside = { path = \"../outside\" }
+
+            [workspace]
+            members = [\"crates/*\"]
+            exclude = [\"ignored\"]
+        ";
+        write_manifest(&root, root_manifest);
+        write_manifest(
+            &root.join("crates/core"),
+            "[package]\ndescription = \"app-core\"",
+        );
+        write_manifest(
+            &root.join("macros"),
+            "[package]\ndescription = \"app-macros\"",
+        );
+        write_manifest(
+            &root.join("nested"),
+            "[package]\ndescription = \"app-nested\"",
+        );
+        write_manifest(
+            &root.join("ignored"),
+            "[package]\ndescription = \"app-ignored\"",
+        );
+
+        let cache = WorkspaceCache::default();
+        let manifest = Manifest::read_from_dir(&root).unwrap();
+        let globs = manifest.workspace.as_ref().unwrap().globs();
+        let workspace = Workspace::from_root(&root, manifest, &globs, &cache);
+        assert_eq!(
+            member_names(&workspace),
+            ["app", "app-core", "app-macros", "app-nested"]
+        );
+
+        let macros = Manifest::read_from_dir(&root.join("macros")).unwrap();
+        let discovered = Workspace::discover(&root.join("macros"), &macros, &cache);
+        let ignored = Manifest::read_from_dir(&root.join("ignored")).unwrap();
+        let not_discovered = Workspace::discover(&root.join("ignored"), &ignored, &cache);
+
+        assert_eq!(discovered.map(|w| w.root_dir), Some(root));
+        assert!(not_discovered.is_none());
+    }
+
+    #[test]
+    fn member_manifests_are_cached() {
+        let temp = TempDir::new("workspace-cache");
+        let root = temp.path().to_path_buf();
+        write_manifest(&root, "[workspace]\nmembers = [\"crates/*\"]");
+        write_manifest(
+            &root.join("crates/core"),
+            "[package]\ndescription = \"old\"",
+        );
+
+        let cache = WorkspaceCache::default();
+        let read_workspace = || {
+            let manifest = Manifest::read_from_dir(&root).unwrap();
+            let globs = manifest.workspace.as_ref().unwrap().globs();
+            Workspace::from_root(&root, manifest, &globs, &cache)
+        };
+        assert_eq!(member_names(&read_workspace()), ["old"]);
+
+        let core_manifest = root.join("crates/core/Cargo.toml");
+        fs::write(&core_manifest, "[package]\ndescription = \"new\"").unwrap();
+        assert_eq!(member_names(&read_workspace()), ["old"]);
+
+        cache.invalidate(&core_manifest);
+        assert_eq!(member_names(&read_workspace()), ["new"]);
+    }
+}
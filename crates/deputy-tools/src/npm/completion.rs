@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use async_language_server::{
     lsp_types::{
         CompletionItem, CompletionItemKind, CompletionResponse, CompletionTextEdit, Position,
@@ -10,13 +12,53 @@ use async_language_server::{
 };
 use tracing::debug;
 
-use deputy_parser::npm;
-use deputy_versioning::Versioned;
+use deputy_clients::npm::NpmRegistry;
+use deputy_parser::{
+    npm::{self, NpmIncompleteDependency},
+    utils::unquote,
+};
+use deputy_versioning::{Version, Versioned};
+
+use crate::shared::{
+    ENTRY_VERSION_PLACEHOLDER, EntryCompletion, EntryMetadata, MatchQuality,
+    entry_completion_response, fetch_entry_versions, format_count, is_entry_version_missing,
+    rank_matches, resolve_entry_version,
+};
 
 use super::Clients;
-use super::constants::top_npm_packages_prefixed;
+use super::constants::top_npm_packages_matching;
+use super::npmrc::NpmConfig;
+use super::package_manager::{PACKAGE_MANAGERS, registry_packages};
+use super::spec::NpmSpec;
+use super::util::split_partial_version;
 
 const MAXIMUM_PACKAGES_SHOWN: usize = 64;
+const MINIMUM_PACKAGES_BEFORE_FETCH: usize = 16; // Less than 16 packages found statically = fetch dynamically
+
+/**
+    A package found either in the bundled list of top packages,
+    or by searching the registry, which also has more metadata.
+*/
+#[derive(Debug, Clone)]
+struct NpmPackage {
+    name: String,
+    popularity: u64,
+    description: Option<String>,
+    weekly_downloads: Option<u64>,
+}
+
+impl NpmPackage {
+    fn detail(&self) -> Option<String> {
+        let downloads = self
+            .weekly_downloads
+            .map(|d| format!("{} weekly downloads", format_count(d)));
+        match (self.description.as_deref(), downloads) {
+            (Some(description), Some(downloads)) => Some(format!("{description} - {downloads}")),
+            (Some(description), None) => Some(description.to_string()),
+            (None, downloads) => downloads,
+        }
+    }
+}
 
 pub async fn get_npm_completions(
     clients: &Clients,
@@ -24,50 +66,309 @@ pub async fn get_npm_completions(
     pos: Position,
     node: Node<'_>,
 ) -> ServerResult<Option<CompletionResponse>> {
-    let Some(dep) = npm::parse_dependency(node) else {
+    let Some(dep) = npm::parse_dependency(doc, node) else {
         return Ok(None);
     };
 
     let (name, spec) = dep.text(doc);
-    if spec.starts_with("file:") || spec.starts_with("github:") || spec.starts_with("git+") {
-        return Ok(None); // Ignore these spec formats, for now
-    }
 
-    // Try to complete specs (versions)
+    // Try to complete specs (versions) - only registry packages have versions to complete
     if ts_range_contains_lsp_position(dep.spec.range(), pos) {
+        let (name, prefix, range) = match NpmSpec::parse(&spec) {
+            NpmSpec::Range(range) => (name.as_str(), "", range),
+            NpmSpec::Alias {
+                name: aliased,
+                range,
+            } if spec.ends_with(&format!("@{range}")) => {
+                (aliased, &spec[..spec.len() - range.len()], range)
+            }
+            _ => return Ok(None),
+        };
         debug!("Completing version: {dep:?}");
+        let registry = NpmConfig::for_document(doc.url()).registry_for_package(name);
         return complete_spec(
             clients,
-            name.as_str(),
-            spec.as_str(),
+            &registry,
+            name,
+            prefix,
+            range,
             ts_range_to_lsp_range(dep.spec.range()),
         )
         .await;
     }
 
-    // Try to complete names
-    if ts_range_contains_lsp_position(dep.name.range(), pos) {
+    // Try to complete names - keys of overrides may also contain selectors, which are kept as-is
+    let is_plain_name = doc.node_text(dep.name).trim_matches('"') == name;
+    if ts_range_contains_lsp_position(dep.name.range(), pos) && is_plain_name {
         debug!("Completing name: {dep:?}");
-        return complete_name(name.as_str(), ts_range_to_lsp_range(dep.name.range()));
+        return complete_name(
+            clients,
+            name.as_str(),
+            ts_range_to_lsp_range(dep.name.range()),
+        )
+        .await;
     }
 
     // No completions yet - probably empty spec
     Ok(None)
 }
 
-fn complete_name(name: &str, range: Range) -> ServerResult<Option<CompletionResponse>> {
-    let packages = top_npm_packages_prefixed(name, MAXIMUM_PACKAGES_SHOWN)
+/**
+    Completes the `packageManager` field, with the names of package managers
+    until an `@` has been typed, and the versions of the package manager after it.
+
+    Completing a version replaces any hash, since it would be for another version.
+*/
+pub async fn get_package_manager_completions(
+    clients: &Clients,
+    doc: &Document,
+    node: Node<'_>,
+) -> ServerResult<Option<CompletionResponse>> {
+    let value = unquote(doc.node_text(node));
+    let range = ts_range_to_lsp_range(node.range()).shrink(1, 1);
+
+    let Some((name, rest)) = value.split_once('@') else {
+        debug!("Completing package manager name: {value}");
+        let items = PACKAGE_MANAGERS
+            .iter()
+            .enumerate()
+            .map(|(index, name)| CompletionItem {
+                label: (*name).to_string(),
+                kind: Some(CompletionItemKind::VALUE),
+                sort_text: Some(format!("{index:0>5}")),
+                text_edit: Some(CompletionTextEdit::Edit(TextEdit {
+                    new_text: format!("{name}@"),
+                    range,
+                })),
+                ..Default::default()
+            })
+            .collect::<Vec<_>>();
+        return Ok(Some(CompletionResponse::Array(items)));
+    };
+
+    debug!("Completing package manager version: {value}");
+    let config = NpmConfig::for_document(doc.url());
+    let mut versions = Vec::new();
+    for package in registry_packages(name) {
+        let registry = config.registry_for_package(package);
+        if let Ok(metadata) = clients
+            .npm
+            .get_registry_metadata_abbreviated(&registry, package)
+            .await
+        {
+            versions.extend(metadata.versions.into_values());
+        }
+    }
+
+    let partial = rest.split('+').next().unwrap_or(rest);
+    let items = partial
+        .extract_completion_versions(versions)
         .into_iter()
-        .cloned()
+        .take(MAXIMUM_PACKAGES_SHOWN)
+        .enumerate()
+        .map(|(index, potential_version)| {
+            let new_text = format!("{name}@{}", potential_version.item_version_raw);
+            CompletionItem {
+                label: potential_version.item_version_raw.to_string(),
+                kind: Some(CompletionItemKind::VALUE),
+                sort_text: Some(format!("{index:0>5}")),
+                filter_text: Some(new_text.clone()),
+                text_edit: Some(CompletionTextEdit::Edit(TextEdit { new_text, range })),
+                ..Default::default()
+            }
+        })
         .collect::<Vec<_>>();
+    Ok(Some(CompletionResponse::Array(items)))
+}
+
+/**
+    Completes a dependency that only has its name typed out so far, inserting
+    the whole entry, along with a trailing comma if more dependencies follow.
+*/
+pub async fn get_npm_entry_completions(
+    clients: &Clients,
+    doc: &Document,
+    dep: NpmIncompleteDependency<'_>,
+) -> ServerResult<Option<CompletionResponse>> {
+    let name = dep.text(doc);
+    let range = ts_range_to_lsp_range(dep.range());
+    let separator = if dep.error.next_named_sibling().is_some() {
+        ","
+    } else {
+        ""
+    };
+
+    debug!("Completing entry: {dep:?}");
+
+    let config = NpmConfig::for_document(doc.url());
+    let packages = top_npm_packages_matching(name.as_str(), MAXIMUM_PACKAGES_SHOWN);
+    let versions = fetch_entry_versions(&packages, |package| {
+        let registry = config.registry_for_package(package.name);
+        async move { latest_package_version(clients, &registry, package.name).await }
+    })
+    .await;
 
     let items = packages
         .into_iter()
-        .map(|package| CompletionItem {
-            label: package.name.to_string(),
+        .zip(versions)
+        .enumerate()
+        .map(|(index, (package, version))| {
+            let entry = EntryCompletion {
+                label: package.name.to_string(),
+                label_detail: None,
+                detail: None,
+                snippet: format!(
+                    "\"{}\": \"^{ENTRY_VERSION_PLACEHOLDER}\"{separator}",
+                    package.name
+                ),
+                range,
+                sort_text: Some(format!("{index:0>5}")),
+                version,
+            };
+            entry.into_completion_item(
+                doc.url().clone(),
+                EntryMetadata::Npm {
+                    name: package.name.to_string(),
+                },
+            )
+        })
+        .collect::<Vec<_>>();
+    Ok(Some(entry_completion_response(items)))
+}
+
+/**
+    Adds the latest version of a package to a dependency entry
+    that did not get its version filled in with the list.
+*/
+pub async fn resolve_npm_entry(
+    clients: &Clients,
+    mut item: CompletionItem,
+    registry: &NpmRegistry,
+    name: &str,
+) -> ServerResult<CompletionItem> {
+    if !is_entry_version_missing(&item) {
+        return Ok(item);
+    }
+    if let Some(latest) = latest_package_version(clients, registry, name).await {
+        resolve_entry_version(&mut item, &latest);
+    }
+    Ok(item)
+}
+
+/**
+    Finds the version that a new dependency on a package should use - the
+    version tagged as `latest`, unless it has been deprecated, in which
+    case the newest stable version that is not deprecated is used instead.
+*/
+async fn latest_package_version(
+    clients: &Clients,
+    registry: &NpmRegistry,
+    name: &str,
+) -> Option<String> {
+    let metadata = clients
+        .npm
+        .get_registry_metadata_abbreviated(registry, name)
+        .await
+        .ok()?;
+
+    let tagged = metadata
+        .dist_tags
+        .get("latest")
+        .and_then(|tag| metadata.versions.get(tag))
+        .filter(|v| !v.deprecated());
+    if let Some(tagged) = tagged {
+        return Some(tagged.version.clone());
+    }
+
+    let latest = Version::new(0, 0, 0)
+        .extract_latest_version_filtered(metadata.versions.into_values(), |v| {
+            !v.item.deprecated()
+        })?;
+    Some(latest.item_version.to_string())
+}
+
+/**
+    Finds packages matching the given name, ranked by match quality and popularity.
+
+    Searches the registry if there are only a few good matches in the bundled list
+    of top packages, which also finds scoped packages that are not bundled at all.
+*/
+async fn find_packages(clients: &Clients, name: &str) -> Vec<NpmPackage> {
+    let mut packages = top_npm_packages_matching(name, MAXIMUM_PACKAGES_SHOWN)
+        .into_iter()
+        .map(|p| NpmPackage {
+            name: p.name.to_string(),
+            popularity: p.popularity,
+            description: None,
+            weekly_downloads: None,
+        })
+        .collect::<Vec<_>>();
+
+    let good_matches = packages
+        .iter()
+        .filter(|p| MatchQuality::of(&p.name, name).is_some_and(|q| q <= MatchQuality::Substring))
+        .count();
+    if good_matches >= MINIMUM_PACKAGES_BEFORE_FETCH || name.trim().is_empty() {
+        return packages;
+    }
+
+    let Ok(results) = clients.npm.search_packages(name).await else {
+        return packages;
+    };
+
+    let count_prev = packages.len();
+
+    // Search results have more metadata, so prefer those for bundled packages
+    let mut indices = packages
+        .iter()
+        .enumerate()
+        .map(|(index, p)| (p.name.clone(), index))
+        .collect::<HashMap<_, _>>();
+    for result in results.objects {
+        let package = NpmPackage {
+            popularity: result.downloads.as_ref().map_or(0, |d| d.weekly),
+            weekly_downloads: result.downloads.map(|d| d.weekly),
+            description: result.package.description,
+            name: result.package.name,
+        };
+        if let Some(index) = indices.get(&package.name) {
+            packages[*index] = package;
+        } else {
+            indices.insert(package.name.clone(), packages.len());
+            packages.push(package);
+        }
+    }
+
+    packages = rank_matches(packages, name, |p| &p.name, |p| p.popularity);
+    packages.truncate(MAXIMUM_PACKAGES_SHOWN);
+
+    let count_after = packages.len();
+    if count_after > count_prev {
+        debug!(
+            "Found {} additional packages for '{name}'",
+            count_after.saturating_sub(count_prev),
+        );
+    }
+
+    packages
+}
+
+async fn complete_name(
+    clients: &Clients,
+    name: &str,
+    range: Range,
+) -> ServerResult<Option<CompletionResponse>> {
+    let items = find_packages(clients, name)
+        .await
+        .into_iter()
+        .enumerate()
+        .map(|(index, package)| CompletionItem {
+            label: package.name.clone(),
             kind: Some(CompletionItemKind::VALUE),
+            sort_text: Some(format!("{index:0>5}")),
+            detail: package.detail(),
             text_edit: Some(CompletionTextEdit::Edit(TextEdit {
-                new_text: package.name.to_string(),
+                new_text: package.name.clone(),
                 range: range.shrink(1, 1),
             })),
             ..Default::default()
@@ -76,33 +377,82 @@ fn complete_name(name: &str, range: Range) -> ServerResult<Option<CompletionResp
     Ok(Some(CompletionResponse::Array(items)))
 }
 
+/**
+    Completes the version in a spec, keeping the given prefix, which
+    is used for aliases such as `npm:other@` that are not part of the range.
+*/
 async fn complete_spec(
     clients: &Clients,
+    registry: &NpmRegistry,
     name: &str,
+    prefix: &str,
     spec: &str,
     range: Range,
 ) -> ServerResult<Option<CompletionResponse>> {
-    let metadata = match clients.npm.get_registry_metadata(name).await {
-        Err(_) => return Ok(None),
-        Ok(m) => m,
+    let Ok(metadata) = clients
+        .npm
+        .get_registry_metadata_abbreviated(registry, name)
+        .await
+    else {
+        return Ok(None);
     };
 
-    let valid_vec = spec
-        .extract_completion_versions(metadata.versions.into_values())
+    // Only complete the last version in ranges such as `>=1.0.0 <2`, keeping the rest
+    let (head, partial) = split_partial_version(spec);
+
+    // Tags can not be combined with ranges, so only suggest them for the whole spec
+    let mut tags = if head.is_empty() {
+        metadata
+            .dist_tags
+            .iter()
+            .filter(|(tag, _)| tag.starts_with(partial))
+            .collect::<Vec<_>>()
+    } else {
+        Vec::new()
+    };
+    tags.sort_unstable_by_key(|(tag, _)| (tag.as_str() != "latest", tag.as_str()));
+    let mut valid_vec = tags
         .into_iter()
-        .take(MAXIMUM_PACKAGES_SHOWN)
         .enumerate()
-        .map(|(index, potential_version)| CompletionItem {
-            label: potential_version.item_version_raw.to_string(),
-            kind: Some(CompletionItemKind::VALUE),
-            sort_text: Some(format!("{index:0>5}")),
-            text_edit: Some(CompletionTextEdit::Edit(TextEdit {
-                new_text: potential_version.item_version_raw.to_string(),
-                range: range.shrink(1, 1),
-            })),
-            ..Default::default()
+        .map(|(index, (tag, tag_version))| {
+            let new_text = format!("{prefix}{tag}");
+            CompletionItem {
+                label: tag.clone(),
+                kind: Some(CompletionItemKind::CONSTANT),
+                sort_text: Some(format!("{index:0>5}")),
+                detail: Some(tag_version.clone()),
+                filter_text: Some(new_text.clone()),
+                text_edit: Some(CompletionTextEdit::Edit(TextEdit {
+                    new_text,
+                    range: range.shrink(1, 1),
+                })),
+                ..Default::default()
+            }
         })
         .collect::<Vec<_>>();
 
+    let version_offset = valid_vec.len();
+    let versions = partial
+        .extract_completion_versions(metadata.versions.into_values())
+        .into_iter()
+        .take(MAXIMUM_PACKAGES_SHOWN)
+        .enumerate()
+        .map(|(index, potential_version)| {
+            let index = version_offset + index;
+            let new_text = format!("{prefix}{head}{}", potential_version.item_version_raw);
+            CompletionItem {
+                label: potential_version.item_version_raw.to_string(),
+                kind: Some(CompletionItemKind::VALUE),
+                sort_text: Some(format!("{index:0>5}")),
+                filter_text: Some(new_text.clone()),
+                text_edit: Some(CompletionTextEdit::Edit(TextEdit {
+                    new_text,
+                    range: range.shrink(1, 1),
+                })),
+                ..Default::default()
+            }
+        });
+    valid_vec.extend(versions);
+
     Ok(Some(CompletionResponse::Array(valid_vec)))
 }
@@ -1,46 +1,343 @@
+use std::{collections::HashMap, path::Path, sync::Arc};
+
+use futures::future::join_all;
+
 use async_language_server::{
-    lsp_types::{Diagnostic, DiagnosticSeverity, DiagnosticTag},
+    lsp_types::{Diagnostic, DiagnosticSeverity, DiagnosticTag, Range},
     server::{Document, ServerResult},
     text_utils::RangeExt,
     tree_sitter::Node,
     tree_sitter_utils::ts_range_to_lsp_range,
 };
 
-use deputy_parser::npm;
+use deputy_clients::npm::{NpmRegistry, models::RegistryMetadataAbbreviated};
+use deputy_parser::npm::{self, DependencyKind, NpmDependency};
+use deputy_parser::utils::unquote;
 use deputy_versioning::{VersionReqExt, Versioned};
 
 use crate::shared::{CodeActionMetadata, ResolveContext};
 
 use super::Clients;
+use super::engines::NpmNodeEngine;
+use super::lockfile::NpmLockfile;
+use super::npmrc::NpmConfig;
+use super::package_manager::{NpmPackageManager, PACKAGE_MANAGERS, corepack_hash};
+use super::spec::{NpmGitSpec, NpmSpec};
+use super::util::{find_resolved_version, parse_tag, parse_version_req, replacement_spec};
+use super::workspace::NpmWorkspace;
+
+const MAXIMUM_TAGS_FETCHED: usize = 1000; // Same as the GitHub client, which follows pagination up to 1000 tags
+
+/**
+    State that is shared between the diagnostics for all dependencies in a document.
+*/
+#[derive(Debug, Clone)]
+pub struct NpmDiagnosticsContext {
+    pub config: NpmConfig,
+    pub lockfile: Option<Arc<NpmLockfile>>,
+    pub node_engine: Option<NpmNodeEngine>,
+    pub declared: HashMap<String, String>,
+}
+
+impl NpmDiagnosticsContext {
+    pub fn for_document(doc: &Document) -> Self {
+        // Peers may be declared in any dependency section, so all of them are collected
+        let declared = npm::find_all_dependencies(doc)
+            .into_iter()
+            .filter_map(|node| npm::parse_dependency(doc, node))
+            .filter(|dep| !dep.kind.is_override())
+            .map(|dep| dep.text(doc))
+            .collect();
+        Self {
+            config: NpmConfig::for_document(doc.url()),
+            lockfile: NpmLockfile::for_document(doc.url()),
+            node_engine: NpmNodeEngine::for_document(doc),
+            declared,
+        }
+    }
+}
 
 pub async fn get_npm_diagnostics(
     clients: &Clients,
+    context: &NpmDiagnosticsContext,
     doc: &Document,
     node: Node<'_>,
 ) -> ServerResult<Vec<Diagnostic>> {
-    let Some(dep) = npm::parse_dependency(node) else {
+    let Some(dep) = npm::parse_dependency(doc, node) else {
         return Ok(Vec::new());
     };
 
     let (name, spec) = dep.text(doc);
-    if spec.starts_with("file:") || spec.starts_with("github:") || spec.starts_with("git+") {
-        return Ok(Vec::new()); // Ignore these spec formats, for now
+    if dep.kind.is_override() {
+        return get_override_diagnostics(clients, context, doc, &dep, &name, &spec).await;
     }
 
-    let Ok(version_req) = spec.parse_version_req() else {
-        return Ok(Vec::new());
+    let (name_node, registry_name, range) = match NpmSpec::parse(&spec) {
+        NpmSpec::Range(range) => (dep.name, name.as_str(), range),
+        NpmSpec::Alias {
+            name: aliased,
+            range,
+        } => (dep.spec, aliased, range),
+        NpmSpec::Git(git) => return Ok(get_git_diagnostics(clients, &dep, git).await),
+        NpmSpec::File(path) => return Ok(get_path_diagnostics(doc, &dep, path, "file")),
+        NpmSpec::Link(path) => return Ok(get_path_diagnostics(doc, &dep, path, "link")),
+        NpmSpec::Workspace(range) => {
+            return Ok(get_workspace_diagnostics(doc, &dep, &name, range));
+        }
+        NpmSpec::Tarball(_) => return Ok(Vec::new()),
+    };
+
+    let registry = context.config.registry_for_package(registry_name);
+    let node_engine = context.node_engine.as_ref();
+    let mut diagnostics = get_registry_diagnostics(
+        clients,
+        &registry,
+        node_engine,
+        doc,
+        &dep,
+        name_node,
+        registry_name,
+        &spec,
+        range,
+    )
+    .await?;
+
+    // The lockfile is keyed by the name of the dependency, even for aliases
+    let locked = context.lockfile.as_ref().and_then(|lockfile| {
+        let version = lockfile.locked_version(&name, &spec)?;
+        Some((lockfile.file_name(), version))
+    });
+    if let Some((lockfile_name, locked)) = locked {
+        let locked_diagnostics = get_locked_diagnostics(
+            clients,
+            &registry,
+            &dep,
+            registry_name,
+            range,
+            lockfile_name,
+            locked,
+        )
+        .await;
+
+        // Deprecations of the whole range are already reported, no need to repeat them
+        let range_deprecated = diagnostics.iter().any(is_deprecation);
+        diagnostics.extend(
+            locked_diagnostics
+                .into_iter()
+                .filter(|d| !range_deprecated || !is_deprecation(d)),
+        );
+    }
+
+    if let Some(node_engine) = node_engine {
+        diagnostics.extend(
+            get_engine_diagnostics(clients, &registry, &dep, registry_name, range, node_engine)
+                .await,
+        );
+    }
+
+    diagnostics.extend(
+        get_peer_diagnostics(clients, context, &registry, doc, &dep, registry_name, range).await,
+    );
+
+    // Aliases are imported by their alias, so their declarations would not be found anyway
+    let is_runtime = matches!(
+        dep.kind,
+        DependencyKind::Dependency | DependencyKind::OptionalDependency
+    );
+    if is_runtime && registry_name == name && context.declared.contains_key("typescript") {
+        diagnostics.extend(
+            get_types_diagnostics(clients, context, &registry, doc, &dep, &name, range).await,
+        );
+    }
+
+    Ok(diagnostics)
+}
+
+/**
+    Diagnostics for the `packageManager` field used by Corepack, which must be
+    a known package manager at an exact version that exists, and should match
+    the lockfile, if there is one.
+
+    Newer versions get a quick action to update, which recomputes the
+    hash of the package manager tarball if the current version has one.
+*/
+pub async fn get_package_manager_diagnostics(
+    clients: &Clients,
+    context: &NpmDiagnosticsContext,
+    doc: &Document,
+    node: Node<'_>,
+) -> Vec<Diagnostic> {
+    let value = unquote(doc.node_text(node));
+    let range = ts_range_to_lsp_range(node.range());
+    let diagnostic = |message: String, severity: DiagnosticSeverity| Diagnostic {
+        source: Some(String::from("NPM")),
+        range,
+        message,
+        severity: Some(severity),
+        ..Default::default()
+    };
+
+    let Some(manager) = NpmPackageManager::parse(&value) else {
+        return vec![diagnostic(
+            String::from("Expected a package manager and its version, such as `pnpm@9.0.0`"),
+            DiagnosticSeverity::ERROR,
+        )];
+    };
+    if !PACKAGE_MANAGERS.contains(&manager.name) {
+        return vec![diagnostic(
+            format!(
+                "Corepack does not support the package manager `{}`.\
+                \nSupported package managers are `{}`",
+                manager.name,
+                PACKAGE_MANAGERS.join("`, `"),
+            ),
+            DiagnosticSeverity::WARNING,
+        )];
+    }
+
+    let mut diagnostics = Vec::new();
+    if let Some(lockfile) = context
+        .lockfile
+        .as_ref()
+        .filter(|lockfile| lockfile.package_manager() != manager.name)
+    {
+        diagnostics.push(diagnostic(
+            format!(
+                "The package manager `{}` does not match the lockfile `{}`, \
+                which was created by `{}`",
+                manager.name,
+                lockfile.file_name(),
+                lockfile.package_manager(),
+            ),
+            DiagnosticSeverity::WARNING,
+        ));
+    }
+
+    let Ok(version) = manager.version.parse_version() else {
+        diagnostics.push(diagnostic(
+            format!(
+                "The version of a package manager must be an exact version, not `{}`",
+                manager.version
+            ),
+            DiagnosticSeverity::ERROR,
+        ));
+        return diagnostics;
+    };
+    let Some(package) = manager.registry_package() else {
+        return diagnostics;
+    };
+    let registry = context.config.registry_for_package(package);
+    let Ok(meta) = clients
+        .npm
+        .get_registry_metadata_abbreviated(&registry, package)
+        .await
+    else {
+        return diagnostics;
+    };
+
+    if !meta.versions.contains_key(manager.version) {
+        diagnostics.push(diagnostic(
+            format!(
+                "No version `{}` exists for the package manager `{}`",
+                manager.version, manager.name
+            ),
+            DiagnosticSeverity::ERROR,
+        ));
+        return diagnostics;
+    }
+
+    let Some(latest) = version
+        .extract_latest_version(meta.versions.into_values())
+        .filter(|latest| latest.item_version > version)
+    else {
+        return diagnostics;
     };
-    let version = version_req.minimum_version();
+
+    // Hashes are for a specific tarball, so they must be recomputed, or dropped if not possible
+    let latest_hash = manager.hash_algorithm.and_then(|algorithm| {
+        let dist = latest.item.dist.as_ref()?;
+        corepack_hash(package, algorithm, dist)
+    });
+    let version_latest = match latest_hash {
+        Some(hash) => format!("{}+{hash}", latest.item_version),
+        None => latest.item_version.to_string(),
+    };
+    let version_current = value
+        .split_once('@')
+        .map_or(manager.version, |(_, rest)| rest)
+        .to_string();
+
+    let metadata = CodeActionMetadata::LatestVersion {
+        edit_range: ts_range_to_lsp_range(node.range().shrink(1, 1)),
+        source_uri: doc.url().clone(),
+        source_text: value.clone(),
+        version_current,
+        version_latest,
+    };
+
+    diagnostics.push(Diagnostic {
+        data: Some(
+            ResolveContext {
+                uri: doc.url().clone(),
+                value: metadata,
+            }
+            .into(),
+        ),
+        ..diagnostic(
+            format!(
+                "A newer version of `{}` is available.\
+                \nThe latest version is `{}`",
+                manager.name, latest.item_version,
+            ),
+            DiagnosticSeverity::INFORMATION,
+        )
+    });
+
+    diagnostics
+}
+
+/**
+    Diagnostics for a dependency on a package in the npm registry, either
+    directly by its name or through an alias, such as `npm:other@^1.2.3`.
+*/
+#[allow(clippy::too_many_arguments)]
+async fn get_registry_diagnostics(
+    clients: &Clients,
+    registry: &NpmRegistry,
+    node_engine: Option<&NpmNodeEngine>,
+    doc: &Document,
+    dep: &NpmDependency<'_>,
+    name_node: Node<'_>,
+    name: &str,
+    spec: &str,
+    range: &str,
+) -> ServerResult<Vec<Diagnostic>> {
+    let (version_req, tag) = (parse_version_req(range), parse_tag(range));
+    if version_req.is_none() && tag.is_none() {
+        return Ok(Vec::new());
+    }
 
     // Fetch versions and make sure there is at least one
-    let meta = match clients.npm.get_registry_metadata(&name).await {
+    let meta = match clients
+        .npm
+        .get_registry_metadata_abbreviated(registry, name)
+        .await
+    {
         Ok(v) => v,
         Err(e) => {
             if e.is_not_found_error() {
+                let message = if registry.is_default() {
+                    format!("No package exists with the name `{name}`")
+                } else {
+                    format!(
+                        "No package exists with the name `{name}` in the registry `{}`",
+                        registry.url()
+                    )
+                };
                 return Ok(vec![Diagnostic {
                     source: Some(String::from("NPM")),
-                    range: ts_range_to_lsp_range(dep.name.range()),
-                    message: format!("No package exists with the name `{name}`"),
+                    range: ts_range_to_lsp_range(name_node.range()),
+                    message,
                     severity: Some(DiagnosticSeverity::ERROR),
                     ..Default::default()
                 }]);
@@ -49,6 +346,15 @@ pub async fn get_npm_diagnostics(
         }
     };
 
+    // Tags point to a single version that changes over time, so they are checked separately
+    if let Some(tag) = tag {
+        return Ok(get_tag_diagnostics(doc, dep, name, spec, tag, &meta));
+    }
+    let Some(version_req) = version_req else {
+        return Ok(Vec::new());
+    };
+    let version = version_req.minimum_version();
+
     let mut has_versions = false;
     let mut deprecation_reason = None;
     for version in meta.versions.values().filter(|v| {
@@ -72,7 +378,7 @@ pub async fn get_npm_diagnostics(
         return Ok(vec![Diagnostic {
             source: Some(String::from("NPM")),
             range: ts_range_to_lsp_range(dep.spec.range()),
-            message: format!("Version `{version}` does not exist for the package `{name}`"),
+            message: format!("No version of the package `{name}` matches `{range}`"),
             severity: Some(DiagnosticSeverity::ERROR),
             ..Default::default()
         }]);
@@ -90,30 +396,42 @@ pub async fn get_npm_diagnostics(
     }
 
     // Everything is OK - but we may be able to suggest new versions...
-    // ... try to find the latest non-prerelease version
-    let Some(latest_version) = version.extract_latest_version(meta.versions.values().cloned())
+    // ... try to find the latest non-prerelease version that supports our Node versions
+    let Some(latest_version) = version
+        .extract_latest_version_filtered(meta.versions.values().cloned(), |v| {
+            node_engine.is_none_or(|e| e.supports(&v.item.engines))
+        })
     else {
         return Ok(Vec::new());
     };
 
-    if !latest_version.is_semver_compatible {
+    // NOTE: Cargo-flavored compatibility does not apply to npm ranges such
+    // as `1.x || >=2.5.0`, so check against the range itself instead
+    if !version_req.matches(&latest_version.item_version) {
         let latest_version_string = latest_version.item_version.to_string();
 
         let metadata = CodeActionMetadata::LatestVersion {
             edit_range: ts_range_to_lsp_range(dep.spec.range().shrink(1, 1)),
             source_uri: doc.url().clone(),
             source_text: spec.to_string(),
-            version_current: version.to_string(),
-            version_latest: latest_version_string.to_string(),
+            version_current: range.to_string(),
+            version_latest: replacement_spec(range, &latest_version.item_version),
         };
 
         return Ok(vec![Diagnostic {
             source: Some(String::from("NPM")),
             range: ts_range_to_lsp_range(dep.spec.range()),
-            message: format!(
-                "A newer version of `{name}` is available.\
-                \nThe latest version is `{latest_version_string}`",
-            ),
+            message: match node_engine {
+                Some(node_engine) => format!(
+                    "A newer version of `{name}` is available.\
+                    \nThe latest version supporting Node `{}` is `{latest_version_string}`",
+                    node_engine.declared(),
+                ),
+                None => format!(
+                    "A newer version of `{name}` is available.\
+                    \nThe latest version is `{latest_version_string}`",
+                ),
+            },
             severity: Some(DiagnosticSeverity::INFORMATION),
             data: Some(
                 ResolveContext {
@@ -128,3 +446,785 @@ pub async fn get_npm_diagnostics(
 
     Ok(Vec::new())
 }
+
+/**
+    Diagnostics for an override, which must point to a package in the registry
+    that exists and is up to date, same as any other dependency, and should
+    still affect at least one installed package - along with the package that
+    it is scoped to, if any - otherwise it has no effect and may be removed.
+
+    Transitive dependencies are only known from lockfiles - without one, the override
+    and its parent can only be checked against the dependencies declared in the
+    document, so anything that is not declared is only hinted at, since it may
+    still be installed as a transitive dependency.
+*/
+async fn get_override_diagnostics(
+    clients: &Clients,
+    context: &NpmDiagnosticsContext,
+    doc: &Document,
+    dep: &NpmDependency<'_>,
+    name: &str,
+    spec: &str,
+) -> ServerResult<Vec<Diagnostic>> {
+    let mut diagnostics = Vec::new();
+
+    let lockfile = context.lockfile.as_ref();
+    let is_installed = |package: &str| {
+        context.declared.contains_key(package)
+            || lockfile.is_some_and(|lockfile| lockfile.is_installed(package))
+    };
+    let uninstalled = dep
+        .override_parent(doc)
+        .filter(|parent| !is_installed(parent))
+        .or_else(|| (!is_installed(name)).then(|| name.to_string()));
+    if let Some(uninstalled) = uninstalled {
+        let diagnostic = if let Some(lockfile) = lockfile {
+            Diagnostic {
+                message: format!(
+                    "No package named `{uninstalled}` is installed according to `{}`, \
+                    so this override has no effect",
+                    lockfile.file_name(),
+                ),
+                severity: Some(DiagnosticSeverity::WARNING),
+                tags: Some(vec![DiagnosticTag::UNNECESSARY]),
+                ..Default::default()
+            }
+        } else {
+            Diagnostic {
+                message: format!(
+                    "No dependency named `{uninstalled}` is declared, and there is no lockfile \
+                    to check if it is installed, so this override may have no effect",
+                ),
+                severity: Some(DiagnosticSeverity::HINT),
+                ..Default::default()
+            }
+        };
+        diagnostics.push(Diagnostic {
+            source: Some(String::from("NPM")),
+            range: ts_range_to_lsp_range(dep.name.range()),
+            ..diagnostic
+        });
+    }
+
+    // References such as `$react` use the version of a direct dependency, and are not checked
+    let spec_kind = NpmSpec::parse(spec);
+    let Some((registry_name, range)) = spec_kind.registry_package(name) else {
+        return Ok(diagnostics);
+    };
+    let name_node = match spec_kind {
+        NpmSpec::Alias { .. } => dep.spec,
+        _ => dep.name,
+    };
+
+    let registry = context.config.registry_for_package(registry_name);
+    diagnostics.extend(
+        get_registry_diagnostics(
+            clients,
+            &registry,
+            context.node_engine.as_ref(),
+            doc,
+            dep,
+            name_node,
+            registry_name,
+            spec,
+            range,
+        )
+        .await?,
+    );
+
+    Ok(diagnostics)
+}
+
+/**
+    Diagnostics for the Node versions required by the version that a registry
+    dependency resolves to, which must include the lowest declared Node version.
+*/
+async fn get_engine_diagnostics(
+    clients: &Clients,
+    registry: &NpmRegistry,
+    dep: &NpmDependency<'_>,
+    name: &str,
+    range: &str,
+    node_engine: &NpmNodeEngine,
+) -> Vec<Diagnostic> {
+    let Ok(meta) = clients
+        .npm
+        .get_registry_metadata_abbreviated(registry, name)
+        .await
+    else {
+        return Vec::new();
+    };
+    let Some((resolved_version, resolved)) =
+        find_resolved_version(range, &meta.dist_tags, &meta.versions)
+    else {
+        return Vec::new();
+    };
+    if node_engine.supports(&resolved.engines) {
+        return Vec::new();
+    }
+
+    let required = resolved.engines.get("node").map_or("", String::as_str);
+    vec![Diagnostic {
+        source: Some(String::from("NPM")),
+        range: ts_range_to_lsp_range(dep.spec.range()),
+        message: format!(
+            "Version `{resolved_version}` of `{name}` requires Node `{required}`, \
+            but `{}` only declares `{}`",
+            node_engine.source(),
+            node_engine.declared(),
+        ),
+        severity: Some(DiagnosticSeverity::WARNING),
+        ..Default::default()
+    }]
+}
+
+/**
+    Diagnostics for the peer dependencies of the version that a registry
+    dependency resolves to, which must be declared in this package, using
+    a range that has at least one version in common with the required range.
+
+    Missing peers get a quick action to add them, using the newest version
+    that satisfies the required range. Optional peers may always be missing.
+*/
+async fn get_peer_diagnostics(
+    clients: &Clients,
+    context: &NpmDiagnosticsContext,
+    registry: &NpmRegistry,
+    doc: &Document,
+    dep: &NpmDependency<'_>,
+    name: &str,
+    range: &str,
+) -> Vec<Diagnostic> {
+    let Ok(meta) = clients
+        .npm
+        .get_registry_metadata_abbreviated(registry, name)
+        .await
+    else {
+        return Vec::new();
+    };
+    let Some((resolved_version, resolved)) =
+        find_resolved_version(range, &meta.dist_tags, &meta.versions)
+    else {
+        return Vec::new();
+    };
+
+    let mut peers = resolved.peer_dependencies.iter().collect::<Vec<_>>();
+    peers.sort_unstable();
+
+    // Filter out peers that need no metadata to check first, since
+    // popular packages may have many peers that are already satisfied
+    let peers = peers
+        .into_iter()
+        .filter_map(|(peer, required)| {
+            let required_req = parse_version_req(required)?;
+            let Some(declared) = context.declared.get(peer.as_str()) else {
+                return (!resolved.is_optional_peer(peer)).then_some((
+                    peer,
+                    required,
+                    required_req,
+                    None,
+                ));
+            };
+            // Only ranges can be compared, peers from workspaces, git, etc are not checked
+            let declared_req = parse_version_req(declared)?;
+            let satisfied = declared_req
+                .min_version()
+                .is_none_or(|v| required_req.matches(&v));
+            (!satisfied).then_some((peer, required, required_req, Some((declared, declared_req))))
+        })
+        .collect::<Vec<_>>();
+
+    let peer_metas = join_all(peers.iter().map(|(peer, ..)| async move {
+        let peer_registry = context.config.registry_for_package(peer);
+        clients
+            .npm
+            .get_registry_metadata_abbreviated(&peer_registry, peer)
+            .await
+            .ok()
+    }))
+    .await;
+
+    let mut diagnostics = Vec::new();
+    for ((peer, required, required_req, declared), peer_meta) in peers.into_iter().zip(peer_metas) {
+        let Some((declared, declared_req)) = declared else {
+            // Prefer a caret range for the newest satisfying version, same as npm install
+            let peer_spec = peer_meta
+                .as_ref()
+                .and_then(|m| find_resolved_version(required, &m.dist_tags, &m.versions))
+                .map_or_else(|| required.clone(), |(version, _)| format!("^{version}"));
+            let metadata = CodeActionMetadata::AddDependency {
+                edit_range: range_after(dep.spec),
+                source_uri: doc.url().clone(),
+                name: peer.clone(),
+                new_text: format!(
+                    ",\n{}\"{peer}\": \"{peer_spec}\"",
+                    line_indent(doc, dep.name)
+                ),
+            };
+
+            diagnostics.push(Diagnostic {
+                source: Some(String::from("NPM")),
+                range: ts_range_to_lsp_range(dep.name.range()),
+                message: format!(
+                    "Version `{resolved_version}` of `{name}` requires the peer dependency \
+                    `{peer}@{required}`, which is not declared in this package"
+                ),
+                severity: Some(DiagnosticSeverity::WARNING),
+                data: Some(
+                    ResolveContext {
+                        uri: doc.url().clone(),
+                        value: metadata,
+                    }
+                    .into(),
+                ),
+                ..Default::default()
+            });
+            continue;
+        };
+
+        // The lowest declared version does not satisfy the peer, but another one may
+        let intersects = peer_meta.is_some_and(|peer_meta| {
+            peer_meta.versions.values().any(|v| {
+                v.parse_version()
+                    .is_ok_and(|v| declared_req.matches(&v) && required_req.matches(&v))
+            })
+        });
+        if intersects {
+            continue;
+        }
+
+        diagnostics.push(Diagnostic {
+            source: Some(String::from("NPM")),
+            range: ts_range_to_lsp_range(dep.name.range()),
+            message: format!(
+                "Version `{resolved_version}` of `{name}` requires the peer dependency \
+                `{peer}@{required}`, but this package declares `{peer}@{declared}`"
+            ),
+            severity: Some(DiagnosticSeverity::WARNING),
+            ..Default::default()
+        });
+    }
+
+    diagnostics
+}
+
+/**
+    Diagnostics for a runtime dependency of a TypeScript package, which does not
+    ship its own type declarations, but has declarations available in `@types`.
+
+    The `@types` package is suggested at the same major version as the dependency,
+    and deprecated `@types` packages - stubs for packages that now ship their own
+    declarations - are never suggested.
+*/
+async fn get_types_diagnostics(
+    clients: &Clients,
+    context: &NpmDiagnosticsContext,
+    registry: &NpmRegistry,
+    doc: &Document,
+    dep: &NpmDependency<'_>,
+    name: &str,
+    range: &str,
+) -> Vec<Diagnostic> {
+    let types_name = types_package_name(name);
+    if name.starts_with("@types/") || context.declared.contains_key(&types_name) {
+        return Vec::new();
+    }
+
+    let Ok(meta) = clients
+        .npm
+        .get_registry_metadata_abbreviated(registry, name)
+        .await
+    else {
+        return Vec::new();
+    };
+    let Some((resolved_version, resolved)) =
+        find_resolved_version(range, &meta.dist_tags, &meta.versions)
+    else {
+        return Vec::new();
+    };
+
+    // Type declarations are not listed in the abbreviated metadata document, so the
+    // resolved version is fetched first, since most packages do not need `@types`
+    let Ok(resolved) = clients
+        .npm
+        .get_registry_version(registry, name, &resolved.version)
+        .await
+    else {
+        return Vec::new();
+    };
+    if resolved.has_type_declarations() {
+        return Vec::new();
+    }
+
+    let types_registry = context.config.registry_for_package(&types_name);
+    let Ok(types_meta) = clients
+        .npm
+        .get_registry_metadata_abbreviated(&types_registry, &types_name)
+        .await
+    else {
+        return Vec::new();
+    };
+    let is_stub = types_meta
+        .dist_tags
+        .get("latest")
+        .and_then(|latest| types_meta.versions.get(latest))
+        .is_some_and(Versioned::deprecated);
+    let Some(types_version) = types_meta
+        .versions
+        .values()
+        .filter(|v| !is_stub && !v.deprecated())
+        .filter_map(|v| v.parse_version().ok())
+        .filter(|v| v.pre.is_empty() && v.major == resolved_version.major)
+        .max()
+    else {
+        return Vec::new();
+    };
+
+    let Some((edit_range, new_text)) =
+        dev_dependency_insertion(doc, dep, &types_name, &format!("^{types_version}"))
+    else {
+        return Vec::new();
+    };
+    let metadata = CodeActionMetadata::AddDependency {
+        edit_range,
+        source_uri: doc.url().clone(),
+        name: types_name.clone(),
+        new_text,
+    };
+
+    vec![Diagnostic {
+        source: Some(String::from("NPM")),
+        range: ts_range_to_lsp_range(dep.name.range()),
+        message: format!(
+            "Version `{resolved_version}` of `{name}` does not ship its own type declarations.\
+            \nDeclarations are available in `{types_name}`"
+        ),
+        severity: Some(DiagnosticSeverity::INFORMATION),
+        data: Some(
+            ResolveContext {
+                uri: doc.url().clone(),
+                value: metadata,
+            }
+            .into(),
+        ),
+        ..Default::default()
+    }]
+}
+
+/**
+    Declarations for scoped packages are named after both the scope
+    and the package, so `@scope/name` becomes `@types/scope__name`
+*/
+fn types_package_name(name: &str) -> String {
+    match name.strip_prefix('@').and_then(|n| n.split_once('/')) {
+        Some((scope, name)) => format!("@types/{scope}__{name}"),
+        None => format!("@types/{name}"),
+    }
+}
+
+/**
+    Finds where to add a new entry to `devDependencies`, which is either after
+    its last entry, the whole section if it is empty, or a new section directly
+    after the section that the given dependency is in, if there is none yet.
+
+    New lines are indented using the same unit as the given dependency,
+    which is the indentation of its entry relative to its section.
+*/
+fn dev_dependency_insertion(
+    doc: &Document,
+    dep: &NpmDependency<'_>,
+    name: &str,
+    spec: &str,
+) -> Option<(Range, String)> {
+    let entry = format!("\"{name}\": \"{spec}\"");
+    let section = dep.name.parent()?.parent()?.parent()?;
+    let indent = line_indent(doc, section);
+    let entry_indent = line_indent(doc, dep.name);
+    let unit = entry_indent
+        .strip_prefix(indent.as_str())
+        .filter(|unit| !unit.is_empty())
+        .map_or_else(|| String::from("  "), ToString::to_string);
+
+    let Some(dev_section) = npm::find_dependency_section(doc, DependencyKind::DevDependency) else {
+        return Some((
+            range_after(section),
+            format!(",\n{indent}\"devDependencies\": {{\n{indent}{unit}{entry}\n{indent}}}"),
+        ));
+    };
+
+    let object = dev_section
+        .child_by_field_name("value")
+        .filter(|value| value.kind() == "object")?;
+    let mut cursor = object.walk();
+    let last = object
+        .children(&mut cursor)
+        .filter(|child| child.kind() == "pair")
+        .last();
+    if let Some(last) = last {
+        return Some((
+            range_after(last),
+            format!(",\n{}{entry}", line_indent(doc, last)),
+        ));
+    }
+
+    let indent = line_indent(doc, dev_section);
+    Some((
+        ts_range_to_lsp_range(object.range()),
+        format!("{{\n{indent}{unit}{entry}\n{indent}}}"),
+    ))
+}
+
+/**
+    New entries are added using an empty range directly after an existing node:
+
+    ```json
+    "react-dom": "^18.2.0"|
+    ```
+*/
+fn range_after(node: Node<'_>) -> Range {
+    let end = ts_range_to_lsp_range(node.range()).end;
+    Range::new(end, end)
+}
+
+/**
+    Returns the whitespace at the start of the line that the given node starts on.
+*/
+fn line_indent(doc: &Document, node: Node<'_>) -> String {
+    let start = node.start_byte();
+    let line_start = start - node.start_position().column;
+    doc.text()
+        .byte_slice(line_start..start)
+        .to_string()
+        .chars()
+        .take_while(|c| c.is_whitespace())
+        .collect()
+}
+
+/**
+    Diagnostics for the version of a registry dependency that is locked in
+    a lockfile, which may no longer satisfy the range in `package.json`, or
+    be deprecated even if the newest version matching the range is not.
+*/
+async fn get_locked_diagnostics(
+    clients: &Clients,
+    registry: &NpmRegistry,
+    dep: &NpmDependency<'_>,
+    name: &str,
+    range: &str,
+    lockfile_name: &str,
+    locked: &str,
+) -> Vec<Diagnostic> {
+    let Ok(locked_version) = locked.parse_version() else {
+        return Vec::new();
+    };
+
+    let mut diagnostics = Vec::new();
+    if parse_version_req(range).is_some_and(|req| !req.matches(&locked_version)) {
+        diagnostics.push(Diagnostic {
+            source: Some(String::from("NPM")),
+            range: ts_range_to_lsp_range(dep.spec.range()),
+            message: format!(
+                "The version `{locked}` locked in `{lockfile_name}` does not satisfy `{range}`.\
+                \nInstall dependencies to update the lockfile",
+            ),
+            severity: Some(DiagnosticSeverity::WARNING),
+            ..Default::default()
+        });
+    }
+
+    let Ok(meta) = clients
+        .npm
+        .get_registry_metadata_abbreviated(registry, name)
+        .await
+    else {
+        return diagnostics;
+    };
+    if let Some(reason) = meta
+        .versions
+        .get(locked)
+        .and_then(|v| v.deprecated.as_deref())
+    {
+        diagnostics.push(Diagnostic {
+            source: Some(String::from("NPM")),
+            range: ts_range_to_lsp_range(dep.spec.range()),
+            message: format!(
+                "The version `{locked}` locked in `{lockfile_name}` is deprecated: {reason}"
+            ),
+            severity: Some(DiagnosticSeverity::WARNING),
+            tags: Some(vec![DiagnosticTag::DEPRECATED]),
+            ..Default::default()
+        });
+    }
+
+    diagnostics
+}
+
+fn is_deprecation(diagnostic: &Diagnostic) -> bool {
+    diagnostic
+        .tags
+        .as_ref()
+        .is_some_and(|tags| tags.contains(&DiagnosticTag::DEPRECATED))
+}
+
+/**
+    Diagnostics for a dependency on a dist-tag, such as `latest` or `next`,
+    which must exist, and should preferably not point to a prerelease.
+
+    Specs using prerelease tags get an informational diagnostic,
+    with a quick action to switch to the version of the `latest` tag.
+*/
+fn get_tag_diagnostics(
+    doc: &Document,
+    dep: &NpmDependency<'_>,
+    name: &str,
+    spec: &str,
+    tag: &str,
+    meta: &RegistryMetadataAbbreviated,
+) -> Vec<Diagnostic> {
+    let Some(tag_version) = meta.dist_tags.get(tag) else {
+        let mut tags = meta
+            .dist_tags
+            .keys()
+            .map(String::as_str)
+            .collect::<Vec<_>>();
+        tags.sort_unstable();
+        let message = if tags.is_empty() {
+            format!("No tag `{tag}` exists for the package `{name}`")
+        } else {
+            format!(
+                "No tag `{tag}` exists for the package `{name}`\nAvailable tags are `{}`",
+                tags.join("`, `")
+            )
+        };
+        return vec![Diagnostic {
+            source: Some(String::from("NPM")),
+            range: ts_range_to_lsp_range(dep.spec.range()),
+            message,
+            severity: Some(DiagnosticSeverity::ERROR),
+            ..Default::default()
+        }];
+    };
+
+    let is_prerelease = tag_version.parse_version().is_ok_and(|v| !v.pre.is_empty());
+    let stable_version = meta
+        .dist_tags
+        .get("latest")
+        .filter(|v| v.parse_version().is_ok_and(|v| v.pre.is_empty()));
+    let (true, Some(stable_version)) = (is_prerelease && tag != "latest", stable_version) else {
+        return Vec::new();
+    };
+
+    // Only the tag after the final @ is replaced for aliases such as `npm:name@next`,
+    // since the tag may also be part of the name, and plain tags replace the whole spec
+    let spec_latest = match spec.rfind('@').filter(|index| *index > 0) {
+        Some(index) => format!("{}^{stable_version}", &spec[..=index]),
+        None => format!("^{stable_version}"),
+    };
+    let metadata = CodeActionMetadata::LatestVersion {
+        edit_range: ts_range_to_lsp_range(dep.spec.range().shrink(1, 1)),
+        source_uri: doc.url().clone(),
+        source_text: spec.to_string(),
+        version_current: spec.to_string(),
+        version_latest: spec_latest,
+    };
+
+    vec![Diagnostic {
+        source: Some(String::from("NPM")),
+        range: ts_range_to_lsp_range(dep.spec.range()),
+        message: format!(
+            "The tag `{tag}` points to the prerelease version `{tag_version}`.\
+            \nThe stable equivalent is the `latest` tag, at version `{stable_version}`",
+        ),
+        severity: Some(DiagnosticSeverity::INFORMATION),
+        data: Some(
+            ResolveContext {
+                uri: doc.url().clone(),
+                value: metadata,
+            }
+            .into(),
+        ),
+        ..Default::default()
+    }]
+}
+
+/**
+    Diagnostics for a dependency on a git repository, which are only
+    available for GitHub repositories, and check against their tags.
+
+    A `semver:` range must match at least one tag, and a commit-ish that looks
+    like a version must be an existing tag - other commit-ish values may be
+    branches or commit hashes, which are not validated.
+*/
+async fn get_git_diagnostics(
+    clients: &Clients,
+    dep: &NpmDependency<'_>,
+    git: NpmGitSpec<'_>,
+) -> Vec<Diagnostic> {
+    let Some((owner, repository)) = git.github_repository() else {
+        return Vec::new();
+    };
+
+    let tags = match clients
+        .github
+        .get_repository_tags(&owner, &repository)
+        .await
+    {
+        Ok(tags) => tags,
+        Err(e) if e.is_not_found_error() => {
+            return vec![Diagnostic {
+                source: Some(String::from("NPM")),
+                range: ts_range_to_lsp_range(dep.spec.range()),
+                message: format!("No GitHub repository exists at `{owner}/{repository}`"),
+                severity: Some(DiagnosticSeverity::ERROR),
+                ..Default::default()
+            }];
+        }
+        Err(_) => return Vec::new(),
+    };
+
+    // Only the most recent tags are fetched, so older tags may exist in huge repositories
+    if tags.len() >= MAXIMUM_TAGS_FETCHED {
+        return Vec::new();
+    }
+
+    let message = if let Some(range) = git.semver {
+        let Some(version_req) = parse_version_req(range) else {
+            return Vec::new();
+        };
+        let has_match = tags
+            .iter()
+            .filter_map(|tag| tag.parse_version().ok())
+            .any(|version| version_req.matches(&version));
+        (!has_match)
+            .then(|| format!("No tag in the repository `{owner}/{repository}` matches `{range}`"))
+    } else if let Some(committish) = git
+        .committish
+        .filter(|c| c.trim_start_matches('v').parse_version().is_ok())
+    {
+        let has_tag = tags.iter().any(|tag| tag.name == committish);
+        (!has_tag).then(|| {
+            format!("No tag `{committish}` exists in the repository `{owner}/{repository}`")
+        })
+    } else {
+        None
+    };
+
+    message
+        .map(|message| Diagnostic {
+            source: Some(String::from("NPM")),
+            range: ts_range_to_lsp_range(dep.spec.range()),
+            message,
+            severity: Some(DiagnosticSeverity::ERROR),
+            ..Default::default()
+        })
+        .into_iter()
+        .collect()
+}
+
+/**
+    Diagnostics for a dependency on a local path, using either the `file:`
+    or `link:` protocols, which must point to a directory with a `package.json`.
+
+    The `file:` protocol may also point to a tarball, which only needs to exist.
+*/
+fn get_path_diagnostics(
+    doc: &Document,
+    dep: &NpmDependency<'_>,
+    path: &str,
+    protocol: &str,
+) -> Vec<Diagnostic> {
+    let Some(doc_dir) = doc
+        .url()
+        .to_file_path()
+        .ok()
+        .and_then(|p| p.parent().map(Path::to_path_buf))
+    else {
+        return Vec::new();
+    };
+
+    let full_path = doc_dir.join(path);
+    let message = if !full_path.exists() {
+        Some(format!("No file or directory exists at `{path}`"))
+    } else if full_path.is_dir() && !full_path.join("package.json").is_file() {
+        Some(format!(
+            "The directory `{path}` does not contain a `package.json`"
+        ))
+    } else if full_path.is_file() && protocol == "link" {
+        Some(format!(
+            "The `link:` protocol must point to a directory, but `{path}` is a file"
+        ))
+    } else {
+        None
+    };
+
+    message
+        .map(|message| Diagnostic {
+            source: Some(String::from("NPM")),
+            range: ts_range_to_lsp_range(dep.spec.range()),
+            message,
+            severity: Some(DiagnosticSeverity::ERROR),
+            ..Default::default()
+        })
+        .into_iter()
+        .collect()
+}
+
+/**
+    Diagnostics for a dependency using the `workspace:` protocol, which
+    must refer to a package in the same workspace, and may also have a
+    version range that the version of the workspace package must match.
+*/
+fn get_workspace_diagnostics(
+    doc: &Document,
+    dep: &NpmDependency<'_>,
+    name: &str,
+    range: &str,
+) -> Vec<Diagnostic> {
+    let Some(doc_dir) = doc
+        .url()
+        .to_file_path()
+        .ok()
+        .and_then(|p| p.parent().map(Path::to_path_buf))
+    else {
+        return Vec::new();
+    };
+
+    let message = match NpmWorkspace::discover(&doc_dir) {
+        None => Some(String::from(
+            "The `workspace:` protocol can only be used inside of a workspace",
+        )),
+        Some(workspace) => match workspace.find_package(name) {
+            None => Some(format!("No package named `{name}` exists in the workspace")),
+            Some(package) => {
+                // Only `*`, `^` and `~` are shorthands, anything else is a range to check
+                let version_req = Some(range)
+                    .filter(|r| !matches!(*r, "*" | "^" | "~"))
+                    .and_then(parse_version_req);
+                let version = package
+                    .version
+                    .as_deref()
+                    .and_then(|v| v.parse_version().ok());
+                match (version_req, version) {
+                    (Some(version_req), Some(version)) if !version_req.matches(&version) => {
+                        Some(format!(
+                            "The workspace package `{name}` has version `{version}`.\
+                            \nThis does not match `{range}`"
+                        ))
+                    }
+                    _ => None,
+                }
+            }
+        },
+    };
+
+    message
+        .map(|message| Diagnostic {
+            source: Some(String::from("NPM")),
+            range: ts_range_to_lsp_range(dep.spec.range()),
+            message,
+            severity: Some(DiagnosticSeverity::ERROR),
+            ..Default::default()
+        })
+        .into_iter()
+        .collect()
+}
@@ -0,0 +1,85 @@
+use std::collections::HashMap;
+
+use serde_json::Value;
+
+use async_language_server::server::Document;
+
+use deputy_versioning::Version;
+
+use crate::shared::FileCache;
+
+use super::util::parse_version_req;
+
+const NVMRC: &str = ".nvmrc";
+const NODE_VERSION: &str = ".node-version";
+
+static VERSION_FILES: FileCache<String> = FileCache::new();
+
+/**
+    The Node versions that a package declares support for, either using
+    `engines.node` in its `package.json`, or a `.nvmrc` or `.node-version` file.
+
+    Only the lowest supported version is checked against dependencies,
+    since any dependency that supports it also supports newer versions.
+*/
+#[derive(Debug, Clone)]
+pub struct NpmNodeEngine {
+    declared: String,
+    source: &'static str,
+    minimum: Version,
+}
+
+impl NpmNodeEngine {
+    /**
+        Finds the Node versions declared for the package in the given document,
+        preferring `engines.node`, and otherwise searching upwards for the closest
+        version file, skipping aliases such as `node` or `lts` that are not versions.
+    */
+    pub fn for_document(doc: &Document) -> Option<Self> {
+        let json = serde_json::from_str::<Value>(&doc.text().to_string()).ok();
+        if let Some(declared) = json
+            .as_ref()
+            .and_then(|json| json.get("engines")?.get("node")?.as_str())
+        {
+            return Self::parse(declared, "engines.node");
+        }
+
+        let doc_path = doc.url().to_file_path().ok()?;
+        doc_path.ancestors().skip(1).find_map(|dir| {
+            [NVMRC, NODE_VERSION].into_iter().find_map(|file_name| {
+                let contents =
+                    VERSION_FILES.get_or_parse(&dir.join(file_name), "", str::to_string)?;
+                Self::parse(contents.trim(), file_name)
+            })
+        })
+    }
+
+    fn parse(declared: &str, source: &'static str) -> Option<Self> {
+        let minimum = parse_version_req(declared.trim_start_matches('v'))?.min_version()?;
+        Some(Self {
+            declared: declared.to_string(),
+            source,
+            minimum,
+        })
+    }
+
+    pub fn declared(&self) -> &str {
+        &self.declared
+    }
+
+    pub fn source(&self) -> &'static str {
+        self.source
+    }
+
+    /**
+        Checks if the `engines` of a dependency support the lowest declared Node version.
+
+        Dependencies without a valid `engines.node` range are assumed to support it.
+    */
+    pub fn supports(&self, engines: &HashMap<String, String>) -> bool {
+        engines
+            .get("node")
+            .and_then(|range| parse_version_req(range))
+            .is_none_or(|req| req.matches(&self.minimum))
+    }
+}
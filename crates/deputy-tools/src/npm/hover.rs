@@ -1,62 +1,190 @@
 use tracing::trace;
 
 use async_language_server::{
-    lsp_types::{Hover, HoverContents, MarkupContent, MarkupKind},
+    lsp_types::{Hover, HoverContents, MarkupContent, MarkupKind, Position},
     server::{Document, ServerResult},
     tree_sitter::Node,
-    tree_sitter_utils::ts_range_to_lsp_range,
+    tree_sitter_utils::{ts_range_contains_lsp_position, ts_range_to_lsp_range},
 };
 
-use deputy_clients::npm::models::RegistryMetadataRepositoryVariant;
+use deputy_clients::npm::models::{
+    RegistryMetadata, RegistryMetadataHumanVariant, RegistryMetadataRepositoryVariant,
+};
 use deputy_parser::npm;
-use deputy_versioning::{VersionReqExt, Versioned};
+use deputy_versioning::{Version, VersionReqExt, Versioned};
 
-use crate::shared::MarkdownBuilder;
+use crate::shared::{MarkdownBuilder, ReleaseNotes, format_date};
 
 use super::Clients;
+use super::lockfile::NpmLockfile;
+use super::npmrc::NpmConfig;
+use super::spec::NpmSpec;
+use super::util::{find_resolved_version, parse_tag, parse_version_req};
+
+const MAXIMUM_MAINTAINERS_SHOWN: usize = 5;
 
 pub async fn get_npm_hover(
     clients: &Clients,
     doc: &Document,
+    pos: Position,
     node: Node<'_>,
 ) -> ServerResult<Option<Hover>> {
-    let Some(dep) = npm::parse_dependency(node) else {
+    let Some(dep) = npm::parse_dependency(doc, node) else {
         return Ok(None);
     };
 
-    let (name, spec) = dep.text(doc);
-    let Ok(version_req) = spec.parse_version_req() else {
+    let (dep_name, dep_spec) = dep.text(doc);
+    let Some((name, spec)) = NpmSpec::parse(&dep_spec).registry_package(&dep_name) else {
         return Ok(None);
     };
+    let (version_req, tag) = (parse_version_req(spec), parse_tag(spec));
+    if version_req.is_none() && tag.is_none() {
+        return Ok(None);
+    }
+    let registry = NpmConfig::for_document(doc.url()).registry_for_package(name);
+
+    // Hovering the version spec explains the requirement or tag instead
+    if ts_range_contains_lsp_position(dep.spec.range(), pos) {
+        trace!("Hovering version range: {name} version {spec}");
+        let mut md = MarkdownBuilder::new();
+        md.h2(name);
+        md.version(spec);
+        let meta = clients
+            .npm
+            .get_registry_metadata_abbreviated(&registry, name)
+            .await
+            .ok();
+        if let Some(tag) = tag {
+            if let Some(tag_version) = meta.as_ref().and_then(|m| m.dist_tags.get(tag)) {
+                md.br();
+                md.p(format!(
+                    "The tag `{tag}` currently points to `{tag_version}`"
+                ));
+            }
+        } else if let Some(range) =
+            meta.and_then(|meta| spec.extract_npm_version_range(meta.versions.into_values()))
+        {
+            md.br();
+            md.version_range(&range);
+        }
+        return Ok(Some(Hover {
+            range: Some(ts_range_to_lsp_range(dep.spec.range())),
+            contents: HoverContents::Markup(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value: md.build(),
+            }),
+        }));
+    }
+
+    // Try to fetch additional information from the index - description, tags, links,
+    // which are only in the full metadata document, unlike diagnostics and completions
+    trace!("Fetching package data from npm");
+    let meta = clients
+        .npm
+        .get_registry_metadata(&registry, name)
+        .await
+        .ok();
 
-    let version = version_req.minimum_version();
+    // Tags can only be resolved to a version using the registry
+    let version = version_req
+        .as_ref()
+        .map(|req| req.minimum_version().to_string())
+        .or_else(|| meta.as_ref()?.dist_tags.get(tag?).cloned());
+    let Some(version) = version else {
+        return Ok(None);
+    };
 
     // Add basic hover information with version and name
     trace!("Hovering: {name} version {version}");
     let mut md = MarkdownBuilder::new();
-    md.h2(&name);
+    md.h2(name);
     md.version(version);
 
-    // Try to fetch additional information from the index - description, links
-    trace!("Fetching package data from npm");
-    if let Ok(meta) = clients.npm.get_registry_metadata(&name).await {
-        if let Some(desc) = meta.current_version.description.as_ref() {
+    // Add the installed version, if any, which is keyed by the name of the dependency itself
+    let lockfile = NpmLockfile::for_document(doc.url());
+    let locked = lockfile.as_ref().and_then(|lockfile| {
+        let version = lockfile.locked_version(&dep_name, &dep_spec)?;
+        Some((lockfile.file_name(), version))
+    });
+    if let Some((lockfile_name, locked)) = locked {
+        md.br();
+        md.p(format!("Locked **{locked}** in `{lockfile_name}`"));
+    }
+
+    if let Some(meta) = meta {
+        let release_notes = get_npm_release_notes(clients, doc, node).await;
+        if let Some(desc) = meta.package.description.as_ref() {
             md.br();
             md.p(desc);
         }
 
+        // Add information about the version that the range or tag resolves to
+        let resolved = find_resolved_version(spec, &meta.dist_tags, &meta.versions);
+        if let Some((resolved_version, resolved_data)) = resolved {
+            md.br();
+            md.h3(format!("Version {resolved_version}"));
+            if let Some(published) = meta.timestamps.get(&resolved_data.version) {
+                md.p(format!("- Published {}", format_date(published)));
+            }
+            match count_newer_versions(&meta, &resolved_version) {
+                (0, _) => md.p("- This is the latest version"),
+                (1, Some(latest)) => md.p(format!("- 1 version behind latest `{latest}`")),
+                (n, Some(latest)) => md.p(format!("- {n} versions behind latest `{latest}`")),
+                (_, None) => {}
+            }
+            if let Some(license) = resolved_data
+                .license
+                .as_ref()
+                .or(meta.package.license.as_ref())
+            {
+                md.p(format!("- License: {}", license.kind()));
+            }
+            if let Some(author) = resolved_data.author.as_ref() {
+                md.p(format!("- Author: {}", author.name()));
+            }
+            if !resolved_data.maintainers.is_empty() {
+                md.p(format!(
+                    "- Maintainers: {}",
+                    name_list(&resolved_data.maintainers)
+                ));
+            }
+            if let Some(reason) = resolved_data.deprecated.as_deref() {
+                md.p(format!("- **Deprecated**: {reason}"));
+            }
+        }
+
+        // Add a summary of what changed since the current version
+        if let Some(notes) = release_notes {
+            md.br();
+            notes.add_hover_section(&mut md);
+        }
+
+        // Add the versions that each tag currently points to
+        if !meta.dist_tags.is_empty() {
+            let mut tags = meta.dist_tags.iter().collect::<Vec<_>>();
+            tags.sort_unstable();
+            md.br();
+            md.h3("Tags");
+            for (tag, tag_version) in tags {
+                md.p(format!("- `{tag}` - {tag_version}"));
+            }
+        }
+
         // Ignore homepage or docs if it's the same as the repo
-        let mut page = meta.current_version.homepage.as_deref();
-        let repo = meta.current_version.repository.as_ref();
+        let mut page = meta.package.homepage.as_deref();
+        let repo = meta.package.repository.as_ref();
         if page.is_some_and(|p| {
             repo.is_some_and(|r| r.url().is_some_and(|u| u.eq_ignore_ascii_case(p)))
         }) {
             page = None;
         }
 
-        // Add links to repo and homepage
+        // Add links to npm, repo and homepage - npm only lists public packages
         md.br();
         md.h3("Links");
+        if registry.is_default() {
+            md.a("npm", format!("https://www.npmjs.com/package/{name}"));
+        }
         if let Some(repo) = repo.and_then(RegistryMetadataRepositoryVariant::url) {
             md.a("Repository", repo);
         }
@@ -73,3 +201,67 @@ pub async fn get_npm_hover(
         }),
     }))
 }
+
+/**
+    Gets cached release notes for all versions between the minimum
+    version of the specifier and the latest non-deprecated version.
+*/
+pub async fn get_npm_release_notes(
+    clients: &Clients,
+    doc: &Document,
+    node: Node<'_>,
+) -> Option<ReleaseNotes> {
+    let dep = npm::parse_dependency(doc, node)?;
+    let (name, spec) = dep.text(doc);
+    let (name, spec) = NpmSpec::parse(&spec).registry_package(&name)?;
+    let registry = NpmConfig::for_document(doc.url()).registry_for_package(name);
+    let meta = clients
+        .npm
+        .get_registry_metadata(&registry, name)
+        .await
+        .ok()?;
+    let version_min = match parse_version_req(spec) {
+        Some(version_req) => version_req.minimum_version(),
+        None => meta.dist_tags.get(parse_tag(spec)?)?.parse_version().ok()?,
+    };
+    let latest = version_min.extract_latest_version(meta.versions.into_values())?;
+    let repository = meta.package.repository.as_ref()?.url()?;
+
+    ReleaseNotes::cached(
+        clients,
+        name,
+        &repository,
+        &version_min,
+        &latest.item_version,
+    )
+}
+
+/**
+    Counts the number of stable, non-deprecated versions newer than
+    the given version, and returns the newest one of those.
+*/
+fn count_newer_versions(meta: &RegistryMetadata, version: &Version) -> (usize, Option<Version>) {
+    let newer = meta
+        .versions
+        .values()
+        .filter(|v| v.deprecated.is_none())
+        .filter_map(|v| v.parse_version().ok())
+        .filter(|v| v.pre.is_empty() && v > version)
+        .collect::<Vec<_>>();
+    (newer.len(), newer.into_iter().max())
+}
+
+fn name_list(humans: &[RegistryMetadataHumanVariant]) -> String {
+    let mut names = humans
+        .iter()
+        .take(MAXIMUM_MAINTAINERS_SHOWN)
+        .map(|human| format!("`{}`", human.name()))
+        .collect::<Vec<_>>();
+    if humans.len() > MAXIMUM_MAINTAINERS_SHOWN {
+        names.push(format!(
+            "and {} more",
+            humans.len() - MAXIMUM_MAINTAINERS_SHOWN
+        ));
+    }
+    names.join(", ")
+}
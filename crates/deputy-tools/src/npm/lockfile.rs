@@ -0,0 +1,635 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+
+use serde::Deserialize;
+
+use async_language_server::lsp_types::Url;
+
+use crate::shared::FileCache;
+
+const PACKAGE_LOCK: &str = "package-lock.json";
+const YARN_LOCK: &str = "yarn.lock";
+const PNPM_LOCK: &str = "pnpm-lock.yaml";
+
+const PNPM_DEPENDENCY_SECTIONS: [&str; 3] =
+    ["dependencies", "devDependencies", "optionalDependencies"];
+const PNPM_PACKAGE_SECTIONS: [&str; 2] = ["packages", "snapshots"];
+
+static LOCKFILES: FileCache<NpmLockfile> = FileCache::new();
+
+/**
+    A package version that was resolved by a package manager.
+
+    The spec is only known for yarn lockfiles, which are keyed
+    by both the name and the spec, such as `react@^18.2.0`.
+*/
+#[derive(Debug, Clone)]
+struct NpmLockedPackage {
+    name: String,
+    spec: Option<String>,
+    version: String,
+}
+
+/**
+    The lockfile for a package, containing versions that were resolved
+    by npm, yarn or pnpm, found next to the `package.json` file or
+    in the root of the workspace that the package belongs to.
+
+    Only versions that are relevant to the package itself are kept,
+    which for npm and pnpm means that other workspace packages are skipped,
+    but the names of all installed packages are kept, including transitive ones.
+*/
+#[derive(Debug, Clone)]
+pub struct NpmLockfile {
+    file_name: &'static str,
+    packages: Vec<NpmLockedPackage>,
+    installed: HashSet<String>,
+}
+
+impl NpmLockfile {
+    /**
+        Reads the lockfile for the package that the given document belongs to,
+        searching upwards for the closest directory containing any lockfile.
+
+        Lockfiles are only parsed again once they have been modified.
+    */
+    pub fn for_document(uri: &Url) -> Option<Arc<Self>> {
+        let doc_path = uri.to_file_path().ok()?;
+        let package_dir = doc_path.parent()?;
+        package_dir.ancestors().find_map(|dir| {
+            let importer = package_dir
+                .strip_prefix(dir)
+                .ok()?
+                .to_string_lossy()
+                .replace('\\', "/");
+            let importer = if importer.is_empty() { "." } else { &importer };
+
+            [PACKAGE_LOCK, PNPM_LOCK, YARN_LOCK]
+                .into_iter()
+                .find_map(|file_name| {
+                    LOCKFILES.get_or_parse(&dir.join(file_name), importer, |contents| {
+                        Self::parse(file_name, contents, importer)
+                    })
+                })
+        })
+    }
+
+    /**
+        Parses the contents of a lockfile with the given file name, keeping
+        only versions that are relevant to the package at the given importer
+        path, which is relative to the lockfile, or `.` for the root package.
+    */
+    fn parse(file_name: &'static str, contents: &str, importer: &str) -> Self {
+        let (packages, installed) = match file_name {
+            PACKAGE_LOCK => parse_package_lock(contents, importer),
+            PNPM_LOCK => parse_pnpm_lock(contents, importer),
+            _ => {
+                let packages = parse_yarn_lock(contents);
+                let installed = packages.iter().map(|p| p.name.clone()).collect();
+                (packages, installed)
+            }
+        };
+        Self {
+            file_name,
+            packages,
+            installed,
+        }
+    }
+
+    pub fn file_name(&self) -> &'static str {
+        self.file_name
+    }
+
+    /**
+        Returns the name of the package manager that created this lockfile.
+    */
+    pub fn package_manager(&self) -> &'static str {
+        match self.file_name {
+            PNPM_LOCK => "pnpm",
+            YARN_LOCK => "yarn",
+            _ => "npm",
+        }
+    }
+
+    /**
+        Returns the locked version for the dependency with the given name and spec.
+
+        Prefers an exact match on the spec, but falls back to any version
+        of the package, which is how outdated lockfiles are detected.
+    */
+    pub fn locked_version(&self, name: &str, spec: &str) -> Option<&str> {
+        let mut candidates = self.packages.iter().filter(|p| p.name == name);
+        candidates
+            .clone()
+            .find(|p| {
+                p.spec
+                    .as_deref()
+                    .is_none_or(|s| s == spec || s.strip_prefix("npm:").is_some_and(|s| s == spec))
+            })
+            .or_else(|| candidates.next())
+            .map(|p| p.version.as_str())
+    }
+
+    /**
+        Checks if any version of the package with the given name is installed,
+        either as a dependency of the package itself, or of any other package.
+    */
+    pub fn is_installed(&self, name: &str) -> bool {
+        self.installed.contains(name)
+    }
+}
+
+/*
+    Lockfile versions 2 and 3 store packages by their path in `packages`:
+
+    ```json
+    {
+        "packages": {
+            "node_modules/react": { "version": "18.2.0" },
+            "packages/app/node_modules/react": { "version": "17.0.2" }
+        }
+    }
+    ```
+
+    Version 1 stores them by name in `dependencies`, without workspaces.
+*/
+fn parse_package_lock(contents: &str, importer: &str) -> (Vec<NpmLockedPackage>, HashSet<String>) {
+    #[derive(Deserialize)]
+    struct PackageLock {
+        #[serde(default)]
+        packages: HashMap<String, PackageLockEntry>,
+        #[serde(default)]
+        dependencies: HashMap<String, PackageLockEntry>,
+    }
+
+    #[derive(Deserialize)]
+    struct PackageLockEntry {
+        #[serde(default)]
+        version: Option<String>,
+    }
+
+    let Ok(lock) = serde_json::from_str::<PackageLock>(contents) else {
+        return (Vec::new(), HashSet::new());
+    };
+
+    let mut installed = lock
+        .packages
+        .keys()
+        .filter_map(|path| path.rsplit_once("node_modules/"))
+        .map(|(_, name)| name.to_string())
+        .collect::<HashSet<_>>();
+    installed.extend(lock.dependencies.keys().cloned());
+
+    // Packages nested in the importer take precedence over hoisted ones
+    let importer_prefix = format!("{importer}/node_modules/");
+    let mut nested = Vec::new();
+    let mut hoisted = Vec::new();
+    for (path, entry) in lock.packages {
+        let Some(version) = entry.version else {
+            continue;
+        };
+        if let Some(name) = path.strip_prefix(&importer_prefix) {
+            nested.push(NpmLockedPackage {
+                name: name.to_string(),
+                spec: None,
+                version,
+            });
+        } else if let Some(name) = path.strip_prefix("node_modules/") {
+            hoisted.push(NpmLockedPackage {
+                name: name.to_string(),
+                spec: None,
+                version,
+            });
+        }
+    }
+
+    let legacy = lock.dependencies.into_iter().filter_map(|(name, entry)| {
+        Some(NpmLockedPackage {
+            name,
+            spec: None,
+            version: entry.version?,
+        })
+    });
+
+    nested.extend(hoisted);
+    nested.extend(legacy);
+    nested.retain(|p| !p.name.contains("/node_modules/"));
+    (nested, installed)
+}
+
+/*
+    Both yarn classic and yarn berry store packages by their descriptors:
+
+    ```yaml
+    "react@^18.0.0", "react@^18.2.0":
+      version "18.2.0"
+
+    "react@npm:^18.2.0":
+      version: 18.2.0
+    ```
+*/
+fn parse_yarn_lock(contents: &str) -> Vec<NpmLockedPackage> {
+    let mut packages = Vec::new();
+    let mut descriptors = Vec::new();
+    for line in contents.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+
+        if !line.starts_with([' ', '\t']) {
+            descriptors = trimmed
+                .trim_end_matches(':')
+                .split(", ")
+                .filter_map(|d| split_yarn_descriptor(d.trim().trim_matches('"')))
+                .collect::<Vec<_>>();
+            continue;
+        }
+
+        let Some(version) = trimmed
+            .strip_prefix("version")
+            .filter(|v| v.starts_with([' ', ':']))
+        else {
+            continue;
+        };
+        let version = version.trim_start_matches(':').trim().trim_matches('"');
+        for (name, spec) in descriptors.drain(..) {
+            packages.push(NpmLockedPackage {
+                name: name.to_string(),
+                spec: Some(spec.to_string()),
+                version: version.to_string(),
+            });
+        }
+    }
+    packages
+}
+
+fn split_yarn_descriptor(descriptor: &str) -> Option<(&str, &str)> {
+    // Scoped package names start with `@`, so skip it when looking for the spec
+    let index = descriptor.get(1..)?.find('@')? + 1;
+    Some((&descriptor[..index], &descriptor[index + 1..]))
+}
+
+/*
+    Lockfile versions 6 and above store direct dependencies per importer:
+
+    ```yaml
+    importers:
+      .:
+        dependencies:
+          react:
+            specifier: ^18.2.0
+            version: 18.2.0(loose-envify@1.4.0)
+    ```
+
+    Older versions store the version directly, as `react: 18.2.0_loose-envify@1.4.0`,
+    and single-package lockfiles may omit `importers` and use top-level sections.
+*/
+fn parse_pnpm_lock(contents: &str, importer: &str) -> (Vec<NpmLockedPackage>, HashSet<String>) {
+    let mut packages = Vec::new();
+    let mut installed = HashSet::new();
+    let mut stack = Vec::<(usize, String)>::new();
+    for line in contents.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+
+        let indent = line.len() - line.trim_start().len();
+        while stack.last().is_some_and(|(i, _)| *i >= indent) {
+            stack.pop();
+        }
+
+        let (key, value) = trimmed
+            .split_once(": ")
+            .unwrap_or((trimmed.trim_end_matches(':'), ""));
+        let key = key.trim().trim_matches(['\'', '"']);
+        let value = value.trim().trim_matches(['\'', '"']);
+        if value.is_empty() {
+            let is_package = matches!(
+                stack.as_slice(),
+                [(_, section)] if PNPM_PACKAGE_SECTIONS.contains(&section.as_str())
+            );
+            if is_package {
+                installed.insert(split_pnpm_package_key(key).to_string());
+            }
+            stack.push((indent, key.to_string()));
+            continue;
+        }
+
+        let mut path = stack.iter().map(|(_, k)| k.as_str()).collect::<Vec<_>>();
+        path.push(key);
+        let (path_importer, path) = match path.as_slice() {
+            ["importers", path_importer, rest @ ..] => (*path_importer, rest.to_vec()),
+            _ => (".", path),
+        };
+        if path_importer != importer {
+            continue;
+        }
+
+        let name = match path.as_slice() {
+            [section, name, "version"] | [section, name]
+                if PNPM_DEPENDENCY_SECTIONS.contains(section) =>
+            {
+                *name
+            }
+            _ => continue,
+        };
+
+        // Strip peer dependency suffixes, such as `(react@18.2.0)` or `_react@18.2.0`
+        let version = value.split(['(', '_']).next().unwrap_or(value).to_string();
+        packages.push(NpmLockedPackage {
+            name: name.to_string(),
+            spec: None,
+            version,
+        });
+    }
+    (packages, installed)
+}
+
+/*
+    Packages are keyed by both their name and version, in one of these formats:
+
+    - `/react@18.2.0` or `react@18.2.0(loose-envify@1.4.0)` for version 6 and above
+    - `/react/18.2.0` or `/@types/react/18.2.0` for version 5 and below, where
+      the version may have a peer dependency suffix, as in `/react-dom/18.2.0_react@18.2.0`
+*/
+fn split_pnpm_package_key(key: &str) -> &str {
+    let key = key.trim_start_matches('/');
+
+    // Version 5 keys must be matched first, since their peer suffixes also contain `@`
+    let scope_len = if key.starts_with('@') {
+        key.find('/').map_or(0, |index| index + 1)
+    } else {
+        0
+    };
+    if let Some((name, version)) = key[scope_len..].split_once('/') {
+        let is_version = version.starts_with(|c: char| c.is_ascii_digit());
+        if is_version && !name.contains('@') {
+            return &key[..scope_len + name.len()];
+        }
+    }
+
+    match key.get(1..).and_then(|k| k.find('@')) {
+        Some(index) => &key[..=index],
+        None => key,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn locked<'a>(lockfile: &'a NpmLockfile, name: &str, spec: &str) -> Option<&'a str> {
+        lockfile.locked_version(name, spec)
+    }
+
+    #[test]
+    fn package_lock_v1() {
+        let lockfile = NpmLockfile::parse(
+            PACKAGE_LOCK,
+            r#"{
+                "lockfileVersion": 1,
+                "dependencies": {
+                    "react": { "version": "18.2.0" },
+                    "@types/react": { "version": "18.2.7" },
+                    "loose-envify": { "version": "1.4.0" }
+                }
+            }"#,
+            ".",
+        );
+        assert_eq!(lockfile.package_manager(), "npm");
+        assert_eq!(locked(&lockfile, "react", "^18.0.0"), Some("18.2.0"));
+        assert_eq!(locked(&lockfile, "@types/react", "^18"), Some("18.2.7"));
+        assert!(lockfile.is_installed("loose-envify"));
+        assert!(!lockfile.is_installed("react-dom"));
+    }
+
+    #[test]
+    fn package_lock_v2_and_v3() {
+        let contents = r#"{
+            "lockfileVersion": 3,
+            "packages": {
+                "": { "name": "root", "workspaces": ["packages/app"] },
+                "node_modules/react": { "version": "18.2.0" },
+                "node_modules/@types/react": { "version": "18.2.7" },
+                "node_modules/react/node_modules/loose-envify": { "version": "1.4.0" },
+                "node_modules/app": { "resolved": "packages/app", "link": true },
+                "packages/app/node_modules/react": { "version": "17.0.2" }
+            }
+        }"#;
+
+        let root = NpmLockfile::parse(PACKAGE_LOCK, contents, ".");
+        assert_eq!(locked(&root, "react", "^18.0.0"), Some("18.2.0"));
+        assert_eq!(locked(&root, "@types/react", "^18"), Some("18.2.7"));
+        assert_eq!(locked(&root, "loose-envify", "^1"), None);
+        assert!(root.is_installed("loose-envify"));
+
+        let app = NpmLockfile::parse(PACKAGE_LOCK, contents, "packages/app");
+        assert_eq!(locked(&app, "react", "^17.0.0"), Some("17.0.2"));
+        assert_eq!(locked(&app, "@types/react", "^18"), Some("18.2.7"));
+    }
+
+    #[test]
+    fn yarn_classic() {
+        let lockfile = NpmLockfile::parse(
+            YARN_LOCK,
+            r#"# THIS IS AN AUTOGENERATED FILE. DO NOT EDIT THIS FILE DIRECTLY.
+# yarn lockfile v1
+
+
+"@types/react@^18.0.0":
+  version "18.2.7"
+  resolved "https://registry.yarnpkg.com/@types/react/-/react-18.2.7.tgz"
+
+react@^17.0.0:
+  version "17.0.2"
+
+react@^18.0.0, react@^18.2.0:
+  version "18.2.0"
+  dependencies:
+    loose-envify "^1.1.0"
+"#,
+            ".",
+        );
+        assert_eq!(lockfile.package_manager(), "yarn");
+        assert_eq!(locked(&lockfile, "react", "^18.2.0"), Some("18.2.0"));
+        assert_eq!(locked(&lockfile, "react", "^17.0.0"), Some("17.0.2"));
+        assert_eq!(locked(&lockfile, "@types/react", "^18.0.0"), Some("18.2.7"));
+        assert!(!lockfile.is_installed("loose-envify"));
+    }
+
+    #[test]
+    fn yarn_berry() {
+        let lockfile = NpmLockfile::parse(
+            YARN_LOCK,
+            r#"# This file is generated by running "yarn install" inside your project.
+
+__metadata:
+  version: 6
+  cacheKey: 8
+
+"@types/react@npm:^18.0.0":
+  version: 18.2.7
+  resolution: "@types/react@npm:18.2.7"
+
+"react@npm:^18.0.0, react@npm:^18.2.0":
+  version: 18.2.0
+  resolution: "react@npm:18.2.0"
+"#,
+            ".",
+        );
+        assert_eq!(locked(&lockfile, "react", "^18.2.0"), Some("18.2.0"));
+        assert_eq!(locked(&lockfile, "@types/react", "^18.0.0"), Some("18.2.7"));
+        assert!(!lockfile.is_installed("__metadata"));
+    }
+
+    #[test]
+    fn pnpm_v5() {
+        let lockfile = NpmLockfile::parse(
+            PNPM_LOCK,
+            "lockfileVersion: 5.4
+
+specifiers:
+  '@types/react': ^18.0.0
+  react: ^18.2.0
+  react-dom: ^18.2.0
+
+dependencies:
+  react: 18.2.0
+  react-dom: 18.2.0_react@18.2.0
+
+devDependencies:
+  '@types/react': 18.2.7
+
+packages:
+
+  /@types/react/18.2.7:
+    resolution: {integrity: sha512-abc}
+    dev: true
+
+  /loose-envify/1.4.0:
+    resolution: {integrity: sha512-def}
+
+  /react-dom/18.2.0_react@18.2.0:
+    resolution: {integrity: sha512-ghi}
+",
+            ".",
+        );
+        assert_eq!(lockfile.package_manager(), "pnpm");
+        assert_eq!(locked(&lockfile, "react", "^18.2.0"), Some("18.2.0"));
+        assert_eq!(locked(&lockfile, "react-dom", "^18.2.0"), Some("18.2.0"));
+        assert_eq!(locked(&lockfile, "@types/react", "^18.0.0"), Some("18.2.7"));
+        assert!(lockfile.is_installed("@types/react"));
+        assert!(lockfile.is_installed("loose-envify"));
+        assert!(lockfile.is_installed("react-dom"));
+        assert!(!lockfile.is_installed("react-dom/18.2.0_react"));
+    }
+
+    #[test]
+    fn pnpm_v6() {
+        let contents = "lockfileVersion: '6.0'
+
+importers:
+
+  .:
+    dependencies:
+      react:
+        specifier: ^18.2.0
+        version: 18.2.0
+
+  packages/app:
+    dependencies:
+      react-dom:
+        specifier: ^17.0.0
+        version: 17.0.2(react@17.0.2)
+
+packages:
+
+  /@types/react@18.2.7:
+    resolution: {integrity: sha512-abc}
+
+  /react-dom@17.0.2(react@17.0.2):
+    resolution: {integrity: sha512-def}
+";
+        let root = NpmLockfile::parse(PNPM_LOCK, contents, ".");
+        assert_eq!(locked(&root, "react", "^18.2.0"), Some("18.2.0"));
+        assert_eq!(locked(&root, "react-dom", "^17.0.0"), None);
+        assert!(root.is_installed("@types/react"));
+        assert!(root.is_installed("react-dom"));
+
+        let app = NpmLockfile::parse(PNPM_LOCK, contents, "packages/app");
+        assert_eq!(locked(&app, "react-dom", "^17.0.0"), Some("17.0.2"));
+        assert_eq!(locked(&app, "react", "^18.2.0"), None);
+    }
+
+    #[test]
+    fn pnpm_v9() {
+        let lockfile = NpmLockfile::parse(
+            PNPM_LOCK,
+            "lockfileVersion: '9.0'
+
+importers:
+
+  .:
+    dependencies:
+      '@types/react':
+        specifier: ^18.0.0
+        version: 18.2.7
+      react-dom:
+        specifier: ^18.2.0
+        version: 18.2.0(react@18.2.0)
+
+packages:
+
+  '@types/react@18.2.7':
+    resolution: {integrity: sha512-abc}
+
+snapshots:
+
+  loose-envify@1.4.0: {}
+
+  react-dom@18.2.0(react@18.2.0):
+    dependencies:
+      react: 18.2.0
+",
+            ".",
+        );
+        assert_eq!(locked(&lockfile, "@types/react", "^18.0.0"), Some("18.2.7"));
+        assert_eq!(locked(&lockfile, "react-dom", "^18.2.0"), Some("18.2.0"));
+        assert!(lockfile.is_installed("@types/react"));
+        assert!(lockfile.is_installed("react-dom"));
+    }
+
+    #[test]
+    fn pnpm_package_keys() {
+        assert_eq!(split_pnpm_package_key("/react/18.2.0"), "react");
+        assert_eq!(
+            split_pnpm_package_key("/@types/react/18.2.7"),
+            "@types/react"
+        );
+        assert_eq!(
+            split_pnpm_package_key("/react-dom/18.2.0_react@18.2.0"),
+            "react-dom"
+        );
+        assert_eq!(
+            split_pnpm_package_key("/@testing-library/react/14.0.0_react-dom@18.2.0+react@18.2.0"),
+            "@testing-library/react"
+        );
+        assert_eq!(split_pnpm_package_key("/react@18.2.0"), "react");
+        assert_eq!(
+            split_pnpm_package_key("/@types/react@18.2.7"),
+            "@types/react"
+        );
+        assert_eq!(
+            split_pnpm_package_key("react-dom@18.2.0(@types/react@18.2.7)(react@18.2.0)"),
+            "react-dom"
+        );
+        assert_eq!(
+            split_pnpm_package_key("@types/react@18.2.7"),
+            "@types/react"
+        );
+    }
+}
@@ -2,22 +2,39 @@ use futures::future::try_join_all;
 use tracing::debug;
 
 use async_language_server::{
-    lsp_types::{CompletionResponse, Diagnostic, DocumentDiagnosticParams, Hover, Position},
+    lsp_types::{
+        CompletionItem, CompletionResponse, Diagnostic, DocumentDiagnosticParams, Hover, Position,
+        Url,
+    },
     server::{Document, ServerResult},
     tree_sitter::Node,
+    tree_sitter_utils::ts_range_contains_lsp_position,
 };
 
 use deputy_clients::Clients;
 use deputy_parser::npm;
 
+use crate::shared::ReleaseNotes;
+
 mod completion;
 mod constants;
 mod diagnostics;
+mod engines;
 mod hover;
+mod lockfile;
+mod npmrc;
+mod package_manager;
+mod spec;
+mod util;
+mod workspace;
 
-use completion::get_npm_completions;
-use diagnostics::get_npm_diagnostics;
-use hover::get_npm_hover;
+use completion::{
+    get_npm_completions, get_npm_entry_completions, get_package_manager_completions,
+    resolve_npm_entry,
+};
+use diagnostics::{NpmDiagnosticsContext, get_npm_diagnostics, get_package_manager_diagnostics};
+use hover::{get_npm_hover, get_npm_release_notes};
+use npmrc::NpmConfig;
 
 #[derive(Debug, Clone)]
 pub struct Npm {
@@ -41,7 +58,16 @@ impl Npm {
 
         debug!("Hovering: {dep:?}");
 
-        get_npm_hover(&self.clients, doc, dep).await
+        get_npm_hover(&self.clients, doc, pos, dep).await
+    }
+
+    pub(super) async fn release_notes(
+        &self,
+        doc: &Document,
+        pos: Position,
+    ) -> Option<ReleaseNotes> {
+        let dep = npm::find_dependency_at(doc, pos)?;
+        get_npm_release_notes(&self.clients, doc, dep).await
     }
 
     pub(super) async fn completion(
@@ -50,8 +76,17 @@ impl Npm {
         pos: Position,
         _node: Node<'_>,
     ) -> ServerResult<Option<CompletionResponse>> {
+        if let Some(package_manager) = npm::find_package_manager(doc)
+            .filter(|node| ts_range_contains_lsp_position(node.range(), pos))
+        {
+            return get_package_manager_completions(&self.clients, doc, package_manager).await;
+        }
+
         let Some(dep) = npm::find_dependency_at(doc, pos) else {
-            return Ok(None);
+            return match npm::find_incomplete_dependency_at(doc, pos) {
+                Some(dep) => get_npm_entry_completions(&self.clients, doc, dep).await,
+                None => Ok(None),
+            };
         };
 
         debug!("Fetching completions: {dep:?}");
@@ -59,26 +94,46 @@ impl Npm {
         get_npm_completions(&self.clients, doc, pos, dep).await
     }
 
+    pub(super) async fn completion_resolve(
+        &self,
+        item: CompletionItem,
+        uri: &Url,
+        name: &str,
+    ) -> ServerResult<CompletionItem> {
+        let registry = NpmConfig::for_document(uri).registry_for_package(name);
+        resolve_npm_entry(&self.clients, item, &registry, name).await
+    }
+
     pub(super) async fn diagnostics(
         &self,
         doc: &Document,
         _params: DocumentDiagnosticParams,
     ) -> ServerResult<Vec<Diagnostic>> {
-        // Find all dependencies
+        // Find all dependencies, and the package manager
         let dependencies = npm::find_all_dependencies(doc);
-        if dependencies.is_empty() {
+        let package_manager = npm::find_package_manager(doc);
+        if dependencies.is_empty() && package_manager.is_none() {
             return Ok(Vec::new());
         }
 
-        // Fetch all diagnostics concurrently
+        // Fetch all diagnostics concurrently, sharing the registry configuration, lockfile and engines
         debug!("Fetching npm diagnostics for dependencies");
+        let context = NpmDiagnosticsContext::for_document(doc);
         let results = try_join_all(
             dependencies
                 .into_iter()
-                .map(|node| get_npm_diagnostics(&self.clients, doc, node)),
+                .map(|node| get_npm_diagnostics(&self.clients, &context, doc, node)),
         )
         .await?;
 
-        Ok(results.into_iter().flatten().collect())
+        let mut diagnostics = results.into_iter().flatten().collect::<Vec<_>>();
+        if let Some(package_manager) = package_manager {
+            diagnostics.extend(
+                get_package_manager_diagnostics(&self.clients, &context, doc, package_manager)
+                    .await,
+            );
+        }
+
+        Ok(diagnostics)
     }
 }
@@ -0,0 +1,387 @@
+use std::{
+    collections::HashMap,
+    env,
+    path::{Path, PathBuf},
+};
+
+use async_language_server::lsp_types::Url;
+
+use deputy_clients::npm::NpmRegistry;
+
+use crate::shared::FileCache;
+
+const NPMRC: &str = ".npmrc";
+
+static NPMRC_FILES: FileCache<String> = FileCache::new();
+
+/**
+    Credentials for a registry, keyed by its URL without the scheme,
+    such as `//npm.company/`, which is also how they are keyed in `.npmrc`.
+*/
+#[derive(Debug, Clone)]
+enum NpmCredentials {
+    AuthToken(String),
+    BasicAuth(String),
+}
+
+/**
+    Registry configuration read from `.npmrc` files, used to find which
+    registry a package should be fetched from, and how to authorize with it.
+
+    Files closer to the package take precedence over files further up
+    the directory tree, which in turn take precedence over the user file.
+*/
+#[derive(Debug, Clone, Default)]
+pub struct NpmConfig {
+    registry: Option<String>,
+    scoped_registries: HashMap<String, String>,
+    credentials: HashMap<String, NpmCredentials>,
+}
+
+impl NpmConfig {
+    /**
+        Reads the configuration for the package that the given document belongs to.
+
+        Documents that are not on disk only use the user configuration.
+    */
+    pub fn for_document(uri: &Url) -> Self {
+        Self::for_document_with_user_config(uri, user_config_path().as_deref())
+    }
+
+    /**
+        Reads the configuration for the package that the given document
+        belongs to, using the given user configuration file, if any.
+    */
+    fn for_document_with_user_config(uri: &Url, user_config: Option<&Path>) -> Self {
+        let mut config = Self::default();
+
+        let doc_path = uri.to_file_path().ok();
+        for dir in doc_path.iter().flat_map(|path| path.ancestors().skip(1)) {
+            config.read_file(&dir.join(NPMRC));
+        }
+        if let Some(path) = user_config {
+            config.read_file(path);
+        }
+
+        config
+    }
+
+    /**
+        Returns the registry that the package with the given name should
+        be fetched from - the registry for its scope if one is configured,
+        otherwise the default registry, along with any matching credentials.
+    */
+    pub fn registry_for_package(&self, name: &str) -> NpmRegistry {
+        let url = name
+            .split_once('/')
+            .filter(|(scope, _)| scope.starts_with('@'))
+            .and_then(|(scope, _)| self.scoped_registries.get(scope))
+            .or(self.registry.as_ref());
+        let registry = url.map_or_else(NpmRegistry::default, NpmRegistry::new);
+
+        match self.credentials_for_url(registry.url()) {
+            Some(NpmCredentials::AuthToken(token)) => registry.with_auth_token(token),
+            Some(NpmCredentials::BasicAuth(auth)) => registry.with_basic_auth(auth),
+            None => registry,
+        }
+    }
+
+    /**
+        Finds the most specific credentials for the given registry URL - for
+        example, `//npm.company/private/` is preferred over `//npm.company/`.
+    */
+    fn credentials_for_url(&self, url: &str) -> Option<&NpmCredentials> {
+        let key = registry_key(url);
+        self.credentials
+            .iter()
+            .filter(|(prefix, _)| key.starts_with(prefix.as_str()))
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map(|(_, credentials)| credentials)
+    }
+
+    fn read_file(&mut self, path: &Path) {
+        if let Some(contents) = NPMRC_FILES.get_or_parse(path, "", str::to_string) {
+            self.read_contents(&contents);
+        }
+    }
+
+    /**
+        Reads settings from the contents of a single `.npmrc` file, keeping
+        any settings that were already read from a more specific file.
+    */
+    fn read_contents(&mut self, contents: &str) {
+        for line in contents.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with(['#', ';']) {
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+
+            let key = interpolate_env(key.trim());
+            let value = interpolate_env(value.trim().trim_matches(['"', '\'']));
+            if key == "registry" {
+                self.registry.get_or_insert(value);
+                continue;
+            }
+
+            let Some((prefix, setting)) = key.rsplit_once(':') else {
+                continue;
+            };
+            if prefix.starts_with('@') && setting == "registry" {
+                self.scoped_registries
+                    .entry(prefix.to_string())
+                    .or_insert(value);
+            } else if prefix.starts_with("//") {
+                let credentials = match setting {
+                    "_authToken" => NpmCredentials::AuthToken(value),
+                    "_auth" => NpmCredentials::BasicAuth(value),
+                    _ => continue,
+                };
+                self.credentials
+                    .entry(registry_key(prefix))
+                    .or_insert(credentials);
+            }
+        }
+    }
+}
+
+fn user_config_path() -> Option<PathBuf> {
+    if let Some(path) =
+        env::var_os("NPM_CONFIG_USERCONFIG").or_else(|| env::var_os("npm_config_userconfig"))
+    {
+        return Some(PathBuf::from(path));
+    }
+    let home = env::var_os("HOME").or_else(|| env::var_os("USERPROFILE"))?;
+    Some(PathBuf::from(home).join(NPMRC))
+}
+
+/*
+    Registries are keyed without their scheme, and always with a trailing
+    slash, so that `https://npm.company` matches `//npm.company/:_authToken`
+*/
+fn registry_key(url: &str) -> String {
+    let without_scheme = url.find("//").map_or(url, |index| &url[index..]);
+    format!("{}/", without_scheme.trim_end_matches('/'))
+}
+
+/*
+    Replaces environment variables in the form of `${NAME}`, same as npm.
+
+    Variables that are not set are kept as-is, unless they are written
+    as `${NAME?}`, in which case they are replaced with an empty string.
+*/
+fn interpolate_env(s: &str) -> String {
+    let mut result = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(start) = rest.find("${") {
+        let Some(len) = rest[start..].find('}') else {
+            break;
+        };
+        let name = &rest[start + 2..start + len];
+        result.push_str(&rest[..start]);
+        match (name.strip_suffix('?'), env::var(name.trim_end_matches('?'))) {
+            (_, Ok(value)) => result.push_str(&value),
+            (Some(_), Err(_)) => {}
+            (None, Err(_)) => result.push_str(&rest[start..=start + len]),
+        }
+        rest = &rest[start + len + 1..];
+    }
+    result.push_str(rest);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        env, fs,
+        io::{BufRead, BufReader, Write},
+        net::TcpListener,
+        thread,
+    };
+
+    use deputy_clients::npm::NpmClient;
+
+    use crate::shared::TempDir;
+
+    use super::*;
+
+    fn config(files: &[&str]) -> NpmConfig {
+        let mut config = NpmConfig::default();
+        for contents in files {
+            config.read_contents(contents);
+        }
+        config
+    }
+
+    #[test]
+    fn default_registry() {
+        let config = config(&["# comment\n; another comment\n"]);
+        assert_eq!(config.registry_for_package("react"), NpmRegistry::default());
+        assert_eq!(
+            config.registry_for_package("@types/react"),
+            NpmRegistry::default()
+        );
+    }
+
+    #[test]
+    fn scoped_registries() {
+        let config = config(&[
+            "@company:registry=https://npm.company/\nregistry = \"https://mirror.example/\"",
+            "@company:registry=https://ignored.example/\n@other:registry=https://npm.other/",
+        ]);
+        assert_eq!(
+            config.registry_for_package("@company/ui").url(),
+            "https://npm.company/"
+        );
+        assert_eq!(
+            config.registry_for_package("@other/ui").url(),
+            "https://npm.other/"
+        );
+        assert_eq!(
+            config.registry_for_package("@unknown/ui").url(),
+            "https://mirror.example/"
+        );
+        assert_eq!(
+            config.registry_for_package("react").url(),
+            "https://mirror.example/"
+        );
+    }
+
+    #[test]
+    fn credentials_by_longest_prefix() {
+        let config = config(&["
+            @company:registry=https://npm.company/private/
+            @public:registry=https://npm.company/public
+            @other:registry=https://npm.other
+            //npm.company/:_auth=dXNlcjpwYXNz
+            //npm.company/private/:_authToken=private-token
+            //npm.other/:_password=ignored
+        "]);
+        assert_eq!(
+            config.registry_for_package("@company/ui"),
+            NpmRegistry::new("https://npm.company/private/").with_auth_token("private-token")
+        );
+        assert_eq!(
+            config.registry_for_package("@public/ui"),
+            NpmRegistry::new("https://npm.company/public").with_basic_auth("dXNlcjpwYXNz")
+        );
+        assert_eq!(
+            config.registry_for_package("@other/ui"),
+            NpmRegistry::new("https://npm.other")
+        );
+    }
+
+    #[test]
+    fn closer_credentials_take_precedence() {
+        let config = config(&[
+            "//npm.company/:_authToken=project-token",
+            "//npm.company/:_authToken=user-token\n@company:registry=https://npm.company",
+        ]);
+        assert_eq!(
+            config.registry_for_package("@company/ui"),
+            NpmRegistry::new("https://npm.company").with_auth_token("project-token")
+        );
+    }
+
+    #[test]
+    fn environment_variables() {
+        // SAFETY: Only this test reads or writes this variable
+        unsafe { env::set_var("DEPUTY_TEST_NPM_TOKEN", "env-token") };
+
+        assert_eq!(interpolate_env("${DEPUTY_TEST_NPM_TOKEN}"), "env-token");
+        assert_eq!(
+            interpolate_env("a-${DEPUTY_TEST_NPM_TOKEN}-b"),
+            "a-env-token-b"
+        );
+        assert_eq!(
+            interpolate_env("${DEPUTY_TEST_NPM_MISSING}"),
+            "${DEPUTY_TEST_NPM_MISSING}"
+        );
+        assert_eq!(interpolate_env("${DEPUTY_TEST_NPM_MISSING?}"), "");
+        assert_eq!(interpolate_env("${unterminated"), "${unterminated");
+
+        let config = config(&[
+            "@company:registry=https://npm.company/\n//npm.company/:_authToken=${DEPUTY_TEST_NPM_TOKEN}",
+        ]);
+        assert_eq!(
+            config.registry_for_package("@company/ui"),
+            NpmRegistry::new("https://npm.company/").with_auth_token("env-token")
+        );
+    }
+
+    /**
+        Serves a single abbreviated metadata document, as a stand-in
+        for a private registry, and returns the request that it received.
+    */
+    fn serve_registry(listener: TcpListener) -> thread::JoinHandle<Vec<String>> {
+        thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut request = Vec::new();
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if line.trim().is_empty() {
+                    break;
+                }
+                request.push(line.trim().to_string());
+            }
+
+            let body = r#"{
+                "name": "@company/ui",
+                "dist-tags": { "latest": "1.2.0" },
+                "versions": {
+                    "1.0.0": { "name": "@company/ui", "version": "1.0.0" },
+                    "1.2.0": { "name": "@company/ui", "version": "1.2.0" }
+                }
+            }"#;
+            let mut stream = reader.into_inner();
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\n\
+                Content-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            )
+            .unwrap();
+            request
+        })
+    }
+
+    #[tokio::test]
+    async fn private_registry() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = serve_registry(listener);
+
+        let temp = TempDir::new("npmrc");
+        let dir = temp.path();
+        fs::write(
+            dir.join(NPMRC),
+            format!(
+                "@company:registry=http://127.0.0.1:{port}/\n\
+                //127.0.0.1:{port}/:_authToken=local-token\n"
+            ),
+        )
+        .unwrap();
+        let uri = Url::from_file_path(dir.join("package.json")).unwrap();
+        let registry = NpmConfig::for_document_with_user_config(&uri, None)
+            .registry_for_package("@company/ui");
+
+        let metadata = NpmClient::new()
+            .get_registry_metadata_abbreviated(&registry, "@company/ui")
+            .await
+            .unwrap();
+        let request = server.join().unwrap();
+
+        assert_eq!(request[0], "GET /@company%2fui HTTP/1.1");
+        assert!(
+            request
+                .iter()
+                .any(|line| line.eq_ignore_ascii_case("authorization: Bearer local-token"))
+        );
+        assert_eq!(metadata.dist_tags["latest"], "1.2.0");
+        assert_eq!(metadata.versions.len(), 2);
+    }
+}
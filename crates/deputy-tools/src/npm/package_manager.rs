@@ -0,0 +1,176 @@
+use std::fmt::Write;
+
+use deputy_clients::npm::models::RegistryMetadataDist;
+use deputy_versioning::Versioned;
+
+/**
+    Package managers that may be declared in the `packageManager` field,
+    which are the package managers that Corepack knows how to install.
+*/
+pub const PACKAGE_MANAGERS: [&str; 3] = ["npm", "pnpm", "yarn"];
+
+const YARN_CLASSIC_PACKAGE: &str = "yarn";
+const YARN_BERRY_PACKAGE: &str = "@yarnpkg/cli-dist";
+
+/**
+    A package manager declared in the `packageManager` field, such as `pnpm@8.6.0`,
+    optionally followed by a hash of its tarball, as in `pnpm@8.6.0+sha512.<hex>`.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NpmPackageManager<'a> {
+    pub name: &'a str,
+    pub version: &'a str,
+    pub hash_algorithm: Option<&'a str>,
+}
+
+impl<'a> NpmPackageManager<'a> {
+    pub fn parse(value: &'a str) -> Option<Self> {
+        let (name, rest) = value.trim().split_once('@')?;
+        let (version, hash_algorithm) = match rest.split_once('+') {
+            Some((version, hash)) => (version, Some(hash.split_once('.')?.0)),
+            None => (rest, None),
+        };
+        Some(Self {
+            name,
+            version,
+            hash_algorithm,
+        })
+    }
+
+    /**
+        Returns the package in the registry that this version of the package
+        manager is published as - Yarn 2 and above are published separately.
+    */
+    pub fn registry_package(&self) -> Option<&'static str> {
+        if self.name == "yarn" {
+            let major = self.version.parse_version().ok()?.major;
+            return Some(if major < 2 {
+                YARN_CLASSIC_PACKAGE
+            } else {
+                YARN_BERRY_PACKAGE
+            });
+        }
+        registry_packages(self.name).first().copied()
+    }
+}
+
+/**
+    Returns all packages in the registry that versions of the package manager
+    with the given name are published as, which is more than one for Yarn.
+*/
+pub fn registry_packages(name: &str) -> &'static [&'static str] {
+    match name {
+        "npm" => &["npm"],
+        "pnpm" => &["pnpm"],
+        "yarn" => &[YARN_CLASSIC_PACKAGE, YARN_BERRY_PACKAGE],
+        _ => &[],
+    }
+}
+
+/**
+    Computes the hash of a published tarball in the same format as Corepack,
+    such as `sha512.<hex>`, which is only possible for the algorithms that the
+    registry publishes checksums for - SHA-1 and those in the integrity string.
+
+    Corepack installs Yarn 2 and above from a standalone bundle rather than
+    from the published tarball, so no hash can be computed for those versions.
+*/
+pub fn corepack_hash(
+    package: &str,
+    algorithm: &str,
+    dist: &RegistryMetadataDist,
+) -> Option<String> {
+    if package == YARN_BERRY_PACKAGE {
+        return None;
+    }
+    let digest = if algorithm == "sha1" {
+        dist.shasum.clone()?
+    } else {
+        let encoded = dist
+            .integrity
+            .as_deref()?
+            .split_whitespace()
+            .find_map(|integrity| integrity.strip_prefix(algorithm)?.strip_prefix('-'))?;
+        base64_to_hex(encoded)?
+    };
+    Some(format!("{algorithm}.{digest}"))
+}
+
+/*
+    Integrity strings use standard base64, while Corepack uses hexadecimal:
+
+    `sha512-z4PhNX7vuL3xVChQ1m2AB9Yg5AULVxXcg/SpIdNs6c5H0NE8XYXysP+DGNKHfuwvY7kxvUR1xpzvzmlb2mXEVg==`
+*/
+fn base64_to_hex(encoded: &str) -> Option<String> {
+    let mut hex = String::with_capacity(encoded.len() * 3 / 2);
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    for byte in encoded.trim_end_matches('=').bytes() {
+        let value = match byte {
+            b'A'..=b'Z' => byte - b'A',
+            b'a'..=b'z' => byte - b'a' + 26,
+            b'0'..=b'9' => byte - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            _ => return None,
+        };
+        buffer = ((buffer << 6) | u32::from(value)) & 0xffff;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            write!(hex, "{:02x}", (buffer >> bits) & 0xff).ok()?;
+        }
+    }
+    Some(hex)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dist(integrity: &str, shasum: &str) -> RegistryMetadataDist {
+        RegistryMetadataDist {
+            integrity: Some(integrity.to_string()),
+            shasum: Some(shasum.to_string()),
+        }
+    }
+
+    #[test]
+    fn base64_to_hex_padding() {
+        assert_eq!(base64_to_hex("").as_deref(), Some(""));
+        assert_eq!(base64_to_hex("Zg==").as_deref(), Some("66"));
+        assert_eq!(base64_to_hex("Zm8=").as_deref(), Some("666f"));
+        assert_eq!(base64_to_hex("Zm9v").as_deref(), Some("666f6f"));
+        assert_eq!(base64_to_hex("+/+/").as_deref(), Some("fbffbf"));
+        assert_eq!(base64_to_hex("Zm9v!"), None);
+    }
+
+    #[test]
+    fn corepack_hash_sha512() {
+        // The SHA-512 digest of an empty input, in both formats
+        let dist = dist(
+            "sha512-z4PhNX7vuL3xVChQ1m2AB9Yg5AULVxXcg/SpIdNs6c5H0NE8XYXysP+DGNKHfuwvY7kxvUR1xpzvzmlb2mXEVg==",
+            "da39a3ee5e6b4b0d3255bfef95601890afd80709",
+        );
+        assert_eq!(
+            corepack_hash("pnpm", "sha512", &dist).as_deref(),
+            Some(
+                "sha512.cf83e1357eefb8bdf1542850d66d8007d620e4050b5715dc83f4a921d36ce9ce\
+                47d0d13c5d85f2b0ff8318d2877eec2f63b931bd4475c69cefce695bda65c456"
+            )
+        );
+        assert_eq!(
+            corepack_hash("pnpm", "sha1", &dist).as_deref(),
+            Some("sha1.da39a3ee5e6b4b0d3255bfef95601890afd80709")
+        );
+        assert_eq!(corepack_hash("pnpm", "sha256", &dist), None);
+    }
+
+    #[test]
+    fn corepack_hash_yarn_berry() {
+        let dist = dist("sha512-Zm9v", "0beec7b5ea3f0fdbc95d0dd47f3c5bc275da8a33");
+        assert!(corepack_hash(YARN_CLASSIC_PACKAGE, "sha1", &dist).is_some());
+        assert_eq!(corepack_hash(YARN_BERRY_PACKAGE, "sha1", &dist), None);
+        assert_eq!(corepack_hash(YARN_BERRY_PACKAGE, "sha512", &dist), None);
+    }
+}
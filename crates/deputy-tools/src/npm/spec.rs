@@ -0,0 +1,171 @@
+use crate::shared::parse_github_repository;
+
+/**
+    A dependency spec in a `package.json` file, classified by its protocol.
+
+    See the [npm documentation](https://docs.npmjs.com/cli/configuring-npm/package-json#dependencies)
+    for all of the supported formats, as well as the `workspace:` protocol
+    which is supported by yarn, pnpm and bun.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NpmSpec<'a> {
+    /**
+        A version range or a tag, such as `^1.2.3` or `latest`.
+    */
+    Range(&'a str),
+    /**
+        An alias for another package, such as `npm:other@^1.2.3`.
+
+        The range is empty if no version was given.
+    */
+    Alias { name: &'a str, range: &'a str },
+    /**
+        A git repository, such as `github:owner/repo#v1.2.3`,
+        `owner/repo`, or `git+https://github.com/owner/repo.git`.
+    */
+    Git(NpmGitSpec<'a>),
+    /**
+        A path to a local directory or tarball, such as `file:../package`.
+    */
+    File(&'a str),
+    /**
+        A path to a local directory that is symlinked, such as `link:../package`.
+    */
+    Link(&'a str),
+    /**
+        A URL to a remote tarball, such as `https://example.com/package.tgz`.
+    */
+    Tarball(&'a str),
+    /**
+        A package in the same workspace, such as `workspace:*` or `workspace:^1.2.3`.
+    */
+    Workspace(&'a str),
+}
+
+impl<'a> NpmSpec<'a> {
+    pub fn parse(spec: &'a str) -> Self {
+        let spec = spec.trim();
+
+        if let Some(rest) = spec.strip_prefix("npm:") {
+            // Scoped package names start with `@`, so skip it when looking for the version
+            let (name, range) = match rest.get(1..).and_then(|r| r.find('@')) {
+                Some(index) => (&rest[..=index], &rest[index + 2..]),
+                None => (rest, ""),
+            };
+            return Self::Alias { name, range };
+        }
+
+        if let Some(rest) = spec.strip_prefix("workspace:") {
+            return Self::Workspace(rest);
+        }
+        if let Some(rest) = spec.strip_prefix("file:") {
+            return Self::File(rest);
+        }
+        if let Some(rest) = spec.strip_prefix("link:") {
+            return Self::Link(rest);
+        }
+        if ["./", "../", "/", "~/"].iter().any(|p| spec.starts_with(p)) {
+            return Self::File(spec);
+        }
+
+        if [
+            "git+",
+            "git:",
+            "git@",
+            "github:",
+            "gitlab:",
+            "bitbucket:",
+            "gist:",
+        ]
+        .iter()
+        .any(|p| spec.starts_with(p))
+            || is_github_shorthand(spec)
+        {
+            return Self::Git(NpmGitSpec::parse(spec));
+        }
+
+        if spec.starts_with("http://") || spec.starts_with("https://") {
+            return Self::Tarball(spec);
+        }
+
+        Self::Range(spec)
+    }
+
+    /**
+        Returns the name of the package in the registry, as well as the version
+        range for it, if this spec refers to a package in the registry at all.
+
+        The given name is the name of the dependency, which is used unless aliased.
+    */
+    pub fn registry_package<'b>(self, name: &'b str) -> Option<(&'b str, &'b str)>
+    where
+        'a: 'b,
+    {
+        match self {
+            Self::Range(range) => Some((name, range)),
+            Self::Alias { name, range } => Some((name, range)),
+            _ => None,
+        }
+    }
+}
+
+/**
+    A git repository dependency, with its optional `#` fragment parsed.
+
+    The fragment may contain a commit-ish, such as a tag, branch or commit
+    hash, or a `semver:` range which is matched against tags in the repository.
+    Multiple parts of the fragment are separated by `::`, as in `#semver:^1::path:sub`.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NpmGitSpec<'a> {
+    pub url: &'a str,
+    pub committish: Option<&'a str>,
+    pub semver: Option<&'a str>,
+}
+
+impl<'a> NpmGitSpec<'a> {
+    fn parse(spec: &'a str) -> Self {
+        let (url, fragment) = spec.split_once('#').unwrap_or((spec, ""));
+
+        let mut committish = None;
+        let mut semver = None;
+        for part in fragment.split("::").filter(|p| !p.is_empty()) {
+            if let Some(range) = part.strip_prefix("semver:") {
+                semver = Some(range);
+            } else if !part.contains(':') {
+                committish = Some(part);
+            }
+        }
+
+        Self {
+            url,
+            committish,
+            semver,
+        }
+    }
+
+    /**
+        Returns the owner and repository, if this is a GitHub repository.
+    */
+    pub fn github_repository(&self) -> Option<(String, String)> {
+        if is_github_shorthand(self.url) {
+            let (owner, repository) = self.url.split_once('/')?;
+            return Some((owner.to_string(), repository.to_string()));
+        }
+        parse_github_repository(self.url)
+    }
+}
+
+fn is_github_shorthand(spec: &str) -> bool {
+    // Scoped package names are not valid specs, so `owner/repo` is unambiguous
+    let url = spec.split_once('#').map_or(spec, |(url, _)| url);
+    url.split_once('/').is_some_and(|(owner, repository)| {
+        !owner.is_empty()
+            && !repository.is_empty()
+            && !owner.starts_with('@')
+            && !repository.contains('/')
+            && url
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | '/'))
+    })
+}
@@ -0,0 +1,110 @@
+use std::collections::HashMap;
+
+use deputy_versioning::{NpmVersionReq, Version, Versioned};
+
+/**
+    Parses a dependency spec as an npm version range.
+
+    Note that specs may also be dist-tags, such as `latest`, see `parse_tag`.
+*/
+pub fn parse_version_req(spec: &str) -> Option<NpmVersionReq> {
+    spec.trim().parse_npm_version_req().ok()
+}
+
+/**
+    Parses a dependency spec as a dist-tag, such as `latest` or `next`.
+
+    Same as npm, anything that is not a valid version range
+    is a tag, as long as it is a valid URL path component.
+*/
+pub fn parse_tag(spec: &str) -> Option<&str> {
+    let spec = spec.trim();
+    let is_tag = !spec.is_empty()
+        && parse_version_req(spec).is_none()
+        && spec
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | '~'));
+    is_tag.then_some(spec)
+}
+
+/**
+    Splits a dependency spec into everything before the version that is
+    currently being typed, including its operator, and that partial version.
+
+    For example, `>=1.0.0 <2.1` is split into `>=1.0.0 <` and `2.1`,
+    so that only the last version is completed and everything else is kept.
+*/
+pub fn split_partial_version(spec: &str) -> (&str, &str) {
+    let start = spec
+        .rfind(|c: char| c.is_whitespace() || c == '|')
+        .map_or(0, |index| index + 1);
+    let (head, last) = spec.split_at(start);
+
+    let operator_len = last.len()
+        - last
+            .trim_start_matches(['<', '>', '=', '~', '^', 'v'])
+            .len();
+    let (operator, partial) = last.split_at(operator_len);
+
+    (&spec[..head.len() + operator.len()], partial)
+}
+
+/**
+    Finds the version that a range or tag resolves to without a lockfile.
+
+    For ranges, npm prefers the `latest` tag if it matches, and otherwise the
+    newest matching version that is not deprecated, unless all of them are.
+*/
+pub fn find_resolved_version<'a, V: Versioned>(
+    spec: &str,
+    dist_tags: &HashMap<String, String>,
+    versions: &'a HashMap<String, V>,
+) -> Option<(Version, &'a V)> {
+    let tagged = |tag: &str| {
+        let raw = dist_tags.get(tag)?;
+        Some((raw.parse_version().ok()?, versions.get(raw)?))
+    };
+
+    let Some(version_req) = parse_version_req(spec) else {
+        return tagged(parse_tag(spec)?);
+    };
+    let latest = tagged("latest").filter(|(version, _)| version_req.matches(version));
+    if latest.is_some() {
+        return latest;
+    }
+
+    let matching = versions
+        .values()
+        .filter_map(|v| v.parse_version().ok().map(|version| (version, v)))
+        .filter(|(version, _)| version_req.matches(version))
+        .collect::<Vec<_>>();
+    let newest = |include_deprecated: bool| {
+        matching
+            .iter()
+            .filter(|(_, v)| include_deprecated || !v.deprecated())
+            .max_by(|(a, _), (b, _)| a.cmp(b))
+            .cloned()
+    };
+    newest(false).or_else(|| newest(true))
+}
+
+/**
+    Builds a spec for the given version that replaces an existing spec, keeping its
+    operator if it is a single comparator, such as `~1.2.3` or `>=1.2.3`.
+
+    Compound ranges such as `>=1.0.0 <2.0.0` or `1.x || 2.x` can not be updated
+    by replacing a single version in them, so they are replaced with a caret range.
+*/
+pub fn replacement_spec(spec: &str, version: &Version) -> String {
+    let spec = spec.trim();
+    let is_single = !spec.contains("||") && spec.split_whitespace().count() == 1;
+    let operator_len = spec.len() - spec.trim_start_matches(['<', '>', '=', '~', '^']).len();
+    let (operator, current) = spec.split_at(operator_len);
+    match operator {
+        "^" | "~" | ">=" | ">" if is_single => format!("{operator}{version}"),
+        "" | "=" if is_single && current.trim_start_matches('v').parse_version().is_ok() => {
+            format!("{operator}{version}")
+        }
+        _ => format!("^{version}"),
+    }
+}
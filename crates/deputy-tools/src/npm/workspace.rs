@@ -0,0 +1,142 @@
+use std::{collections::HashSet, fs, path::Path};
+
+use glob::Pattern;
+use serde_json::Value;
+
+const PACKAGE_JSON: &str = "package.json";
+const PNPM_WORKSPACE: &str = "pnpm-workspace.yaml";
+
+/**
+    A package in an npm, yarn, pnpm or bun workspace.
+*/
+#[derive(Debug, Clone)]
+pub struct NpmWorkspacePackage {
+    pub name: String,
+    pub version: Option<String>,
+}
+
+/**
+    A workspace of JavaScript packages, found either using the `workspaces`
+    field in a root `package.json`, or using a `pnpm-workspace.yaml` file.
+
+    All package manifests are read from disk.
+*/
+#[derive(Debug, Clone)]
+pub struct NpmWorkspace {
+    pub packages: Vec<NpmWorkspacePackage>,
+}
+
+impl NpmWorkspace {
+    /**
+        Discovers the workspace that the package in the given directory belongs
+        to, by searching upwards for the closest directory with workspace globs.
+    */
+    pub fn discover(package_dir: &Path) -> Option<Self> {
+        package_dir.ancestors().find_map(|dir| {
+            let globs = read_workspace_globs(dir)?;
+            Some(Self::from_root(dir, &globs))
+        })
+    }
+
+    fn from_root(root_dir: &Path, globs: &[String]) -> Self {
+        let mut packages = Vec::new();
+        packages.extend(read_package(root_dir));
+
+        let (excluded, included): (Vec<_>, Vec<_>) =
+            globs.iter().partition(|glob| glob.starts_with('!'));
+        let excluded = excluded
+            .into_iter()
+            .filter_map(|glob| Pattern::new(glob.trim_start_matches('!')).ok())
+            .collect::<Vec<_>>();
+
+        let root_escaped = Pattern::escape(&root_dir.to_string_lossy());
+        let mut visited = HashSet::new();
+        for package_glob in included {
+            let Ok(paths) = glob::glob(&format!("{root_escaped}/{package_glob}")) else {
+                continue;
+            };
+            for dir in paths.flatten() {
+                let Ok(relative) = dir.strip_prefix(root_dir) else {
+                    continue;
+                };
+                let relative = relative.to_string_lossy().replace('\\', "/");
+                if relative.is_empty()
+                    || relative.split('/').any(|part| part == "node_modules")
+                    || excluded.iter().any(|p| p.matches(&relative))
+                    || !visited.insert(relative)
+                {
+                    continue;
+                }
+                packages.extend(read_package(&dir));
+            }
+        }
+
+        Self { packages }
+    }
+
+    pub fn find_package(&self, name: &str) -> Option<&NpmWorkspacePackage> {
+        self.packages.iter().find(|package| package.name == name)
+    }
+}
+
+fn read_package(dir: &Path) -> Option<NpmWorkspacePackage> {
+    let contents = fs::read_to_string(dir.join(PACKAGE_JSON)).ok()?;
+    let json = serde_json::from_str::<Value>(&contents).ok()?;
+    Some(NpmWorkspacePackage {
+        name: json.get("name")?.as_str()?.to_string(),
+        version: json
+            .get("version")
+            .and_then(Value::as_str)
+            .map(ToString::to_string),
+    })
+}
+
+fn read_workspace_globs(dir: &Path) -> Option<Vec<String>> {
+    if let Ok(contents) = fs::read_to_string(dir.join(PNPM_WORKSPACE)) {
+        return Some(parse_pnpm_workspace_globs(&contents));
+    }
+
+    // Both `"workspaces": [...]` and `"workspaces": { "packages": [...] }` are used
+    let contents = fs::read_to_string(dir.join(PACKAGE_JSON)).ok()?;
+    let json = serde_json::from_str::<Value>(&contents).ok()?;
+    let workspaces = json.get("workspaces")?;
+    let globs = workspaces
+        .as_array()
+        .or_else(|| workspaces.get("packages")?.as_array())?;
+    Some(
+        globs
+            .iter()
+            .filter_map(Value::as_str)
+            .map(ToString::to_string)
+            .collect(),
+    )
+}
+
+/*
+    Stored as:
+
+    ```yaml
+    packages:
+      - 'packages/core'
+      - "apps/web"
+      - '!packages/internal'
+    ```
+*/
+fn parse_pnpm_workspace_globs(contents: &str) -> Vec<String> {
+    let mut globs = Vec::new();
+    let mut in_packages = false;
+    for line in contents.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        if !line.starts_with([' ', '\t', '-']) {
+            in_packages = trimmed.trim_end_matches(':') == "packages";
+            continue;
+        }
+        if let Some(glob) = trimmed.strip_prefix('-').filter(|_| in_packages) {
+            globs.push(glob.trim().trim_matches(['\'', '"']).to_string());
+        }
+    }
+    globs
+}
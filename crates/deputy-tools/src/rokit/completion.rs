@@ -9,11 +9,18 @@ use async_language_server::{
 };
 use tracing::debug;
 
-use deputy_parser::rokit;
-use deputy_versioning::Versioned;
+use deputy_parser::rokit::{self, RokitIncompleteDependency};
+use deputy_versioning::{Version, Versioned};
+
+use crate::shared::{
+    ENTRY_VERSION_PLACEHOLDER, EntryCompletion, EntryMetadata, entry_completion_response,
+    fetch_entry_versions, is_entry_version_missing, resolve_entry_version,
+};
 
 use super::Clients;
-use super::constants::{top_rokit_tool_authors_prefixed, top_rokit_tool_names_prefixed};
+use super::constants::{
+    top_rokit_tool_authors_matching, top_rokit_tool_names_matching, top_rokit_tools_matching,
+};
 
 const MAXIMUM_TOOLS_SHOWN: usize = 64;
 
@@ -69,12 +76,92 @@ pub async fn get_rokit_completions(
     Ok(None)
 }
 
+/**
+    Completes a tool that only has its name typed out so far, inserting the
+    whole entry, using the tool name as the alias and the latest release.
+*/
+pub async fn get_rokit_entry_completions(
+    clients: &Clients,
+    doc: &Document,
+    dep: RokitIncompleteDependency<'_>,
+) -> ServerResult<Option<CompletionResponse>> {
+    let name = dep.text(doc);
+    let range = ts_range_to_lsp_range(dep.range());
+
+    debug!("Completing entry: {dep:?}");
+
+    let tools = top_rokit_tools_matching(name.as_str(), MAXIMUM_TOOLS_SHOWN);
+    let versions = fetch_entry_versions(&tools, |tool| {
+        latest_tool_version(clients, &tool.author, &tool.name)
+    })
+    .await;
+
+    let items = tools
+        .into_iter()
+        .zip(versions)
+        .enumerate()
+        .map(|(index, (tool, version))| {
+            let entry = EntryCompletion {
+                label: tool.name.to_string(),
+                label_detail: Some(format!(" ({}/{})", tool.author, tool.name)),
+                detail: None,
+                snippet: format!(
+                    "{} = \"{}/{}@{ENTRY_VERSION_PLACEHOLDER}\"",
+                    tool.name, tool.author, tool.name
+                ),
+                range,
+                sort_text: Some(format!("{index:0>5}")),
+                version,
+            };
+            entry.into_completion_item(
+                doc.url().clone(),
+                EntryMetadata::Rokit {
+                    owner: tool.author.to_string(),
+                    repository: tool.name.to_string(),
+                },
+            )
+        })
+        .collect::<Vec<_>>();
+    Ok(Some(entry_completion_response(items)))
+}
+
+/**
+    Adds the latest release of a tool to a dependency entry
+    that did not get its version filled in with the list.
+*/
+pub async fn resolve_rokit_entry(
+    clients: &Clients,
+    mut item: CompletionItem,
+    owner: &str,
+    repository: &str,
+) -> ServerResult<CompletionItem> {
+    if !is_entry_version_missing(&item) {
+        return Ok(item);
+    }
+    if let Some(latest) = latest_tool_version(clients, owner, repository).await {
+        resolve_entry_version(&mut item, &latest);
+    }
+    Ok(item)
+}
+
+async fn latest_tool_version(clients: &Clients, owner: &str, repository: &str) -> Option<String> {
+    let releases = clients
+        .github
+        .get_repository_releases(owner, repository)
+        .await
+        .ok()?;
+    let latest = Version::new(0, 0, 0).extract_latest_version(releases)?;
+    Some(latest.item_version.to_string())
+}
+
 fn complete_owner(author: &str, range: Range) -> ServerResult<Option<CompletionResponse>> {
-    let items = top_rokit_tool_authors_prefixed(author, MAXIMUM_TOOLS_SHOWN)
+    let items = top_rokit_tool_authors_matching(author, MAXIMUM_TOOLS_SHOWN)
         .into_iter()
-        .map(|item| CompletionItem {
+        .enumerate()
+        .map(|(index, item)| CompletionItem {
             label: item.name.to_string(),
             kind: Some(CompletionItemKind::ENUM),
+            sort_text: Some(format!("{index:0>5}")),
             commit_characters: Some(vec![String::from("/")]),
             text_edit: Some(CompletionTextEdit::Edit(TextEdit {
                 new_text: item.name.to_string(),
@@ -91,11 +178,13 @@ fn complete_repository(
     name: &str,
     range: Range,
 ) -> ServerResult<Option<CompletionResponse>> {
-    let items = top_rokit_tool_names_prefixed(author, name, MAXIMUM_TOOLS_SHOWN)
+    let items = top_rokit_tool_names_matching(author, name, MAXIMUM_TOOLS_SHOWN)
         .into_iter()
-        .map(|item| CompletionItem {
+        .enumerate()
+        .map(|(index, item)| CompletionItem {
             label: item.name.to_string(),
             kind: Some(CompletionItemKind::ENUM_MEMBER),
+            sort_text: Some(format!("{index:0>5}")),
             commit_characters: Some(vec![String::from("@")]),
             text_edit: Some(CompletionTextEdit::Edit(TextEdit {
                 new_text: item.name.to_string(),
@@ -1,6 +1,6 @@
 use std::sync::{Arc, OnceLock};
 
-use crate::shared::CompletionMap;
+use crate::shared::{CompletionMap, Popularity, rank_matches};
 
 /**
     A statically stored author of Rokit-compatible tools.
@@ -25,6 +25,13 @@ impl AsRef<str> for RokitToolAuthor {
     }
 }
 
+impl Popularity for RokitToolAuthor {
+    fn popularity(&self) -> u64 {
+        // No download counts are available, so rank authors with more tools higher
+        self.tools.len() as u64
+    }
+}
+
 /**
     A statically stored Rokit-compatible tool.
 
@@ -57,7 +64,7 @@ impl AsRef<str> for RokitToolName {
 static TOP_TOOLS_ROKIT: &str = include_str!("../../assets/top-rokit-tools.txt");
 static TOP_TOOLS: OnceLock<CompletionMap<RokitToolAuthor>> = OnceLock::new();
 
-pub fn top_rokit_tool_authors_prefixed(prefix: &str, limit: usize) -> Vec<&RokitToolAuthor> {
+pub fn top_rokit_tool_authors_matching(query: &str, limit: usize) -> Vec<&RokitToolAuthor> {
     let top = TOP_TOOLS.get_or_init(|| {
         TOP_TOOLS_ROKIT
             .lines()
@@ -80,19 +87,30 @@ pub fn top_rokit_tool_authors_prefixed(prefix: &str, limit: usize) -> Vec<&Rokit
             .collect::<CompletionMap<_>>()
     });
 
-    top.iter(prefix).take(limit).collect()
+    top.search(query, limit)
 }
 
-pub fn top_rokit_tool_names_prefixed<'a>(
+pub fn top_rokit_tool_names_matching<'a>(
     author: &'a str,
-    prefix: &'a str,
+    query: &'a str,
     limit: usize,
 ) -> Vec<&'a RokitToolName> {
-    top_rokit_tool_authors_prefixed(author, 1)
-        .first()
+    let tools = top_rokit_tool_authors_matching(author, 1)
         .into_iter()
         .filter(|a| a.name.eq_ignore_ascii_case(author))
-        .flat_map(|a| a.tools.iter().filter(|t| t.as_ref().starts_with(prefix)))
-        .take(limit)
-        .collect::<Vec<_>>()
+        .flat_map(|a| a.tools.iter());
+
+    let mut tools = rank_matches(tools, query, |t| t.as_ref(), |_| 0);
+    tools.truncate(limit);
+    tools
+}
+
+pub fn top_rokit_tools_matching(query: &str, limit: usize) -> Vec<&RokitToolName> {
+    let tools = top_rokit_tool_authors_matching("", usize::MAX)
+        .into_iter()
+        .flat_map(|a| a.tools.iter());
+
+    let mut tools = rank_matches(tools, query, |t| t.as_ref(), |_| 0);
+    tools.truncate(limit);
+    tools
 }
@@ -8,8 +8,9 @@ use async_language_server::{
 };
 
 use deputy_parser::rokit;
+use deputy_versioning::{Version, Versioned};
 
-use crate::shared::MarkdownBuilder;
+use crate::shared::{MarkdownBuilder, ReleaseNotes};
 
 use super::Clients;
 
@@ -46,6 +47,12 @@ pub async fn get_rokit_hover(
         }
     }
 
+    // Add a summary of what changed since the current version
+    if let Some(notes) = get_rokit_release_notes(clients, doc, node).await {
+        md.br();
+        notes.add_hover_section(&mut md);
+    }
+
     // Add link to the repository and latest release
     md.br();
     md.h3("Links");
@@ -66,3 +73,34 @@ pub async fn get_rokit_hover(
         }),
     }))
 }
+
+/**
+    Gets cached release notes for all releases between
+    the specified version and the latest release.
+*/
+pub async fn get_rokit_release_notes(
+    clients: &Clients,
+    doc: &Document,
+    node: Node<'_>,
+) -> Option<ReleaseNotes> {
+    let dep = rokit::parse_dependency(node)?;
+    let (Some(owner), Some(repository), Some(version)) = dep.spec_ranges(doc).text(doc) else {
+        return None;
+    };
+    let version_current = version.trim_start_matches('v').parse::<Version>().ok()?;
+
+    let releases = clients
+        .github
+        .get_repository_releases(owner, repository)
+        .await
+        .ok()?;
+    let latest = version_current.extract_latest_version(releases)?;
+
+    ReleaseNotes::cached(
+        clients,
+        repository,
+        &format!("https://github.com/{owner}/{repository}"),
+        &version_current,
+        &latest.item_version,
+    )
+}
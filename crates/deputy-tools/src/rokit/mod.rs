@@ -2,7 +2,9 @@ use futures::future::try_join_all;
 use tracing::debug;
 
 use async_language_server::{
-    lsp_types::{CompletionResponse, Diagnostic, DocumentDiagnosticParams, Hover, Position},
+    lsp_types::{
+        CompletionItem, CompletionResponse, Diagnostic, DocumentDiagnosticParams, Hover, Position,
+    },
     server::{Document, ServerResult},
     tree_sitter::Node,
 };
@@ -10,14 +12,16 @@ use async_language_server::{
 use deputy_clients::Clients;
 use deputy_parser::rokit;
 
+use crate::shared::ReleaseNotes;
+
 mod completion;
 mod constants;
 mod diagnostics;
 mod hover;
 
-use completion::get_rokit_completions;
+use completion::{get_rokit_completions, get_rokit_entry_completions, resolve_rokit_entry};
 use diagnostics::get_rokit_diagnostics;
-use hover::get_rokit_hover;
+use hover::{get_rokit_hover, get_rokit_release_notes};
 
 #[derive(Debug, Clone)]
 pub struct Rokit {
@@ -44,6 +48,15 @@ impl Rokit {
         get_rokit_hover(&self.clients, doc, dep).await
     }
 
+    pub(super) async fn release_notes(
+        &self,
+        doc: &Document,
+        pos: Position,
+    ) -> Option<ReleaseNotes> {
+        let dep = rokit::find_dependency_at(doc, pos)?;
+        get_rokit_release_notes(&self.clients, doc, dep).await
+    }
+
     pub(super) async fn completion(
         &self,
         doc: &Document,
@@ -51,7 +64,10 @@ impl Rokit {
         _node: Node<'_>,
     ) -> ServerResult<Option<CompletionResponse>> {
         let Some(dep) = rokit::find_dependency_at(doc, pos) else {
-            return Ok(None);
+            return match rokit::find_incomplete_dependency_at(doc, pos) {
+                Some(dep) => get_rokit_entry_completions(&self.clients, doc, dep).await,
+                None => Ok(None),
+            };
         };
 
         debug!("Fetching completions: {dep:?}");
@@ -59,6 +75,15 @@ impl Rokit {
         get_rokit_completions(&self.clients, doc, pos, dep).await
     }
 
+    pub(super) async fn completion_resolve(
+        &self,
+        item: CompletionItem,
+        owner: &str,
+        repository: &str,
+    ) -> ServerResult<CompletionItem> {
+        resolve_rokit_entry(&self.clients, item, owner, repository).await
+    }
+
     pub(super) async fn diagnostics(
         &self,
         doc: &Document,
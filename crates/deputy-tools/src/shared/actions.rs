@@ -7,6 +7,8 @@ use async_language_server::lsp_types::{
     WorkspaceEdit,
 };
 
+const OPERATOR_CHARS: [char; 5] = ['=', '>', '<', '~', '^'];
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "kind")]
 pub enum CodeActionMetadata {
@@ -17,6 +19,35 @@ pub enum CodeActionMetadata {
         version_current: String,
         version_latest: String,
     },
+    RaiseMinimumVersion {
+        edit_range: Range,
+        source_uri: Url,
+        source_text: String,
+        version_minimum: String,
+    },
+    UnyankedVersion {
+        edit_range: Range,
+        source_uri: Url,
+        source_text: String,
+        version_unyanked: String,
+    },
+    RemoveDependency {
+        edit_range: Range,
+        source_uri: Url,
+        name: String,
+    },
+    InheritWorkspaceDependency {
+        edit_range: Range,
+        source_uri: Url,
+        name: String,
+        new_text: String,
+    },
+    AddDependency {
+        edit_range: Range,
+        source_uri: Url,
+        name: String,
+        new_text: String,
+    },
 }
 
 impl CodeActionMetadata {
@@ -29,30 +60,196 @@ impl CodeActionMetadata {
                 version_current,
                 version_latest,
                 ..
-            } => {
-                let replaced = source_text.replace(&version_current, &version_latest);
-                let text_edit = TextEdit {
-                    new_text: if replaced == source_text {
-                        // failed to replace substring, just insert latest version
-                        version_latest
-                    } else {
-                        // means we replaced substring like ^x0.y0.z0 with ^x1.y1.z1
-                        replaced
-                    },
+            } => quick_fix(
+                String::from("Update to latest version"),
+                source_uri,
+                replace_version(edit_range, &source_text, &version_current, version_latest),
+                diag,
+            ),
+            Self::RaiseMinimumVersion {
+                edit_range,
+                source_uri,
+                source_text,
+                version_minimum,
+            } => quick_fix(
+                format!("Raise minimum version to `{version_minimum}`"),
+                source_uri,
+                TextEdit {
+                    new_text: replace_minimum_version(&source_text, &version_minimum),
+                    range: edit_range,
+                },
+                diag,
+            ),
+            Self::UnyankedVersion {
+                edit_range,
+                source_uri,
+                source_text,
+                version_unyanked,
+            } => quick_fix(
+                format!("Use non-yanked version `{version_unyanked}`"),
+                source_uri,
+                TextEdit {
+                    new_text: replace_minimum_version(&source_text, &version_unyanked),
+                    range: edit_range,
+                },
+                diag,
+            ),
+            Self::RemoveDependency {
+                edit_range,
+                source_uri,
+                name,
+            } => quick_fix(
+                format!("Remove unused dependency `{name}`"),
+                source_uri,
+                TextEdit {
+                    new_text: String::new(),
+                    range: edit_range,
+                },
+                diag,
+            ),
+            Self::InheritWorkspaceDependency {
+                edit_range,
+                source_uri,
+                name,
+                new_text,
+            } => quick_fix(
+                format!("Inherit `{name}` from the workspace"),
+                source_uri,
+                TextEdit {
+                    new_text,
+                    range: edit_range,
+                },
+                diag,
+            ),
+            Self::AddDependency {
+                edit_range,
+                source_uri,
+                name,
+                new_text,
+            } => quick_fix(
+                format!("Add missing dependency `{name}`"),
+                source_uri,
+                TextEdit {
+                    new_text,
                     range: edit_range,
-                };
-                CodeActionOrCommand::CodeAction(CodeAction {
-                    title: String::from("Update to latest version"),
-                    kind: Some(CodeActionKind::QUICKFIX),
-                    edit: Some(WorkspaceEdit {
-                        changes: Some(HashMap::from([(source_uri, vec![text_edit])])),
-                        ..Default::default()
-                    }),
-                    diagnostics: Some(vec![diag]),
-                    is_preferred: Some(true),
-                    ..Default::default()
-                })
-            }
+                },
+                diag,
+            ),
         }
     }
 }
+
+fn replace_version(
+    edit_range: Range,
+    source_text: &str,
+    version_current: &str,
+    version_new: String,
+) -> TextEdit {
+    let replaced = source_text.replace(version_current, &version_new);
+    TextEdit {
+        new_text: if replaced == source_text {
+            // failed to replace substring, just insert new version
+            version_new
+        } else {
+            // means we replaced substring like ^x0.y0.z0 with ^x1.y1.z1
+            replaced
+        },
+        range: edit_range,
+    }
+}
+
+/**
+    Replaces the version of the comparator that sets the lower bound of a
+    version requirement, such as `~1.2` or the `>=1.2` in `>=1.2, <1.5`,
+    keeping its operator along with any other comparators as they are.
+
+    An exclusive lower bound such as `>1.2` becomes inclusive, since
+    it would otherwise exclude the new version it is raised to.
+*/
+fn replace_minimum_version(source_text: &str, version_new: &str) -> String {
+    let mut offset = 0;
+    for comparator in source_text.split(',') {
+        let comparator_start = offset;
+        offset += comparator.len() + 1;
+
+        let trimmed = comparator.trim_start();
+        let op_start = comparator_start + comparator.len() - trimmed.len();
+        let op = &trimmed[..trimmed.len() - trimmed.trim_start_matches(OPERATOR_CHARS).len()];
+        if op.starts_with('<') {
+            continue;
+        }
+
+        let version = trimmed[op.len()..].trim();
+        if version.is_empty() {
+            continue;
+        }
+        let version_start = op_start + trimmed.find(version).unwrap_or(op.len());
+        let version_end = version_start + version.len();
+
+        let op_new = if op == ">" { ">=" } else { op };
+        return format!(
+            "{}{op_new}{}{version_new}{}",
+            &source_text[..op_start],
+            &source_text[op_start + op.len()..version_start],
+            &source_text[version_end..],
+        );
+    }
+    version_new.to_string()
+}
+
+fn quick_fix(
+    title: String,
+    source_uri: Url,
+    text_edit: TextEdit,
+    diag: Diagnostic,
+) -> CodeActionOrCommand {
+    CodeActionOrCommand::CodeAction(CodeAction {
+        title,
+        kind: Some(CodeActionKind::QUICKFIX),
+        edit: Some(WorkspaceEdit {
+            changes: Some(HashMap::from([(source_uri, vec![text_edit])])),
+            ..Default::default()
+        }),
+        diagnostics: Some(vec![diag]),
+        is_preferred: Some(true),
+        ..Default::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::replace_minimum_version;
+
+    #[test]
+    fn minimum_version_keeps_operator() {
+        assert_eq!(replace_minimum_version("1.2.0", "1.2.3"), "1.2.3");
+        assert_eq!(replace_minimum_version("^1.2.0", "1.2.3"), "^1.2.3");
+        assert_eq!(replace_minimum_version("~1.2.0", "1.2.3"), "~1.2.3");
+        assert_eq!(replace_minimum_version("=1.2.0", "1.2.3"), "=1.2.3");
+        assert_eq!(replace_minimum_version(">= 1.2.0", "1.2.3"), ">= 1.2.3");
+        assert_eq!(replace_minimum_version(">1.2.0", "1.2.3"), ">=1.2.3");
+    }
+
+    #[test]
+    fn minimum_version_keeps_other_comparators() {
+        assert_eq!(
+            replace_minimum_version(">=1.2, <1.5", "1.2.3"),
+            ">=1.2.3, <1.5"
+        );
+        assert_eq!(
+            replace_minimum_version("<1.5, >=1.2", "1.2.3"),
+            "<1.5, >=1.2.3"
+        );
+        assert_eq!(
+            replace_minimum_version("^1.2, <1.2.8", "1.2.3"),
+            "^1.2.3, <1.2.8"
+        );
+    }
+
+    #[test]
+    fn minimum_version_partial() {
+        assert_eq!(replace_minimum_version("1", "1.0.5"), "1.0.5");
+        assert_eq!(replace_minimum_version("~1.2", "1.2.3"), "~1.2.3");
+        assert_eq!(replace_minimum_version("=1.2", "1.2.4"), "=1.2.4");
+    }
+}
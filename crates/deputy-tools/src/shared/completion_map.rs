@@ -3,7 +3,14 @@ use std::{
     sync::Arc,
 };
 
-use super::filter_starts_with;
+use super::{filter_starts_with, rank_matches};
+
+/**
+    Popularity of a completion item, used to rank items that match equally well.
+*/
+pub trait Popularity {
+    fn popularity(&self) -> u64;
+}
 
 /**
     An append-only map for completion purposes.
@@ -69,6 +76,33 @@ impl<T: Clone + AsRef<str> + 'static> CompletionMap<T> {
             .iter()
             .filter(move |item| filter_starts_with(item.as_ref(), prefix.as_str()))
     }
+
+    /**
+        Searches for items matching the given query, ranked by how well they
+        match and then by popularity, returning at most `limit` items.
+
+        Prefix matches are always ranked first, so these are found using the fast
+        prefix path, and all items are only scanned for fuzzy matches if needed.
+    */
+    pub fn search(&self, query: impl AsRef<str>, limit: usize) -> Vec<&T>
+    where
+        T: Popularity,
+    {
+        let query = query.as_ref().trim();
+
+        let mut matches = rank_matches(self.iter(query), query, |t| t.as_ref(), |t| t.popularity());
+        if matches.len() < limit && !query.is_empty() {
+            matches = rank_matches(
+                self.unprefixed.iter(),
+                query,
+                |t| t.as_ref(),
+                |t| t.popularity(),
+            );
+        }
+
+        matches.truncate(limit);
+        matches
+    }
 }
 
 impl<T: Clone + AsRef<str> + 'static> FromIterator<T> for CompletionMap<T> {
@@ -0,0 +1,162 @@
+use futures::future::join_all;
+use serde::{Deserialize, Serialize};
+
+use async_language_server::lsp_types::{
+    CompletionItem, CompletionItemKind, CompletionItemLabelDetails, CompletionList,
+    CompletionResponse, CompletionTextEdit, InsertTextFormat, Range, TextEdit, Url,
+};
+
+use super::ResolveContext;
+
+/**
+    Tab stop for the version in a dependency entry snippet.
+
+    Replaced with the latest version when the completion list is built, for the
+    first `MAXIMUM_ENTRY_VERSIONS_FETCHED` entries - any other entries insert the
+    placeholder text and select it, so that it is obvious that a version is missing.
+*/
+pub const ENTRY_VERSION_PLACEHOLDER: &str = "${1:version}";
+
+/**
+    Number of entries, from the top of the completion list,
+    that get the latest version looked up and filled in.
+*/
+pub const MAXIMUM_ENTRY_VERSIONS_FETCHED: usize = 8;
+
+/**
+    Metadata for completion items that insert a whole dependency entry,
+    containing what is needed to look up the latest version during resolve.
+*/
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind")]
+pub enum EntryMetadata {
+    Cargo {
+        name: String,
+    },
+    Npm {
+        name: String,
+    },
+    Rokit {
+        owner: String,
+        repository: String,
+    },
+    Wally {
+        index_url: String,
+        scope: String,
+        name: String,
+    },
+}
+
+/**
+    A completion item that inserts a whole dependency entry as a snippet.
+
+    The snippet text should contain `ENTRY_VERSION_PLACEHOLDER` where the
+    version goes, which is replaced with the given version, if any.
+*/
+pub struct EntryCompletion {
+    pub label: String,
+    pub label_detail: Option<String>,
+    pub detail: Option<String>,
+    pub snippet: String,
+    pub range: Range,
+    pub sort_text: Option<String>,
+    pub version: Option<String>,
+}
+
+impl EntryCompletion {
+    pub fn into_completion_item(self, uri: Url, metadata: EntryMetadata) -> CompletionItem {
+        let (snippet, detail) = match self.version {
+            None => (self.snippet, self.detail),
+            Some(version) => (
+                self.snippet
+                    .replace(ENTRY_VERSION_PLACEHOLDER, &format!("${{1:{version}}}")),
+                Some(with_version_detail(self.detail, &version)),
+            ),
+        };
+        CompletionItem {
+            label: self.label,
+            label_details: self.label_detail.map(|detail| CompletionItemLabelDetails {
+                detail: Some(detail),
+                description: None,
+            }),
+            kind: Some(CompletionItemKind::SNIPPET),
+            detail,
+            sort_text: self.sort_text,
+            insert_text_format: Some(InsertTextFormat::SNIPPET),
+            text_edit: Some(CompletionTextEdit::Edit(TextEdit {
+                new_text: snippet,
+                range: self.range,
+            })),
+            data: Some(
+                ResolveContext {
+                    uri,
+                    value: metadata,
+                }
+                .into(),
+            ),
+            ..Default::default()
+        }
+    }
+}
+
+/**
+    Looks up versions for the first `MAXIMUM_ENTRY_VERSIONS_FETCHED` entries
+    concurrently, returning a version, or `None`, for every given entry.
+*/
+pub async fn fetch_entry_versions<'a, T, F, Fut>(entries: &'a [T], fetch: F) -> Vec<Option<String>>
+where
+    F: Fn(&'a T) -> Fut,
+    Fut: Future<Output = Option<String>>,
+{
+    let mut versions = join_all(
+        entries
+            .iter()
+            .take(MAXIMUM_ENTRY_VERSIONS_FETCHED)
+            .map(fetch),
+    )
+    .await;
+    versions.resize(entries.len(), None);
+    versions
+}
+
+/**
+    Creates a completion response for dependency entries.
+
+    The response is marked as incomplete if any entry is missing its version,
+    so that the list is built again as more of the name is typed out, and
+    the entries that end up at the top of it get their versions filled in.
+*/
+pub fn entry_completion_response(items: Vec<CompletionItem>) -> CompletionResponse {
+    let is_incomplete = items.iter().any(is_entry_version_missing);
+    CompletionResponse::List(CompletionList {
+        is_incomplete,
+        items,
+    })
+}
+
+/**
+    Checks if the snippet of a dependency entry still has the version placeholder.
+*/
+pub fn is_entry_version_missing(item: &CompletionItem) -> bool {
+    matches!(
+        &item.text_edit,
+        Some(CompletionTextEdit::Edit(edit)) if edit.new_text.contains(ENTRY_VERSION_PLACEHOLDER)
+    )
+}
+
+/**
+    Adds the latest version to the detail of a dependency entry during resolve.
+
+    Clients are only required to accept changes to the detail and documentation
+    of a completion item during resolve, so the snippet itself is left as-is.
+*/
+pub fn resolve_entry_version(item: &mut CompletionItem, version: &str) {
+    item.detail = Some(with_version_detail(item.detail.take(), version));
+}
+
+fn with_version_detail(detail: Option<String>, version: &str) -> String {
+    match detail {
+        Some(detail) => format!("{version} - {detail}"),
+        None => version.to_string(),
+    }
+}
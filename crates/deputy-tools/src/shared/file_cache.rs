@@ -0,0 +1,69 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::SystemTime,
+};
+
+type CachedFiles<T> = BTreeMap<(PathBuf, String), (SystemTime, Arc<T>)>;
+
+/**
+    Cache for files on disk that are parsed into some value, keyed by the
+    path of the file along with a variant, for files that may be parsed
+    in more than one way, such as a lockfile for a specific workspace package.
+
+    Parsed values are reused for as long as the modification time of the file
+    stays the same, which only requires a cheap metadata lookup per read.
+*/
+#[derive(Debug)]
+pub struct FileCache<T> {
+    entries: Mutex<CachedFiles<T>>,
+}
+
+impl<T> FileCache<T> {
+    pub const fn new() -> Self {
+        Self {
+            entries: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    /**
+        Returns the parsed value for the file at the given path, reading and
+        parsing it again only if the file has been modified since the last read.
+
+        Returns `None` if the file does not exist or could not be read.
+    */
+    pub fn get_or_parse(
+        &self,
+        path: &Path,
+        variant: &str,
+        parse: impl FnOnce(&str) -> T,
+    ) -> Option<Arc<T>> {
+        let modified = fs::metadata(path).and_then(|m| m.modified()).ok()?;
+        let key = (path.to_path_buf(), variant.to_string());
+        if let Some((_, cached)) = self
+            .entries
+            .lock()
+            .unwrap()
+            .get(&key)
+            .filter(|(cached_modified, _)| *cached_modified == modified)
+        {
+            return Some(Arc::clone(cached));
+        }
+
+        let contents = fs::read_to_string(path).ok()?;
+        let parsed = Arc::new(parse(&contents));
+        self.entries
+            .lock()
+            .unwrap()
+            .insert(key, (modified, Arc::clone(&parsed)));
+        Some(parsed)
+    }
+}
+
+impl<T> Default for FileCache<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,28 @@
+/**
+    Formats a count, such as a number of downloads, in a short
+    and human-readable way - `1234567` becomes `1.2M` and so on.
+*/
+pub fn format_count(count: u64) -> String {
+    const UNITS: [(u64, &str); 3] = [(1_000_000_000, "B"), (1_000_000, "M"), (1_000, "K")];
+    for (size, suffix) in UNITS {
+        if count >= size {
+            let value = count as f64 / size as f64;
+            return if value >= 100.0 {
+                format!("{value:.0}{suffix}")
+            } else {
+                format!("{value:.1}{suffix}")
+            };
+        }
+    }
+    count.to_string()
+}
+
+/**
+    Formats an RFC 3339 timestamp, such as `2024-05-01T12:34:56.789+00:00`,
+    as only its date - `2024-05-01` - which is precise enough for display.
+*/
+pub fn format_date(timestamp: &str) -> &str {
+    timestamp
+        .split_once('T')
+        .map_or(timestamp, |(date, _)| date)
+}
@@ -1,5 +1,9 @@
 #![allow(dead_code)]
 
+use std::fmt;
+
+use deputy_versioning::VersionRange;
+
 pub struct MarkdownBuilder {
     lines: Vec<String>,
 }
@@ -38,6 +42,49 @@ impl MarkdownBuilder {
         self.p(format!("Version **{}**", version.to_string()));
     }
 
+    /**
+        Adds a section explaining which versions a requirement
+        matches, as well as its bounds and any notable versions.
+    */
+    pub fn version_range<T, R: fmt::Display>(&mut self, range: &VersionRange<T, R>) {
+        self.h3("Version Range");
+        self.p(format!("- Requirement `{}`", range.version_req));
+        match &range.maximum_version_exclusive {
+            Some(max) => self.p(format!("- Matches `>={}, <{max}`", range.minimum_version)),
+            None => self.p(format!("- Matches `>={}`", range.minimum_version)),
+        }
+
+        if range.matching_count == 0 {
+            self.p("- No published versions match this requirement");
+            return;
+        }
+        if let Some(highest) = &range.highest_version {
+            self.p(format!("- Highest matching version is `{highest}`"));
+        }
+
+        let mut notable = Vec::new();
+        for (count, what) in [
+            (range.yanked_count, "yanked"),
+            (range.deprecated_count, "deprecated"),
+            (range.prerelease_count, "prerelease"),
+        ] {
+            if count > 0 {
+                notable.push(format!("{count} {what}"));
+            }
+        }
+        if range.matching_count == 1 {
+            self.p("- Matches 1 published version");
+        } else {
+            self.p(format!(
+                "- Matches {} published versions",
+                range.matching_count
+            ));
+        }
+        if !notable.is_empty() {
+            self.extend_last(format!(" ({})", notable.join(", ")));
+        }
+    }
+
     pub fn extend_last(&mut self, suffix: impl AsRef<str>) {
         let last = self.lines.last_mut().unwrap();
         last.push_str(suffix.as_ref());
@@ -1,11 +1,25 @@
 mod actions;
 mod completion_map;
+mod entries;
+mod file_cache;
+mod format;
 mod markdown_builder;
+mod release_notes;
 mod resolve_context;
+mod static_index;
 mod strsim;
+#[cfg(test)]
+mod temp_dir;
 
 pub use actions::*;
 pub use completion_map::*;
+pub use entries::*;
+pub use file_cache::*;
+pub use format::*;
 pub use markdown_builder::*;
+pub use release_notes::*;
 pub use resolve_context::*;
+pub use static_index::*;
 pub use strsim::*;
+#[cfg(test)]
+pub use temp_dir::*;
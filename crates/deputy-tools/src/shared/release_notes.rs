@@ -0,0 +1,372 @@
+use std::{
+    collections::HashMap,
+    sync::{LazyLock, Mutex},
+};
+
+use serde_json::json;
+use tracing::debug;
+
+use async_language_server::lsp_types::{
+    CodeAction, CodeActionKind, CodeActionOrCommand, Command, Diagnostic,
+};
+
+use deputy_clients::Clients;
+use deputy_versioning::Version;
+
+use super::MarkdownBuilder;
+
+const CHANGELOG_PATHS: [&str; 3] = ["CHANGELOG.md", "Changelog.md", "changelog.md"];
+
+const HOVER_MAXIMUM_RELEASES: usize = 3;
+const HOVER_MAXIMUM_LINES: usize = 6;
+
+/*
+    Release notes by name, repository and version range, where `None`
+    means that the notes are still being fetched or that there were none
+*/
+static CACHED_NOTES: LazyLock<Mutex<HashMap<String, Option<ReleaseNotes>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/**
+    Release notes for a single version of a package,
+    either from a GitHub release or from a changelog.
+*/
+#[derive(Debug, Clone)]
+pub struct ReleaseNote {
+    pub version: Version,
+    pub title: String,
+    pub body: String,
+}
+
+/**
+    Release notes for all versions between a current and a latest version.
+*/
+#[derive(Debug, Clone)]
+pub struct ReleaseNotes {
+    pub name: String,
+    pub owner: String,
+    pub repository: String,
+    pub version_current: Version,
+    pub version_latest: Version,
+    pub notes: Vec<ReleaseNote>, // Newest first
+}
+
+impl ReleaseNotes {
+    /**
+        Gets release notes for all versions after `version_current`, up to and
+        including `version_latest`, if they have already been fetched.
+
+        Release notes may need many requests to GitHub, so they are never waited
+        for - if they have not been fetched yet, they are fetched in the background
+        and become available for any hover or code action that comes after.
+    */
+    pub fn cached(
+        clients: &Clients,
+        name: &str,
+        repository_url: &str,
+        version_current: &Version,
+        version_latest: &Version,
+    ) -> Option<Self> {
+        if version_latest <= version_current {
+            return None;
+        }
+
+        let key = format!("{name}@{repository_url}@{version_current}@{version_latest}");
+        let mut cached = CACHED_NOTES.lock().unwrap();
+        if let Some(notes) = cached.get(&key) {
+            return notes.clone();
+        }
+        cached.insert(key.clone(), None);
+        drop(cached);
+
+        let clients = clients.clone();
+        let name = name.to_string();
+        let repository_url = repository_url.to_string();
+        let version_current = version_current.clone();
+        let version_latest = version_latest.clone();
+        tokio::spawn(async move {
+            let notes = Self::fetch(
+                &clients,
+                &name,
+                &repository_url,
+                &version_current,
+                &version_latest,
+            )
+            .await;
+            CACHED_NOTES.lock().unwrap().insert(key, notes);
+        });
+
+        None
+    }
+
+    /**
+        Fetches release notes for all versions after `version_current`, up to
+        and including `version_latest`, from the given GitHub repository.
+
+        Uses GitHub releases if there are any in the range, otherwise
+        falls back to parsing a changelog file in the repository root.
+    */
+    async fn fetch(
+        clients: &Clients,
+        name: &str,
+        repository_url: &str,
+        version_current: &Version,
+        version_latest: &Version,
+    ) -> Option<Self> {
+        let (owner, repository) = parse_github_repository(repository_url)?;
+        let in_range = |version: &Version| {
+            version > version_current
+                && version <= version_latest
+                && (version.pre.is_empty() || version == version_latest)
+        };
+
+        debug!("Fetching release notes for {owner}/{repository}");
+
+        let mut notes = Vec::new();
+        if let Ok(releases) = clients
+            .github
+            .get_repository_releases(&owner, &repository)
+            .await
+        {
+            for release in releases.into_iter().filter(|r| !r.draft) {
+                let Some(version) =
+                    parse_tag_version(&release.tag_name, name).filter(|v| in_range(v))
+                else {
+                    continue;
+                };
+                notes.push(ReleaseNote {
+                    title: release.name.unwrap_or(release.tag_name),
+                    body: release.body.unwrap_or_default(),
+                    version,
+                });
+            }
+        }
+
+        if notes.is_empty() {
+            for path in CHANGELOG_PATHS {
+                let Ok(bytes) = clients
+                    .github
+                    .get_repository_file(&owner, &repository, path)
+                    .await
+                else {
+                    continue;
+                };
+                let changelog = String::from_utf8_lossy(&bytes);
+                notes.extend(
+                    parse_changelog(&changelog)
+                        .into_iter()
+                        .filter(|note| in_range(&note.version)),
+                );
+                break;
+            }
+        }
+
+        if notes.is_empty() {
+            return None;
+        }
+
+        notes.sort_by(|a, b| b.version.cmp(&a.version));
+        notes.dedup_by(|a, b| a.version == b.version);
+
+        Some(Self {
+            name: name.to_string(),
+            owner,
+            repository,
+            version_current: version_current.clone(),
+            version_latest: version_latest.clone(),
+            notes,
+        })
+    }
+
+    /**
+        Adds a short summary of the release notes to a hover.
+    */
+    pub fn add_hover_section(&self, md: &mut MarkdownBuilder) {
+        md.h3(format!(
+            "Changes since {} ({} releases)",
+            self.version_current,
+            self.notes.len()
+        ));
+        for note in self.notes.iter().take(HOVER_MAXIMUM_RELEASES) {
+            md.p(format!("**{}**", note.version));
+            let lines = note
+                .body
+                .lines()
+                .map(|line| line.trim().trim_start_matches('#').trim())
+                .filter(|line| !line.is_empty())
+                .collect::<Vec<_>>();
+            for line in lines.iter().take(HOVER_MAXIMUM_LINES) {
+                md.p(*line);
+            }
+            if lines.len() > HOVER_MAXIMUM_LINES {
+                md.p("...");
+            }
+            md.br();
+        }
+        if self.notes.len() > HOVER_MAXIMUM_RELEASES {
+            md.p(format!(
+                "And {} more releases.",
+                self.notes.len() - HOVER_MAXIMUM_RELEASES
+            ));
+        }
+        md.a(
+            "All Releases",
+            format!(
+                "https://github.com/{}/{}/releases",
+                self.owner, self.repository
+            ),
+        );
+    }
+
+    /**
+        Renders the full release notes as a standalone markdown document.
+    */
+    pub fn to_document(&self) -> String {
+        let mut md = MarkdownBuilder::new();
+        md.h1(format!(
+            "{} - changes from {} to {}",
+            self.name, self.version_current, self.version_latest
+        ));
+        for note in &self.notes {
+            md.br();
+            md.h2(&note.title);
+            if !note.title.contains(&note.version.to_string()) {
+                md.extend_last(format!(" ({})", note.version));
+            }
+            md.br();
+            md.p(note.body.trim());
+        }
+        md.build()
+    }
+
+    /**
+        Creates a code action that shows the full release notes in a new document.
+
+        This relies on the `deputy.showChanges` command being implemented by the editor
+        extension, since the language server protocol has no way to open documents, so
+        this must only be used for clients that list `showChanges` in their experimental
+        capabilities.
+    */
+    pub fn into_code_action(self, diag: Diagnostic) -> CodeActionOrCommand {
+        let title = format!("{} {}", self.name, self.version_latest);
+        CodeActionOrCommand::CodeAction(CodeAction {
+            title: String::from("Show changes"),
+            kind: Some(CodeActionKind::QUICKFIX),
+            diagnostics: Some(vec![diag]),
+            command: Some(Command {
+                title: String::from("Show changes"),
+                command: String::from("deputy.showChanges"),
+                arguments: Some(vec![json!({
+                    "title": title,
+                    "content": self.to_document(),
+                })]),
+            }),
+            ..Default::default()
+        })
+    }
+}
+
+/**
+    Parses the owner and repository out of a GitHub repository URL, in any of
+    the common formats such as `https://github.com/owner/repo`, `github:owner/repo`,
+    `git+https://github.com/owner/repo.git`, or `git@github.com:owner/repo.git`.
+*/
+pub fn parse_github_repository(url: &str) -> Option<(String, String)> {
+    let url = url.trim();
+    let path = if let Some(path) = url.strip_prefix("github:") {
+        path
+    } else if let Some((_, path)) = url.split_once("github.com/") {
+        path
+    } else if let Some((_, path)) = url.split_once("github.com:") {
+        path
+    } else {
+        return None;
+    };
+
+    let mut parts = path.split(['/', '#', '?']);
+    let owner = parts.next().filter(|s| !s.is_empty())?;
+    let repository = parts.next().map(|r| r.trim_end_matches(".git"))?;
+    if repository.is_empty() {
+        return None;
+    }
+
+    Some((owner.to_string(), repository.to_string()))
+}
+
+/**
+    Parses a version from a release tag, such as `v1.2.3`, or from monorepo
+    tags such as `name-v1.2.3` and `name@1.2.3`, which must match the name.
+*/
+fn parse_tag_version(tag: &str, name: &str) -> Option<Version> {
+    let starts = std::iter::once(0).chain(
+        tag.char_indices()
+            .filter(|(_, c)| matches!(c, '-' | '_' | '@' | '/'))
+            .map(|(index, _)| index + 1),
+    );
+    for start in starts {
+        let Ok(version) = tag[start..].trim_start_matches('v').parse::<Version>() else {
+            continue;
+        };
+        let prefix = normalize_name(tag[..start].trim_end_matches(['-', '_', '@', '/']));
+        let name = normalize_name(name);
+        if prefix.is_empty() || prefix == name {
+            return Some(version);
+        }
+        return None;
+    }
+    None
+}
+
+fn normalize_name(name: &str) -> String {
+    // Scoped packages such as @scope/name are usually tagged without the scope
+    let name = name.rsplit('/').next().unwrap_or(name);
+    name.to_ascii_lowercase().replace('_', "-")
+}
+
+/**
+    Parses release notes out of a markdown changelog, where each version
+    is a heading such as `## 1.2.3`, `## [1.2.3] - 2024-01-01` or `# v1.2.3`.
+*/
+fn parse_changelog(changelog: &str) -> Vec<ReleaseNote> {
+    let mut notes = Vec::new();
+    let mut current: Option<(ReleaseNote, usize)> = None;
+
+    for line in changelog.lines() {
+        let level = line.chars().take_while(|c| *c == '#').count();
+        let heading_version = (level > 0)
+            .then(|| parse_heading_version(&line[level..]))
+            .flatten();
+
+        // A heading at the same or a higher level ends the current section
+        let ends_section = current
+            .as_ref()
+            .is_some_and(|(_, current_level)| level > 0 && level <= *current_level);
+        if ends_section || heading_version.is_some() {
+            notes.extend(current.take().map(|(note, _)| note));
+        }
+
+        if let Some(version) = heading_version {
+            let note = ReleaseNote {
+                title: line[level..].trim().to_string(),
+                body: String::new(),
+                version,
+            };
+            current = Some((note, level));
+        } else if let Some((note, _)) = current.as_mut() {
+            note.body.push_str(line);
+            note.body.push('\n');
+        }
+    }
+
+    if let Some((note, _)) = current {
+        notes.push(note);
+    }
+
+    notes
+}
+
+fn parse_heading_version(heading: &str) -> Option<Version> {
+    heading
+        .split(|c: char| c.is_whitespace() || matches!(c, '[' | ']' | '(' | ')' | '`'))
+        .find_map(|word| word.trim_start_matches('v').parse().ok())
+}
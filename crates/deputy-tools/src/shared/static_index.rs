@@ -0,0 +1,81 @@
+use super::rank_matches;
+
+const MINIMUM_PREFIX_MATCHES: usize = 8; // Less than 8 prefix matches = also scan for fuzzy matches
+
+/**
+    A package in a bundled index of top packages.
+
+    Descriptions are empty for registries that do not bundle them.
+*/
+#[derive(Debug, Clone, Copy)]
+pub struct IndexedPackage {
+    pub name: &'static str,
+    pub popularity: u64,
+    pub description: &'static str,
+}
+
+impl AsRef<str> for IndexedPackage {
+    fn as_ref(&self) -> &str {
+        self.name
+    }
+}
+
+/**
+    A static, prefix-bucketed index of packages, generated by
+    the build script from the bundled text files in `assets`.
+
+    This provides the same search as `CompletionMap`, but needs no
+    parsing or allocations at runtime, so even the first search is instant.
+*/
+#[derive(Debug)]
+pub struct StaticIndex {
+    pub packages: &'static [IndexedPackage],
+    pub prefixes: &'static [(&'static str, &'static [u32])], // Sorted by prefix
+}
+
+impl StaticIndex {
+    fn bucket(&self, prefix: &str) -> &'static [u32] {
+        // Buckets are keyed by up to two lowercase characters
+        let key_len = prefix.chars().take(2).map(char::len_utf8).sum::<usize>();
+        let key = &prefix[..key_len];
+        let prefixes = self.prefixes;
+        prefixes
+            .binary_search_by(|(p, _)| (*p).cmp(key))
+            .map_or(&[], |found| prefixes[found].1)
+    }
+
+    /**
+        Searches for packages matching the given query, ranked by how well they
+        match and then by popularity, returning at most `limit` packages.
+
+        Prefix matches are always ranked first, so these are found using the prefix
+        buckets, and all packages are only scanned for fuzzy matches if there are
+        very few prefix matches, since scanning every package is comparatively slow.
+    */
+    pub fn search(&self, query: &str, limit: usize) -> Vec<&'static IndexedPackage> {
+        let packages = self.packages;
+        let query = query.trim();
+        let prefix = query.to_ascii_lowercase();
+
+        let mut matches = if prefix.is_empty() {
+            rank_matches(packages, query, |p| p.name, |p| p.popularity)
+        } else {
+            let prefixed = self
+                .bucket(&prefix)
+                .iter()
+                .map(|index| &packages[*index as usize])
+                .filter(|p| {
+                    p.name
+                        .get(..prefix.len())
+                        .is_some_and(|start| start.eq_ignore_ascii_case(&prefix))
+                });
+            rank_matches(prefixed, query, |p| p.name, |p| p.popularity)
+        };
+        if matches.len() < limit.min(MINIMUM_PREFIX_MATCHES) && !query.is_empty() {
+            matches = rank_matches(packages, query, |p| p.name, |p| p.popularity);
+        }
+
+        matches.truncate(limit);
+        matches
+    }
+}
@@ -31,3 +31,147 @@ where
     let prefix: String = prefix.into().trim().to_ascii_lowercase();
     s.starts_with(prefix.as_str())
 }
+
+/**
+    How well a completion candidate matches what has been typed so far.
+
+    Ordered from best to worst - candidates that do not match at all have no quality.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum MatchQuality {
+    Exact,
+    Prefix,
+    WordPrefix,
+    Substring,
+    Subsequence,
+}
+
+impl MatchQuality {
+    /**
+        Matches a candidate against a query, ignoring case and treating
+        dashes and underscores the same, as package registries usually do.
+    */
+    pub fn of(candidate: &str, query: &str) -> Option<Self> {
+        // Normalizing only changes ASCII bytes, so byte offsets stay the same and
+        // candidates can be compared without allocating normalized copies of them
+        let candidate = candidate.trim().as_bytes();
+        let query = query.trim().as_bytes();
+        let matches_at = |index: usize| {
+            candidate
+                .get(index..index + query.len())
+                .is_some_and(|part| part.iter().zip(query).all(|(c, q)| eq_normalized(*c, *q)))
+        };
+
+        if candidate.len() == query.len() && matches_at(0) {
+            Some(Self::Exact)
+        } else if matches_at(0) {
+            Some(Self::Prefix)
+        } else if (1..candidate.len()).any(|index| {
+            matches!(
+                normalize_byte(candidate[index - 1]),
+                b'-' | b'/' | b'@' | b'.'
+            ) && matches_at(index)
+        }) {
+            Some(Self::WordPrefix)
+        } else if (1..candidate.len()).any(matches_at) {
+            Some(Self::Substring)
+        } else {
+            let mut bytes = candidate.iter();
+            query
+                .iter()
+                .all(|q| bytes.any(|c| eq_normalized(*c, *q)))
+                .then_some(Self::Subsequence)
+        }
+    }
+}
+
+/**
+    Ranks completion candidates by how well they match the query, and
+    then by popularity, dropping any candidates that do not match at all.
+
+    Candidates that are equally good matches and equally popular keep their order.
+*/
+pub fn rank_matches<T, N, P>(
+    candidates: impl IntoIterator<Item = T>,
+    query: &str,
+    name: N,
+    popularity: P,
+) -> Vec<T>
+where
+    N: Fn(&T) -> &str,
+    P: Fn(&T) -> u64,
+{
+    let mut ranked = candidates
+        .into_iter()
+        .filter_map(|candidate| {
+            let quality = MatchQuality::of(name(&candidate), query)?;
+            Some((quality, popularity(&candidate), candidate))
+        })
+        .collect::<Vec<_>>();
+    ranked.sort_by(|(qa, pa, _), (qb, pb, _)| qa.cmp(qb).then(pb.cmp(pa)));
+    ranked
+        .into_iter()
+        .map(|(_, _, candidate)| candidate)
+        .collect()
+}
+
+fn normalize_byte(b: u8) -> u8 {
+    match b {
+        b'_' => b'-',
+        b => b.to_ascii_lowercase(),
+    }
+}
+
+fn eq_normalized(a: u8, b: u8) -> bool {
+    normalize_byte(a) == normalize_byte(b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MatchQuality;
+
+    #[test]
+    fn match_quality() {
+        assert_eq!(
+            MatchQuality::of("serde", "serde"),
+            Some(MatchQuality::Exact)
+        );
+        assert_eq!(
+            MatchQuality::of("Serde_Json", "serde-json"),
+            Some(MatchQuality::Exact)
+        );
+        assert_eq!(
+            MatchQuality::of("serde_json", "serde"),
+            Some(MatchQuality::Prefix)
+        );
+        assert_eq!(
+            MatchQuality::of("@types/node", "node"),
+            Some(MatchQuality::WordPrefix)
+        );
+        assert_eq!(
+            MatchQuality::of("serde_json", "json"),
+            Some(MatchQuality::WordPrefix)
+        );
+        assert_eq!(
+            MatchQuality::of("miniserde", "serde"),
+            Some(MatchQuality::Substring)
+        );
+        assert_eq!(
+            MatchQuality::of("serde_json", "sjs"),
+            Some(MatchQuality::Subsequence)
+        );
+        assert_eq!(MatchQuality::of("serde", "serde_json"), None);
+        assert_eq!(MatchQuality::of("serde", "x"), None);
+    }
+
+    #[test]
+    fn match_quality_empty_and_unicode() {
+        assert_eq!(MatchQuality::of("", ""), Some(MatchQuality::Exact));
+        assert_eq!(MatchQuality::of("serde", " "), Some(MatchQuality::Prefix));
+        assert_eq!(
+            MatchQuality::of("ünïcode-pkg", "pkg"),
+            Some(MatchQuality::WordPrefix)
+        );
+        assert_eq!(MatchQuality::of("ünïcode", "ÜN"), None);
+    }
+}
@@ -1,7 +1,7 @@
 use async_language_server::{
     lsp_types::{
-        CodeActionOrCommand, CodeActionParams, CompletionResponse, Diagnostic,
-        DocumentDiagnosticParams, Hover, Position,
+        CodeActionOrCommand, CodeActionParams, CompletionItem, CompletionResponse, Diagnostic,
+        DidChangeWatchedFilesParams, DocumentDiagnosticParams, Hover, Position,
     },
     server::{Document, ServerResult},
     tree_sitter::Node,
@@ -12,7 +12,7 @@ use deputy_clients::Clients;
 use crate::cargo::Cargo;
 use crate::npm::Npm;
 use crate::rokit::Rokit;
-use crate::shared::{CodeActionMetadata, ResolveContext};
+use crate::shared::{CodeActionMetadata, EntryMetadata, ReleaseNotes, ResolveContext};
 use crate::wally::Wally;
 
 #[derive(Debug, Clone)]
@@ -72,6 +72,41 @@ impl Tools {
         }
     }
 
+    /**
+        Resolves additional information for a completion item, such as the
+        latest version for completions that insert a whole dependency entry.
+    */
+    #[allow(clippy::missing_errors_doc)]
+    pub async fn completion_resolve(&self, item: CompletionItem) -> ServerResult<CompletionItem> {
+        let Some(Ok(context)) = item
+            .data
+            .as_ref()
+            .map(ResolveContext::<EntryMetadata>::try_from)
+        else {
+            return Ok(item);
+        };
+
+        let uri = context.uri.clone();
+        match context.into_inner() {
+            EntryMetadata::Cargo { name } => self.cargo.completion_resolve(item, &name).await,
+            EntryMetadata::Npm { name } => self.npm.completion_resolve(item, &uri, &name).await,
+            EntryMetadata::Rokit { owner, repository } => {
+                self.rokit
+                    .completion_resolve(item, &owner, &repository)
+                    .await
+            }
+            EntryMetadata::Wally {
+                index_url,
+                scope,
+                name,
+            } => {
+                self.wally
+                    .completion_resolve(item, &index_url, &scope, &name)
+                    .await
+            }
+        }
+    }
+
     #[allow(clippy::missing_errors_doc)]
     pub async fn diagnostics(
         &self,
@@ -90,31 +125,90 @@ impl Tools {
         }
     }
 
-    #[allow(clippy::unused_self)]
-    #[allow(clippy::unused_async)]
+    /**
+        Notifies tools that files on disk have changed, letting
+        them invalidate any data derived from those files.
+
+        Returns `true` if diagnostics for open documents may have changed,
+        meaning that the client should be asked to pull them again.
+    */
+    #[must_use]
+    pub fn did_change_watched_files(&self, params: &DidChangeWatchedFilesParams) -> bool {
+        let mut changed = false;
+        for change in &params.changes {
+            if let Ok(path) = change.uri.to_file_path() {
+                changed |= self.cargo.did_change_file(&path);
+            }
+        }
+        changed
+    }
+
+    /**
+        Glob patterns for files on disk that tools derive data from, which
+        the client should watch and send `workspace/didChangeWatchedFiles` for.
+    */
+    #[must_use]
+    pub fn watched_file_globs() -> Vec<String> {
+        vec![String::from("**/*.rs"), String::from("**/Cargo.toml")]
+    }
+
+    /**
+        Creates code actions for diagnostics in the given document.
+
+        Actions that show release notes use the `deputy.showChanges` command,
+        and are only created if `show_changes` is set, meaning that the client
+        is known to implement that command.
+    */
     #[allow(clippy::missing_errors_doc)]
     pub async fn code_action(
         &self,
         doc: &Document,
         params: CodeActionParams,
+        show_changes: bool,
     ) -> ServerResult<Vec<CodeActionOrCommand>> {
-        if Tool::from_document(doc).is_none() {
+        let Some(tool) = Tool::from_document(doc) else {
             return Ok(Vec::new());
-        }
+        };
 
         let mut actions = Vec::new();
         for diag in params.context.diagnostics {
-            if let Some(Ok(action)) = diag
+            let Some(Ok(context)) = diag
                 .data
                 .as_ref()
                 .map(ResolveContext::<CodeActionMetadata>::try_from)
-            {
-                actions.push(action.into_inner().into_code_action(diag.clone()));
-            }
+            else {
+                continue;
+            };
+
+            // Newer versions may also come with release notes worth showing
+            let metadata = context.into_inner();
+            let release_notes =
+                if show_changes && matches!(metadata, CodeActionMetadata::LatestVersion { .. }) {
+                    self.release_notes(tool, doc, diag.range.start).await
+                } else {
+                    None
+                };
+
+            actions.push(metadata.into_code_action(diag.clone()));
+            actions.extend(release_notes.map(|notes| notes.into_code_action(diag)));
         }
 
         Ok(actions)
     }
+
+    async fn release_notes(
+        &self,
+        tool: Tool,
+        doc: &Document,
+        pos: Position,
+    ) -> Option<ReleaseNotes> {
+        match tool {
+            Tool::Cargo => self.cargo.release_notes(doc, pos).await,
+            Tool::Npm => self.npm.release_notes(doc, pos).await,
+            Tool::Rokit => self.rokit.release_notes(doc, pos).await,
+            Tool::Wally => self.wally.release_notes(doc, pos).await,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -9,10 +9,13 @@ use async_language_server::{
 };
 use tracing::debug;
 
-use deputy_parser::wally;
-use deputy_versioning::Versioned;
+use deputy_parser::wally::{self, WallyIncompleteDependency};
+use deputy_versioning::{Version, Versioned};
 
-use crate::shared::filter_starts_with;
+use crate::shared::{
+    ENTRY_VERSION_PLACEHOLDER, EntryCompletion, EntryMetadata, entry_completion_response,
+    fetch_entry_versions, is_entry_version_missing, rank_matches, resolve_entry_version,
+};
 
 use super::Clients;
 
@@ -81,6 +84,129 @@ pub async fn get_wally_completions(
     Ok(None)
 }
 
+/**
+    Completes a package that only has its alias typed out so far, inserting
+    the whole entry, using the conventional `PascalCase` alias for the package.
+
+    Wally indexes can not be searched, so packages are only suggested from
+    scopes that the manifest already depends on packages from.
+*/
+pub async fn get_wally_entry_completions(
+    clients: &Clients,
+    doc: &Document,
+    index_url: &str,
+    dep: WallyIncompleteDependency<'_>,
+) -> ServerResult<Option<CompletionResponse>> {
+    let name = dep.text(doc);
+    let range = ts_range_to_lsp_range(dep.range());
+
+    debug!("Completing entry: {dep:?}");
+
+    let mut scopes = wally::find_all_dependencies(doc)
+        .into_iter()
+        .filter_map(wally::parse_dependency)
+        .filter_map(|d| d.spec_ranges(doc).text(doc).0.map(str::to_ascii_lowercase))
+        .collect::<Vec<_>>();
+    scopes.sort_unstable();
+    scopes.dedup();
+
+    let mut candidates = Vec::new();
+    for scope in scopes {
+        if let Ok(packages) = clients.wally.get_index_packages(index_url, &scope).await {
+            candidates.extend(packages.into_iter().map(|package| (scope.clone(), package)));
+        }
+    }
+
+    let candidates = rank_matches(candidates, name.as_str(), |(_, p)| p, |_| 0)
+        .into_iter()
+        .take(MAXIMUM_PACKAGES_SHOWN)
+        .collect::<Vec<_>>();
+    let versions = fetch_entry_versions(&candidates, |(scope, package)| {
+        latest_package_version(clients, index_url, scope, package)
+    })
+    .await;
+
+    let items = candidates
+        .into_iter()
+        .zip(versions)
+        .enumerate()
+        .map(|(index, ((scope, package), version))| {
+            let alias = package_alias(&package);
+            let entry = EntryCompletion {
+                label: alias.clone(),
+                label_detail: Some(format!(" ({scope}/{package})")),
+                detail: None,
+                snippet: format!("{alias} = \"{scope}/{package}@{ENTRY_VERSION_PLACEHOLDER}\""),
+                range,
+                sort_text: Some(format!("{index:0>5}")),
+                version,
+            };
+            entry.into_completion_item(
+                doc.url().clone(),
+                EntryMetadata::Wally {
+                    index_url: index_url.to_string(),
+                    scope,
+                    name: package,
+                },
+            )
+        })
+        .collect::<Vec<_>>();
+
+    Ok(Some(entry_completion_response(items)))
+}
+
+/**
+    Adds the latest version of a package to a dependency entry
+    that did not get its version filled in with the list.
+*/
+pub async fn resolve_wally_entry(
+    clients: &Clients,
+    mut item: CompletionItem,
+    index_url: &str,
+    scope: &str,
+    name: &str,
+) -> ServerResult<CompletionItem> {
+    if !is_entry_version_missing(&item) {
+        return Ok(item);
+    }
+    if let Some(latest) = latest_package_version(clients, index_url, scope, name).await {
+        resolve_entry_version(&mut item, &latest);
+    }
+    Ok(item)
+}
+
+async fn latest_package_version(
+    clients: &Clients,
+    index_url: &str,
+    scope: &str,
+    name: &str,
+) -> Option<String> {
+    let metadatas = clients
+        .wally
+        .get_index_metadatas(index_url, scope, name)
+        .await
+        .ok()?;
+    let latest = Version::new(0, 0, 0).extract_latest_version(metadatas)?;
+    Some(latest.item_version.to_string())
+}
+
+/**
+    Converts a package name such as `react-roblox` into
+    the alias it is conventionally required as, `ReactRoblox`.
+*/
+fn package_alias(package: &str) -> String {
+    package
+        .split(['-', '_'])
+        .map(|part| {
+            let mut chars = part.chars();
+            chars
+                .next()
+                .map(|first| first.to_ascii_uppercase().to_string() + chars.as_str())
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
 async fn complete_scope(
     clients: &Clients,
     index_url: &str,
@@ -92,13 +218,14 @@ async fn complete_scope(
         Ok(m) => m,
     };
 
-    let items = package_scopes
+    let items = rank_matches(package_scopes, scope, String::as_str, |_| 0)
         .into_iter()
-        .filter(|s| filter_starts_with(s.as_str(), scope))
         .take(MAXIMUM_PACKAGES_SHOWN)
-        .map(|scope| CompletionItem {
+        .enumerate()
+        .map(|(index, scope)| CompletionItem {
             label: scope.to_string(),
             kind: Some(CompletionItemKind::ENUM),
+            sort_text: Some(format!("{index:0>5}")),
             commit_characters: Some(vec![String::from("/")]),
             text_edit: Some(CompletionTextEdit::Edit(TextEdit {
                 new_text: scope.to_string(),
@@ -122,13 +249,14 @@ async fn complete_package(
         Ok(m) => m,
     };
 
-    let items = package_names
+    let items = rank_matches(package_names, package, String::as_str, |_| 0)
         .into_iter()
-        .filter(|p| filter_starts_with(p.as_str(), package))
         .take(MAXIMUM_PACKAGES_SHOWN)
-        .map(|package| CompletionItem {
+        .enumerate()
+        .map(|(index, package)| CompletionItem {
             label: package.to_string(),
             kind: Some(CompletionItemKind::ENUM_MEMBER),
+            sort_text: Some(format!("{index:0>5}")),
             commit_characters: Some(vec![String::from("@")]),
             text_edit: Some(CompletionTextEdit::Edit(TextEdit {
                 new_text: package.to_string(),
@@ -1,15 +1,16 @@
 use tracing::trace;
 
 use async_language_server::{
-    lsp_types::{Hover, HoverContents, MarkupContent, MarkupKind},
+    lsp_types::{Hover, HoverContents, MarkupContent, MarkupKind, Position},
     server::{Document, ServerResult},
     tree_sitter::Node,
-    tree_sitter_utils::ts_range_to_lsp_range,
+    tree_sitter_utils::{ts_range_contains_lsp_position, ts_range_to_lsp_range},
 };
 
 use deputy_parser::wally;
+use deputy_versioning::{VersionReq, VersionReqExt, Versioned};
 
-use crate::shared::MarkdownBuilder;
+use crate::shared::{MarkdownBuilder, ReleaseNotes};
 
 use super::Clients;
 use super::constants::WALLY_DEFAULT_REGISTRY;
@@ -18,22 +19,53 @@ pub async fn get_wally_hover(
     clients: &Clients,
     doc: &Document,
     index_url: &str,
+    pos: Position,
     node: Node<'_>,
 ) -> ServerResult<Option<Hover>> {
     let Some(dep) = wally::parse_dependency(node) else {
         return Ok(None);
     };
 
-    let (Some(owner), Some(repository), Some(version)) = dep.spec_ranges(doc).text(doc) else {
+    let spec_ranges = dep.spec_ranges(doc);
+    let (Some(owner), Some(repository), Some(version)) = spec_ranges.text(doc) else {
         return Ok(None);
     };
 
+    // Hovering the version explains the requirement instead
+    if let Some(version_range) = spec_ranges
+        .version
+        .filter(|r| ts_range_contains_lsp_position(*r, pos))
+    {
+        trace!("Hovering version range: {owner}/{repository} version {version}");
+        let mut md = MarkdownBuilder::new();
+        md.h2(format!("{owner}/{repository}"));
+        md.version(version);
+        if let Some(range) = clients
+            .wally
+            .get_index_metadatas(index_url, owner, repository)
+            .await
+            .ok()
+            .and_then(|metadatas| version.extract_version_range(metadatas))
+        {
+            md.br();
+            md.version_range(&range);
+        }
+        return Ok(Some(Hover {
+            range: Some(ts_range_to_lsp_range(version_range)),
+            contents: HoverContents::Markup(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value: md.build(),
+            }),
+        }));
+    }
+
     // Add basic hover information with version and name
     trace!("Hovering: {owner} version {version}");
     let mut md = MarkdownBuilder::new();
 
     // Try to fetch additional information from the index - description, links
     trace!("Fetching index metadatas from Wally API");
+    let release_notes = get_wally_release_notes(clients, doc, index_url, node).await;
     if let Ok(mut metadatas) = clients
         .wally
         .get_index_metadatas(index_url, owner, repository)
@@ -50,6 +82,12 @@ pub async fn get_wally_hover(
                 md.p(desc);
             }
 
+            // Add a summary of what changed since the current version
+            if let Some(notes) = release_notes {
+                md.br();
+                notes.add_hover_section(&mut md);
+            }
+
             // Add links, if available
             let wally_run = metadata
                 .package
@@ -94,3 +132,36 @@ pub async fn get_wally_hover(
         }),
     }))
 }
+
+/**
+    Gets cached release notes for all versions between the
+    specified version and the latest version of the package.
+*/
+pub async fn get_wally_release_notes(
+    clients: &Clients,
+    doc: &Document,
+    index_url: &str,
+    node: Node<'_>,
+) -> Option<ReleaseNotes> {
+    let dep = wally::parse_dependency(node)?;
+    let (Some(owner), Some(repository), Some(version)) = dep.spec_ranges(doc).text(doc) else {
+        return None;
+    };
+    let version_current = VersionReq::parse(version).ok()?.minimum_version();
+
+    let metadatas = clients
+        .wally
+        .get_index_metadatas(index_url, owner, repository)
+        .await
+        .ok()?;
+    let latest = version_current.extract_latest_version(metadatas)?;
+    let repository_url = latest.item.package.repository.as_deref()?;
+
+    ReleaseNotes::cached(
+        clients,
+        &latest.item.package.name,
+        repository_url,
+        &version_current,
+        &latest.item_version,
+    )
+}
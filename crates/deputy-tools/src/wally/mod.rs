@@ -4,7 +4,9 @@ use futures::future::try_join_all;
 use tracing::debug;
 
 use async_language_server::{
-    lsp_types::{CompletionResponse, Diagnostic, DocumentDiagnosticParams, Hover, Position},
+    lsp_types::{
+        CompletionItem, CompletionResponse, Diagnostic, DocumentDiagnosticParams, Hover, Position,
+    },
     server::{Document, ServerResult},
     tree_sitter::Node,
 };
@@ -12,15 +14,17 @@ use async_language_server::{
 use deputy_clients::Clients;
 use deputy_parser::wally;
 
+use crate::shared::ReleaseNotes;
+
 mod completion;
 mod constants;
 mod diagnostics;
 mod hover;
 
-use completion::get_wally_completions;
+use completion::{get_wally_completions, get_wally_entry_completions, resolve_wally_entry};
 use constants::WALLY_DEFAULT_REGISTRY;
 use diagnostics::get_wally_diagnostics;
-use hover::get_wally_hover;
+use hover::{get_wally_hover, get_wally_release_notes};
 
 #[derive(Debug, Clone)]
 pub struct Wally {
@@ -46,7 +50,17 @@ impl Wally {
 
         debug!("Hovering: {dep:?}");
 
-        get_wally_hover(&self.clients, doc, index_url.as_str(), dep).await
+        get_wally_hover(&self.clients, doc, index_url.as_str(), pos, dep).await
+    }
+
+    pub(super) async fn release_notes(
+        &self,
+        doc: &Document,
+        pos: Position,
+    ) -> Option<ReleaseNotes> {
+        let dep = wally::find_dependency_at(doc, pos)?;
+        let index_url = extract_wally_index_url(doc);
+        get_wally_release_notes(&self.clients, doc, index_url.as_str(), dep).await
     }
 
     pub(super) async fn completion(
@@ -55,17 +69,32 @@ impl Wally {
         pos: Position,
         _node: Node<'_>,
     ) -> ServerResult<Option<CompletionResponse>> {
+        let index_url = extract_wally_index_url(doc);
+
         let Some(dep) = wally::find_dependency_at(doc, pos) else {
-            return Ok(None);
+            return match wally::find_incomplete_dependency_at(doc, pos) {
+                Some(dep) => {
+                    get_wally_entry_completions(&self.clients, doc, index_url.as_str(), dep).await
+                }
+                None => Ok(None),
+            };
         };
 
-        let index_url = extract_wally_index_url(doc);
-
         debug!("Fetching completions: {dep:?}");
 
         get_wally_completions(&self.clients, doc, pos, index_url.as_str(), dep).await
     }
 
+    pub(super) async fn completion_resolve(
+        &self,
+        item: CompletionItem,
+        index_url: &str,
+        scope: &str,
+        name: &str,
+    ) -> ServerResult<CompletionItem> {
+        resolve_wally_entry(&self.clients, item, index_url, scope, name).await
+    }
+
     pub(super) async fn diagnostics(
         &self,
         doc: &Document,
@@ -1,7 +1,9 @@
+mod npm_version_req;
 mod version;
 mod version_req;
 
-pub use version::{CompletionVersion, LatestVersion, Versioned};
+pub use npm_version_req::{NpmComparator, NpmOp, NpmVersionReq, NpmVersionReqError};
+pub use version::{CompletionVersion, LatestVersion, VersionRange, Versioned};
 pub use version_req::VersionReqExt;
 
 pub use semver::{Version, VersionReq};
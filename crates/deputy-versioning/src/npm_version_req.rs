@@ -0,0 +1,741 @@
+use std::{cmp::Ordering, error::Error, fmt, str::FromStr};
+
+use semver::{BuildMetadata, Prerelease, Version};
+
+use crate::VersionReqExt;
+
+/**
+    An operator in a desugared npm comparator.
+
+    All of the npm range syntax - tilde, caret, x-ranges and hyphen
+    ranges - is desugared into comparators using only these operators.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NpmOp {
+    Exact,
+    Greater,
+    GreaterEq,
+    Less,
+    LessEq,
+}
+
+impl NpmOp {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Exact => "",
+            Self::Greater => ">",
+            Self::GreaterEq => ">=",
+            Self::Less => "<",
+            Self::LessEq => "<=",
+        }
+    }
+}
+
+/**
+    A single desugared comparator in an npm version range, such as `>=1.2.3`.
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NpmComparator {
+    pub op: NpmOp,
+    pub version: Version,
+}
+
+impl NpmComparator {
+    fn new(op: NpmOp, version: Version) -> Self {
+        Self { op, version }
+    }
+
+    /**
+        Checks if the given version satisfies this comparator,
+        without taking any prerelease inclusion rules into account.
+    */
+    #[must_use]
+    pub fn matches(&self, version: &Version) -> bool {
+        let ordering = compare_ignoring_build(version, &self.version);
+        match self.op {
+            NpmOp::Exact => ordering == Ordering::Equal,
+            NpmOp::Greater => ordering == Ordering::Greater,
+            NpmOp::GreaterEq => ordering != Ordering::Less,
+            NpmOp::Less => ordering == Ordering::Less,
+            NpmOp::LessEq => ordering != Ordering::Greater,
+        }
+    }
+}
+
+impl fmt::Display for NpmComparator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.op.as_str(), self.version)
+    }
+}
+
+/**
+    An error that occurred while parsing an npm version range.
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NpmVersionReqError {
+    input: String,
+    reason: String,
+}
+
+impl NpmVersionReqError {
+    fn new(input: &str, reason: impl Into<String>) -> Self {
+        Self {
+            input: input.to_string(),
+            reason: reason.into(),
+        }
+    }
+}
+
+impl fmt::Display for NpmVersionReqError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid npm version range `{}` - {}",
+            self.input, self.reason
+        )
+    }
+}
+
+impl Error for NpmVersionReqError {}
+
+/**
+    A version range using the semantics of [node-semver](https://github.com/npm/node-semver),
+    which is what npm, and most other JavaScript package managers, use for dependencies.
+
+    This differs from Cargo-flavored version requirements in several ways:
+
+    - Ranges can be combined using `||`, such as `1.x || >=2.5.0`
+    - Comparators are separated by whitespace instead of commas
+    - Hyphen ranges such as `1.2.3 - 2.3.4` are supported
+    - A bare version such as `1.2.3` is an exact match, and not a caret range
+    - Prerelease versions only match if a comparator in the same
+      set has a prerelease on the exact same `major.minor.patch`
+
+    The range is stored in its desugared form, as sets of comparators, where a
+    version matches if it satisfies all of the comparators in any one of the sets.
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NpmVersionReq {
+    pub sets: Vec<Vec<NpmComparator>>,
+}
+
+impl NpmVersionReq {
+    /**
+        Parses an npm version range.
+
+        # Errors
+
+        - If any of the versions in the range are not valid, partial or full, versions.
+        - If the range contains an unknown operator.
+    */
+    pub fn parse(input: &str) -> Result<Self, NpmVersionReqError> {
+        let sets = input
+            .split("||")
+            .map(|set| parse_set(set).map_err(|reason| NpmVersionReqError::new(input, reason)))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self { sets })
+    }
+
+    /**
+        Checks if the given version satisfies this range.
+
+        Prerelease versions are only matched if a comparator in the matching
+        set has a prerelease for the exact same `major.minor.patch` version,
+        so that `>=1.2.3-beta.1` matches `1.2.3-beta.2` but not `1.2.4-beta.1`.
+    */
+    #[must_use]
+    pub fn matches(&self, version: &Version) -> bool {
+        self.sets.iter().any(|set| matches_set(set, version))
+    }
+
+    /**
+        Finds the lowest version that could possibly match this range.
+
+        This follows the `minVersion` function of node-semver,
+        and returns `None` if the range can not match anything.
+    */
+    #[must_use]
+    pub fn min_version(&self) -> Option<Version> {
+        for candidate in [Version::new(0, 0, 0), version_pre0(0, 0, 0)] {
+            if self.matches(&candidate) {
+                return Some(candidate);
+            }
+        }
+
+        let mut minimum: Option<Version> = None;
+        for set in &self.sets {
+            let mut set_minimum: Option<Version> = None;
+            for comparator in set {
+                let candidate = match comparator.op {
+                    NpmOp::Greater => next_version(&comparator.version),
+                    NpmOp::Exact | NpmOp::GreaterEq => Some(comparator.version.clone()),
+                    NpmOp::Less | NpmOp::LessEq => None,
+                };
+                set_minimum = match (set_minimum, candidate) {
+                    (Some(current), Some(candidate)) => Some(current.max(candidate)),
+                    (current, candidate) => current.or(candidate),
+                };
+            }
+            minimum = match (minimum, set_minimum) {
+                (Some(current), Some(candidate)) => Some(current.min(candidate)),
+                (current, candidate) => current.or(candidate),
+            };
+        }
+
+        minimum.filter(|version| self.matches(version))
+    }
+}
+
+impl VersionReqExt for NpmVersionReq {
+    fn minimum_version(&self) -> Version {
+        self.min_version().unwrap_or_else(|| Version::new(0, 0, 0))
+    }
+
+    fn maximum_version_exclusive(&self) -> Option<Version> {
+        // Any set without an upper bound makes the whole range unbounded
+        self.sets
+            .iter()
+            .map(|set| set.iter().filter_map(upper_bound_for_comparator).min())
+            .collect::<Option<Vec<_>>>()?
+            .into_iter()
+            .max()
+    }
+
+    fn exact_version(&self) -> Option<Version> {
+        match self.sets.as_slice() {
+            [set] => match set.as_slice() {
+                [comparator] if comparator.op == NpmOp::Exact => Some(comparator.version.clone()),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for NpmVersionReq {
+    type Err = NpmVersionReqError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl fmt::Display for NpmVersionReq {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, set) in self.sets.iter().enumerate() {
+            if index > 0 {
+                write!(f, " || ")?;
+            }
+            if set.is_empty() {
+                write!(f, "*")?;
+            }
+            for (index, comparator) in set.iter().enumerate() {
+                if index > 0 {
+                    write!(f, " ")?;
+                }
+                write!(f, "{comparator}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/**
+    A partial version, such as `1`, `1.2`, `1.x` or `1.2.3-beta.1`,
+    where missing or wildcard components are represented as `None`.
+*/
+struct Partial {
+    major: Option<u64>,
+    minor: Option<u64>,
+    patch: Option<u64>,
+    pre: Prerelease,
+}
+
+impl Partial {
+    fn full(&self) -> Option<Version> {
+        Some(Version {
+            major: self.major?,
+            minor: self.minor?,
+            patch: self.patch?,
+            pre: self.pre.clone(),
+            build: BuildMetadata::EMPTY,
+        })
+    }
+}
+
+fn parse_set(set: &str) -> Result<Vec<NpmComparator>, String> {
+    // Operators may be separated from their versions by whitespace, such as `>= 1.2.3`
+    let mut tokens = Vec::<String>::new();
+    for token in set.split_whitespace() {
+        match tokens.last_mut() {
+            Some(last) if is_operator(last) => last.push_str(token),
+            _ => tokens.push(token.to_string()),
+        }
+    }
+
+    if tokens.len() == 3 && tokens[1] == "-" {
+        return parse_hyphen(&tokens[0], &tokens[2]);
+    }
+
+    let mut comparators = Vec::new();
+    for token in tokens {
+        comparators.extend(parse_comparator(&token)?);
+    }
+    Ok(comparators)
+}
+
+fn is_operator(token: &str) -> bool {
+    !token.is_empty()
+        && token
+            .chars()
+            .all(|c| matches!(c, '<' | '>' | '=' | '~' | '^'))
+}
+
+fn parse_comparator(token: &str) -> Result<Vec<NpmComparator>, String> {
+    if let Some(rest) = token.strip_prefix("~>").or_else(|| token.strip_prefix('~')) {
+        return Ok(tilde(&parse_partial(rest)?));
+    }
+    if let Some(rest) = token.strip_prefix('^') {
+        return Ok(caret(&parse_partial(rest)?));
+    }
+
+    let (op, rest) = [
+        (">=", NpmOp::GreaterEq),
+        ("<=", NpmOp::LessEq),
+        (">", NpmOp::Greater),
+        ("<", NpmOp::Less),
+        ("=", NpmOp::Exact),
+    ]
+    .into_iter()
+    .find_map(|(prefix, op)| token.strip_prefix(prefix).map(|rest| (op, rest)))
+    .unwrap_or((NpmOp::Exact, token));
+
+    Ok(xrange(op, &parse_partial(rest)?))
+}
+
+fn parse_partial(s: &str) -> Result<Partial, String> {
+    // Loose mode allows a leading `v` or `=`, such as `v1.2.3` or `=v1.2.3`
+    let s = s.trim_start_matches('=').trim_start_matches(['v', 'V']);
+    let s = s.split_once('+').map_or(s, |(version, _build)| version);
+    let (numbers, pre) = s.split_once('-').unwrap_or((s, ""));
+    if numbers.is_empty() && !s.is_empty() {
+        return Err(format!("missing version in `{s}`"));
+    }
+
+    let mut components = [None; 3];
+    if !numbers.is_empty() {
+        let parts = numbers.split('.').collect::<Vec<_>>();
+        if parts.len() > 3 {
+            return Err(format!("too many version components in `{s}`"));
+        }
+        for (index, part) in parts.into_iter().enumerate() {
+            if matches!(part, "x" | "X" | "*") {
+                break; // Anything following a wildcard is also a wildcard
+            }
+            let number = part
+                .parse::<u64>()
+                .map_err(|_| format!("invalid version component `{part}` in `{s}`"))?;
+            components[index] = Some(number);
+        }
+    }
+
+    let [major, minor, patch] = components;
+    if !pre.is_empty() && patch.is_none() {
+        return Err(format!("prerelease on a partial version `{s}`"));
+    }
+
+    let pre = Prerelease::new(pre).map_err(|e| format!("invalid prerelease in `{s}` - {e}"))?;
+    Ok(Partial {
+        major,
+        minor,
+        patch,
+        pre,
+    })
+}
+
+fn parse_hyphen(from: &str, to: &str) -> Result<Vec<NpmComparator>, String> {
+    let from = parse_partial(from)?;
+    let to = parse_partial(to)?;
+
+    let mut comparators = Vec::new();
+    comparators.extend(match (from.major, from.minor, from.patch) {
+        (None, _, _) => None,
+        (Some(major), None, _) => Some(NpmComparator::new(
+            NpmOp::GreaterEq,
+            Version::new(major, 0, 0),
+        )),
+        (Some(major), Some(minor), None) => Some(NpmComparator::new(
+            NpmOp::GreaterEq,
+            Version::new(major, minor, 0),
+        )),
+        (Some(_), Some(_), Some(_)) => from
+            .full()
+            .map(|version| NpmComparator::new(NpmOp::GreaterEq, version)),
+    });
+    comparators.extend(match (to.major, to.minor, to.patch) {
+        (None, _, _) => None,
+        (Some(major), None, _) => {
+            bump_major(major).map(|upper| NpmComparator::new(NpmOp::Less, exclusive(upper)))
+        }
+        (Some(major), Some(minor), None) => {
+            bump_minor(major, minor).map(|upper| NpmComparator::new(NpmOp::Less, exclusive(upper)))
+        }
+        (Some(_), Some(_), Some(_)) => to
+            .full()
+            .map(|version| NpmComparator::new(NpmOp::LessEq, version)),
+    });
+    Ok(comparators)
+}
+
+fn tilde(p: &Partial) -> Vec<NpmComparator> {
+    match (p.major, p.minor) {
+        (None, _) => Vec::new(),
+        (Some(major), None) => between(Version::new(major, 0, 0), bump_major(major)),
+        (Some(major), Some(minor)) => between(
+            p.full().unwrap_or_else(|| Version::new(major, minor, 0)),
+            bump_minor(major, minor),
+        ),
+    }
+}
+
+fn caret(p: &Partial) -> Vec<NpmComparator> {
+    match (p.major, p.minor, p.patch) {
+        (None, _, _) => Vec::new(),
+        (Some(major), None, _) => between(Version::new(major, 0, 0), bump_major(major)),
+        (Some(0), Some(minor), None) => between(Version::new(0, minor, 0), bump_minor(0, minor)),
+        (Some(major), Some(minor), None) => {
+            between(Version::new(major, minor, 0), bump_major(major))
+        }
+        (Some(major), Some(minor), Some(patch)) => {
+            let lower = p
+                .full()
+                .unwrap_or_else(|| Version::new(major, minor, patch));
+            let upper = if major > 0 {
+                bump_major(major)
+            } else if minor > 0 {
+                bump_minor(0, minor)
+            } else {
+                bump_patch(0, 0, patch)
+            };
+            between(lower, upper)
+        }
+    }
+}
+
+fn xrange(op: NpmOp, p: &Partial) -> Vec<NpmComparator> {
+    let Some(major) = p.major else {
+        // Wildcards match everything, except when strictly greater or less than everything
+        return match op {
+            NpmOp::Greater | NpmOp::Less => matches_nothing(),
+            _ => Vec::new(),
+        };
+    };
+
+    if let Some(version) = p.full() {
+        return vec![NpmComparator::new(op, version)];
+    }
+
+    let minor = p.minor;
+    match op {
+        NpmOp::Exact => match minor {
+            None => between(Version::new(major, 0, 0), bump_major(major)),
+            Some(minor) => between(Version::new(major, minor, 0), bump_minor(major, minor)),
+        },
+        NpmOp::Greater => {
+            let lower = match minor {
+                None => bump_major(major),
+                Some(minor) => bump_minor(major, minor),
+            };
+            // Nothing can be greater than the largest possible version
+            lower.map_or_else(matches_nothing, |lower| {
+                vec![NpmComparator::new(NpmOp::GreaterEq, lower)]
+            })
+        }
+        NpmOp::GreaterEq => vec![NpmComparator::new(
+            NpmOp::GreaterEq,
+            Version::new(major, minor.unwrap_or(0), 0),
+        )],
+        NpmOp::Less => vec![NpmComparator::new(
+            NpmOp::Less,
+            version_pre0(major, minor.unwrap_or(0), 0),
+        )],
+        NpmOp::LessEq => match minor {
+            None => bump_major(major),
+            Some(minor) => bump_minor(major, minor),
+        }
+        .map(|upper| NpmComparator::new(NpmOp::Less, exclusive(upper)))
+        .into_iter()
+        .collect(),
+    }
+}
+
+fn between(lower: Version, upper: Option<Version>) -> Vec<NpmComparator> {
+    let mut comparators = vec![NpmComparator::new(NpmOp::GreaterEq, lower)];
+    comparators.extend(upper.map(|upper| NpmComparator::new(NpmOp::Less, exclusive(upper))));
+    comparators
+}
+
+fn matches_nothing() -> Vec<NpmComparator> {
+    vec![NpmComparator::new(NpmOp::Less, version_pre0(0, 0, 0))]
+}
+
+fn matches_set(set: &[NpmComparator], version: &Version) -> bool {
+    if !set.iter().all(|comparator| comparator.matches(version)) {
+        return false;
+    }
+
+    // Prereleases must be explicitly opted into for each major.minor.patch version
+    version.pre.is_empty()
+        || set.iter().any(|comparator| {
+            !comparator.version.pre.is_empty()
+                && comparator.version.major == version.major
+                && comparator.version.minor == version.minor
+                && comparator.version.patch == version.patch
+        })
+}
+
+fn upper_bound_for_comparator(comparator: &NpmComparator) -> Option<Version> {
+    let version = &comparator.version;
+    match comparator.op {
+        // The `-0` prerelease is only used to exclude prereleases, so it is not shown
+        NpmOp::Less if version.pre.as_str() == "0" => {
+            Some(Version::new(version.major, version.minor, version.patch))
+        }
+        NpmOp::Less => Some(version.clone()),
+        NpmOp::Exact | NpmOp::LessEq if version.pre.is_empty() => {
+            bump_patch(version.major, version.minor, version.patch)
+        }
+        NpmOp::Exact | NpmOp::LessEq => {
+            Some(Version::new(version.major, version.minor, version.patch))
+        }
+        NpmOp::Greater | NpmOp::GreaterEq => None,
+    }
+}
+
+fn compare_ignoring_build(a: &Version, b: &Version) -> Ordering {
+    a.major
+        .cmp(&b.major)
+        .then(a.minor.cmp(&b.minor))
+        .then(a.patch.cmp(&b.patch))
+        .then(a.pre.cmp(&b.pre))
+}
+
+fn next_version(version: &Version) -> Option<Version> {
+    if version.pre.is_empty() {
+        bump_patch(version.major, version.minor, version.patch)
+    } else {
+        let pre = format!("{}.0", version.pre);
+        Some(Version {
+            pre: Prerelease::new(&pre).unwrap_or_default(),
+            ..version.clone()
+        })
+    }
+}
+
+fn version_pre0(major: u64, minor: u64, patch: u64) -> Version {
+    exclusive(Version::new(major, minor, patch))
+}
+
+fn exclusive(version: Version) -> Version {
+    Version {
+        pre: Prerelease::new("0").unwrap(),
+        ..version
+    }
+}
+
+/*
+    Finds the next version after bumping a component, carrying over into the
+    next component once it reaches `u64::MAX`, so that there is no version
+    after the largest possible major version, instead of overflowing:
+
+    ```
+    bump_minor(1, u64::MAX) -> 2.0.0
+    bump_major(u64::MAX) -> None
+    ```
+*/
+fn bump_major(major: u64) -> Option<Version> {
+    Some(Version::new(major.checked_add(1)?, 0, 0))
+}
+
+fn bump_minor(major: u64, minor: u64) -> Option<Version> {
+    match minor.checked_add(1) {
+        Some(minor) => Some(Version::new(major, minor, 0)),
+        None => bump_major(major),
+    }
+}
+
+fn bump_patch(major: u64, minor: u64, patch: u64) -> Option<Version> {
+    match patch.checked_add(1) {
+        Some(patch) => Some(Version::new(major, minor, patch)),
+        None => bump_minor(major, minor),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn req(s: &str) -> NpmVersionReq {
+        NpmVersionReq::parse(s).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    fn ver(s: &str) -> Version {
+        Version::parse(s).unwrap()
+    }
+
+    fn assert_matches(range: &str, matching: &[&str], not_matching: &[&str]) {
+        let range_req = req(range);
+        for v in matching {
+            assert!(range_req.matches(&ver(v)), "`{range}` should match `{v}`");
+        }
+        for v in not_matching {
+            assert!(
+                !range_req.matches(&ver(v)),
+                "`{range}` should not match `{v}`"
+            );
+        }
+    }
+
+    #[test]
+    fn unions() {
+        assert_matches(
+            "1.x || >=2.5.0",
+            &["1.0.0", "1.9.9", "2.5.0", "3.0.0"],
+            &["0.9.0", "2.0.0", "2.4.9"],
+        );
+        assert_matches(
+            "^1.2.3 || ^2.0.0",
+            &["1.2.3", "1.9.0", "2.0.0", "2.9.9"],
+            &["1.2.2", "3.0.0"],
+        );
+        assert_eq!(req("1.x || 2.x").sets.len(), 2);
+    }
+
+    #[test]
+    fn comparator_sets() {
+        assert_matches(">=1.0.0 <2.0.0", &["1.0.0", "1.9.9"], &["0.9.9", "2.0.0"]);
+        assert_matches(">1.0.0 <=1.5.0", &["1.0.1", "1.5.0"], &["1.0.0", "1.5.1"]);
+        assert_matches("1.2.3", &["1.2.3"], &["1.2.4", "1.2.2"]);
+        assert_matches("=1.2.3", &["1.2.3"], &["1.2.4"]);
+    }
+
+    #[test]
+    fn hyphen_ranges() {
+        assert_matches(
+            "1.2.3 - 2.3.4",
+            &["1.2.3", "2.0.0", "2.3.4"],
+            &["1.2.2", "2.3.5"],
+        );
+        // Partial lower bounds are filled with zeroes
+        assert_matches("1.2 - 2.3.4", &["1.2.0", "2.3.4"], &["1.1.9", "2.3.5"]);
+        // Partial upper bounds include everything up to the next version
+        assert_matches("1.2.3 - 2.3", &["2.3.0", "2.3.9"], &["2.4.0"]);
+        assert_matches("1.2.3 - 2", &["2.0.0", "2.9.9"], &["3.0.0"]);
+    }
+
+    #[test]
+    fn x_ranges() {
+        assert_matches("*", &["0.0.0", "1.2.3", "99.0.0"], &[]);
+        assert_matches("", &["0.0.0", "1.2.3"], &[]);
+        assert_matches("1.x", &["1.0.0", "1.9.9"], &["0.9.9", "2.0.0"]);
+        assert_matches("1.2.x", &["1.2.0", "1.2.9"], &["1.3.0", "1.1.9"]);
+        assert_matches("1.X", &["1.5.0"], &["2.0.0"]);
+        assert_matches("1", &["1.0.0", "1.9.9"], &["2.0.0"]);
+        assert_matches("1.2", &["1.2.0", "1.2.9"], &["1.3.0"]);
+        assert_matches(">1.x", &["2.0.0"], &["1.9.9"]);
+        assert_matches("<1.2", &["1.1.9"], &["1.2.0"]);
+    }
+
+    #[test]
+    fn tilde_ranges() {
+        assert_matches("~1.2.3", &["1.2.3", "1.2.9"], &["1.2.2", "1.3.0"]);
+        assert_matches("~1.2", &["1.2.0", "1.2.9"], &["1.3.0"]);
+        assert_matches("~1", &["1.0.0", "1.9.9"], &["2.0.0"]);
+        assert_matches("~0.2.3", &["0.2.3", "0.2.9"], &["0.3.0"]);
+        assert_matches("~0.0.1", &["0.0.1", "0.0.9"], &["0.1.0"]);
+    }
+
+    #[test]
+    fn caret_ranges() {
+        assert_matches("^1.2.3", &["1.2.3", "1.9.9"], &["1.2.2", "2.0.0"]);
+        assert_matches("^0.2.3", &["0.2.3", "0.2.9"], &["0.2.2", "0.3.0"]);
+        assert_matches("^0.0.3", &["0.0.3"], &["0.0.2", "0.0.4"]);
+        assert_matches("^0.0", &["0.0.0", "0.0.9"], &["0.1.0"]);
+        assert_matches("^0.x", &["0.0.0", "0.9.9"], &["1.0.0"]);
+        assert_matches("^1.x", &["1.0.0", "1.9.9"], &["2.0.0"]);
+    }
+
+    #[test]
+    fn prereleases() {
+        // Prereleases only match with a prerelease on the same major.minor.patch
+        assert_matches(
+            ">=1.2.3-beta.1",
+            &["1.2.3-beta.1", "1.2.3-beta.2", "1.2.3", "1.3.0"],
+            &["1.2.3-alpha.1", "1.2.4-beta.1"],
+        );
+        assert_matches("^1.2.3", &["1.2.3"], &["1.2.4-beta.1", "1.3.0-rc.1"]);
+        assert_matches(
+            "^1.2.3-rc.1",
+            &["1.2.3-rc.1", "1.2.3-rc.2", "1.5.0"],
+            &["1.2.4-rc.1"],
+        );
+        assert_matches("*", &[], &["1.0.0-beta.1"]);
+        assert_matches("1.2.3-beta.1 || 1.x", &["1.2.3-beta.1"], &["1.5.0-beta.1"]);
+    }
+
+    #[test]
+    fn min_version() {
+        let min = |range: &str| req(range).min_version().map(|v| v.to_string());
+        assert_eq!(min("^1.2.3").as_deref(), Some("1.2.3"));
+        assert_eq!(min(">1.2.3").as_deref(), Some("1.2.4"));
+        assert_eq!(min(">=1.0.0 <2.0.0").as_deref(), Some("1.0.0"));
+        assert_eq!(min("1.2 - 2.3.4").as_deref(), Some("1.2.0"));
+        assert_eq!(min("2.x || 1.x").as_deref(), Some("1.0.0"));
+        assert_eq!(min("*").as_deref(), Some("0.0.0"));
+        assert_eq!(min("<1.0.0").as_deref(), Some("0.0.0"));
+        assert_eq!(min(">=1.2.3-beta.1").as_deref(), Some("1.2.3-beta.1"));
+        assert_eq!(min(">2.0.0 <1.0.0"), None);
+    }
+
+    #[test]
+    fn maximum_version_exclusive() {
+        let max = |range: &str| req(range).maximum_version_exclusive();
+        // The `-0` prerelease that excludes prereleases from upper bounds is not included
+        assert_eq!(max("^1.2.3"), Some(ver("2.0.0")));
+        assert_eq!(max("~1.2.3"), Some(ver("1.3.0")));
+        assert_eq!(max("<2.0.0"), Some(ver("2.0.0")));
+        assert_eq!(max("<=2.0.0"), Some(ver("2.0.1")));
+        assert_eq!(max("1.x || 3.x"), Some(ver("4.0.0")));
+        assert_eq!(max(">=1.0.0"), None);
+        assert_eq!(max("1.x || >=2.5.0"), None);
+    }
+
+    #[test]
+    fn huge_components() {
+        let huge = u64::MAX;
+        let max = |range: &str| req(range).maximum_version_exclusive();
+        let min = |range: &str| req(range).min_version().map(|v| v.to_string());
+        // Bumping the largest component carries over, or leaves no upper bound for the major
+        assert_matches(
+            &format!("^{huge}.0.0"),
+            &[&format!("{huge}.5.0")],
+            &["1.0.0"],
+        );
+        assert_matches(&format!("~1.{huge}"), &[&format!("1.{huge}.3")], &["2.0.0"]);
+        assert_matches(&format!("{huge}.x"), &[&format!("{huge}.1.2")], &["1.0.0"]);
+        assert_matches(&format!("<={huge}"), &["1.0.0"], &[]);
+        assert_matches(&format!(">{huge}"), &[], &["1.0.0"]);
+        assert_eq!(max(&format!("^{huge}.0.0")), None);
+        assert_eq!(max(&format!("<=0.0.{huge}")), Some(ver("0.1.0")));
+        assert_eq!(max(&format!("<={huge}.{huge}.{huge}")), None);
+        assert_eq!(min(&format!(">0.0.{huge}")).as_deref(), Some("0.1.0"));
+        assert_eq!(min(&format!(">{huge}.{huge}.{huge}")), None);
+    }
+
+    #[test]
+    fn invalid_ranges() {
+        assert!(NpmVersionReq::parse("not a version").is_err());
+        assert!(NpmVersionReq::parse("^1.2.3.4").is_err());
+        assert!(NpmVersionReq::parse("1.2.3 -").is_err());
+    }
+}
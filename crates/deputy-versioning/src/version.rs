@@ -1,5 +1,7 @@
 use semver::{Error, Version, VersionReq};
 
+use crate::{NpmVersionReq, NpmVersionReqError, VersionReqExt};
+
 fn trim_version_specifiers(s: &str) -> String {
     s.trim_start_matches('^')
         .trim_start_matches('>')
@@ -42,6 +44,27 @@ pub struct CompletionVersion<T> {
     pub item: T,
 }
 
+/**
+    A summary of which versions a version requirement matches.
+
+    Includes the bounds of the requirement, as well as the highest matching
+    version and the associated data for whatever was compared to.
+
+    The requirement is a Cargo-flavored `VersionReq` by default,
+    but may also be an `NpmVersionReq` for npm version ranges.
+*/
+pub struct VersionRange<T, R = VersionReq> {
+    pub version_req: R,
+    pub minimum_version: Version,
+    pub maximum_version_exclusive: Option<Version>,
+    pub highest_version: Option<Version>,
+    pub highest: Option<T>,
+    pub matching_count: usize,
+    pub yanked_count: usize,
+    pub deprecated_count: usize,
+    pub prerelease_count: usize,
+}
+
 /**
     Helper trait for anything that contains a version string.
 */
@@ -68,10 +91,24 @@ pub trait Versioned {
         self.raw_version_string().trim().parse()
     }
 
+    /**
+       Parses the string into an `NpmVersionReq` object.
+
+       See [`NpmVersionReq::parse`] for more information.
+    */
+    #[allow(clippy::missing_errors_doc)]
+    fn parse_npm_version_req(&self) -> Result<NpmVersionReq, NpmVersionReqError> {
+        NpmVersionReq::parse(self.raw_version_string().trim())
+    }
+
     fn deprecated(&self) -> bool {
         false
     }
 
+    fn yanked(&self) -> bool {
+        false
+    }
+
     fn extract_latest_version_filtered<I, V, F>(
         &self,
         other_versions: I,
@@ -204,6 +241,96 @@ pub trait Versioned {
     {
         self.extract_completion_versions_filtered(potential_versions, |_| true)
     }
+
+    fn extract_version_range<I, V>(&self, other_versions: I) -> Option<VersionRange<V>>
+    where
+        I: IntoIterator<Item = V>,
+        V: Versioned,
+    {
+        let version_req = self.parse_version_req().ok()?;
+        Some(version_range(version_req, other_versions))
+    }
+
+    fn extract_npm_version_range<I, V>(
+        &self,
+        other_versions: I,
+    ) -> Option<VersionRange<V, NpmVersionReq>>
+    where
+        I: IntoIterator<Item = V>,
+        V: Versioned,
+    {
+        let version_req = self.parse_npm_version_req().ok()?;
+        Some(version_range(version_req, other_versions))
+    }
+}
+
+/**
+    Helper trait for matching versions against any kind of version requirement.
+*/
+trait MatchesVersion {
+    fn matches_version(&self, version: &Version) -> bool;
+}
+
+impl MatchesVersion for VersionReq {
+    fn matches_version(&self, version: &Version) -> bool {
+        self.matches(version)
+    }
+}
+
+impl MatchesVersion for NpmVersionReq {
+    fn matches_version(&self, version: &Version) -> bool {
+        self.matches(version)
+    }
+}
+
+fn version_range<R, I, V>(version_req: R, other_versions: I) -> VersionRange<V, R>
+where
+    R: VersionReqExt + MatchesVersion,
+    I: IntoIterator<Item = V>,
+    V: Versioned,
+{
+    let mut range: VersionRange<V, R> = VersionRange {
+        minimum_version: version_req.minimum_version(),
+        maximum_version_exclusive: version_req.maximum_version_exclusive(),
+        version_req,
+        highest_version: None,
+        highest: None,
+        matching_count: 0,
+        yanked_count: 0,
+        deprecated_count: 0,
+        prerelease_count: 0,
+    };
+
+    for item in other_versions {
+        let Ok(item_version) = item.parse_version() else {
+            continue;
+        };
+        if !range.version_req.matches_version(&item_version) {
+            continue;
+        }
+
+        range.matching_count += 1;
+        range.yanked_count += usize::from(item.yanked());
+        range.deprecated_count += usize::from(item.deprecated());
+        range.prerelease_count += usize::from(!item_version.pre.is_empty());
+
+        // Yanked and deprecated versions are never the highest, unless they are the only ones
+        let is_usable = !item.yanked() && !item.deprecated();
+        let is_higher = match (&range.highest, &range.highest_version) {
+            (Some(highest), Some(highest_version)) => {
+                let highest_usable = !highest.yanked() && !highest.deprecated();
+                (is_usable && !highest_usable)
+                    || (is_usable == highest_usable && item_version > *highest_version)
+            }
+            _ => true,
+        };
+        if is_higher {
+            range.highest_version = Some(item_version);
+            range.highest = Some(item);
+        }
+    }
+
+    range
 }
 
 impl Versioned for Version {
@@ -1,4 +1,4 @@
-use semver::{Op, Version, VersionReq};
+use semver::{Comparator, Op, Version, VersionReq};
 
 /**
     Helper trait for deriving versions from a `VersionReq`
@@ -8,6 +8,8 @@ use semver::{Op, Version, VersionReq};
 */
 pub trait VersionReqExt {
     fn minimum_version(&self) -> Version;
+    fn maximum_version_exclusive(&self) -> Option<Version>;
+    fn exact_version(&self) -> Option<Version>;
 }
 
 impl VersionReqExt for VersionReq {
@@ -17,6 +19,28 @@ impl VersionReqExt for VersionReq {
             .min()
             .unwrap_or_else(|| Version::new(0, 0, 0))
     }
+
+    fn maximum_version_exclusive(&self) -> Option<Version> {
+        // All comparators must match, so the lowest upper bound wins
+        self.comparators
+            .iter()
+            .filter_map(upper_bound_for_comparator)
+            .min()
+    }
+
+    fn exact_version(&self) -> Option<Version> {
+        // Only fully specified versions such as =1.2.3 are pinned, =1.2 is a range
+        match self.comparators.as_slice() {
+            [comp] if comp.op == Op::Exact => Some(Version {
+                major: comp.major,
+                minor: comp.minor?,
+                patch: comp.patch?,
+                pre: comp.pre.clone(),
+                build: semver::BuildMetadata::EMPTY,
+            }),
+            _ => None,
+        }
+    }
 }
 
 fn possible_versions_for_req(req: &VersionReq) -> Vec<Version> {
@@ -123,3 +147,31 @@ fn possible_versions_for_req(req: &VersionReq) -> Vec<Version> {
         })
         .collect()
 }
+
+fn upper_bound_for_comparator(comp: &Comparator) -> Option<Version> {
+    let bump_last = || match (comp.minor, comp.patch) {
+        (Some(minor), Some(patch)) => Version::new(comp.major, minor, patch + 1),
+        (Some(minor), None) => Version::new(comp.major, minor + 1, 0),
+        (None, _) => Version::new(comp.major + 1, 0, 0),
+    };
+
+    match comp.op {
+        Op::Exact | Op::LessEq | Op::Wildcard => Some(bump_last()),
+        Op::Less => Some(Version::new(
+            comp.major,
+            comp.minor.unwrap_or(0),
+            comp.patch.unwrap_or(0),
+        )),
+        Op::Tilde => Some(match comp.minor {
+            Some(minor) => Version::new(comp.major, minor + 1, 0),
+            None => Version::new(comp.major + 1, 0, 0),
+        }),
+        Op::Caret => Some(match (comp.major, comp.minor, comp.patch) {
+            (0, Some(0), Some(patch)) => Version::new(0, 0, patch + 1),
+            (0, Some(minor), _) => Version::new(0, minor + 1, 0),
+            (major, _, _) => Version::new(major + 1, 0, 0),
+        }),
+        // Greater and GreaterEq have no upper bound
+        _ => None,
+    }
+}
@@ -0,0 +1,267 @@
+use std::{collections::HashMap, fmt::Write as _, fs::File, io::Read, path::Path};
+
+use anyhow::{Context, Result, bail};
+use flate2::read::GzDecoder;
+use serde::Deserialize;
+use tar::Archive;
+
+/**
+    A row in `data/crates.csv` of a crates.io database dump.
+
+    Older dumps have a `downloads` column here, newer
+    dumps store downloads in `data/crate_downloads.csv`.
+*/
+#[derive(Debug, Deserialize)]
+struct CrateRow {
+    id: u64,
+    name: String,
+    description: String,
+    #[serde(default)]
+    downloads: Option<u64>,
+}
+
+/**
+    A row in `data/crate_downloads.csv` of a crates.io database dump.
+*/
+#[derive(Debug, Deserialize)]
+struct CrateDownloadsRow {
+    crate_id: u64,
+    downloads: u64,
+}
+
+#[derive(Debug)]
+pub struct CratesIoPackage {
+    pub name: String,
+    pub downloads: u64,
+    pub description: String,
+}
+
+/**
+    Reads all packages from a crates.io database dump tarball.
+
+    See <https://crates.io/data-access#database-dumps> for more information.
+*/
+pub fn read_dump(path: &Path) -> Result<Vec<CratesIoPackage>> {
+    let file = File::open(path).with_context(|| format!("failed to open {}", path.display()))?;
+    parse_dump(file)
+}
+
+/**
+    Reads all packages from a gzipped crates.io database dump tarball.
+*/
+fn parse_dump(reader: impl Read) -> Result<Vec<CratesIoPackage>> {
+    let mut crates = None;
+    let mut downloads = None;
+
+    let mut archive = Archive::new(GzDecoder::new(reader));
+    for entry in archive.entries().context("failed to read dump archive")? {
+        let entry = entry.context("failed to read dump archive entry")?;
+        let entry_path = entry.path()?.to_string_lossy().to_string();
+        if entry_path.ends_with("data/crates.csv") {
+            crates = Some(read_csv::<CrateRow>(entry).context("failed to read crates.csv")?);
+        } else if entry_path.ends_with("data/crate_downloads.csv") {
+            downloads = Some(
+                read_csv::<CrateDownloadsRow>(entry)
+                    .context("failed to read crate_downloads.csv")?,
+            );
+        }
+    }
+
+    let Some(crates) = crates else {
+        bail!("dump is missing data/crates.csv");
+    };
+
+    let downloads = downloads
+        .unwrap_or_default()
+        .into_iter()
+        .map(|row| (row.crate_id, row.downloads))
+        .collect::<HashMap<_, _>>();
+
+    crates
+        .into_iter()
+        .map(|row| {
+            let Some(count) = row.downloads.or_else(|| downloads.get(&row.id).copied()) else {
+                bail!("dump is missing downloads for crate '{}'", row.name);
+            };
+            Ok(CratesIoPackage {
+                name: row.name,
+                downloads: count,
+                description: row.description,
+            })
+        })
+        .collect()
+}
+
+fn read_csv<T: for<'de> Deserialize<'de>>(reader: impl Read) -> Result<Vec<T>> {
+    let mut rows = Vec::new();
+    for row in csv::Reader::from_reader(reader).deserialize() {
+        rows.push(row?);
+    }
+    Ok(rows)
+}
+
+/**
+    Formats packages in the format of `top-crates-io-packages.txt`:
+
+    ```
+    name:downloads:"description"
+    ```
+
+    Packages are ordered by downloads, most downloaded first,
+    and then by name, so that the output is deterministic.
+*/
+pub fn format_packages(mut packages: Vec<CratesIoPackage>, limit: usize) -> String {
+    packages.sort_by(|a, b| b.downloads.cmp(&a.downloads).then(a.name.cmp(&b.name)));
+    packages.truncate(limit);
+
+    let mut contents = String::new();
+    for package in packages {
+        // Descriptions must fit on a single line, so collapse any whitespace
+        let description = package.description.split_whitespace().collect::<Vec<_>>();
+        writeln!(
+            contents,
+            "{}:{}:\"{}\"",
+            package.name,
+            package.downloads,
+            description.join(" ")
+        )
+        .unwrap();
+    }
+    contents
+}
+
+#[cfg(test)]
+mod tests {
+    use flate2::{Compression, write::GzEncoder};
+
+    use super::*;
+
+    fn dump(files: &[(&str, &str)]) -> Vec<u8> {
+        let mut builder = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::fast()));
+        for (path, contents) in files {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(contents.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder
+                .append_data(
+                    &mut header,
+                    format!("2024-01-01/{path}"),
+                    contents.as_bytes(),
+                )
+                .unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap()
+    }
+
+    /*
+        Same parsing as `parse_crates_io_packages` in the build script
+        of `deputy-tools`, which reads the formatted file at compile time
+    */
+    fn parse_formatted(contents: &str) -> Vec<(String, u64, String)> {
+        contents
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| {
+                let (name, rest) = line.split_once(':').expect("missing name");
+                let (downloads, rest) = rest.split_once(':').expect("missing downloads");
+                let description = rest
+                    .strip_prefix('"')
+                    .and_then(|d| d.strip_suffix('"'))
+                    .expect("unquoted description");
+                (
+                    name.trim().to_string(),
+                    downloads.parse().expect("invalid downloads"),
+                    description.to_string(),
+                )
+            })
+            .collect()
+    }
+
+    const EXPECTED: &str = "\
+serde:300:\"A generic serialization/deserialization framework\"
+anyhow:200:\"Flexible concrete Error type\"
+tokio:200:\"An event-driven, non-blocking I/O platform\"
+";
+
+    #[test]
+    fn dump_with_downloads_column() {
+        let crates = "\
+id,name,description,downloads
+1,tokio,\"An event-driven,
+non-blocking I/O platform\",200
+2,serde,A generic serialization/deserialization framework,300
+3,anyhow,Flexible concrete Error type,200
+4,tiny,,5
+";
+        let packages = parse_dump(dump(&[("data/crates.csv", crates)]).as_slice()).unwrap();
+        assert_eq!(format_packages(packages, 3), EXPECTED);
+    }
+
+    #[test]
+    fn dump_with_downloads_table() {
+        let crates = "\
+id,name,description,homepage
+1,tokio,\"An event-driven,   non-blocking I/O platform\",https://tokio.rs
+2,serde,A generic serialization/deserialization framework,
+3,anyhow,Flexible concrete Error type,
+4,tiny,,
+";
+        let downloads = "\
+crate_id,downloads
+3,200
+1,200
+2,300
+4,5
+";
+        let archive = dump(&[
+            ("data/crates.csv", crates),
+            ("data/crate_downloads.csv", downloads),
+        ]);
+        let packages = parse_dump(archive.as_slice()).unwrap();
+        assert_eq!(format_packages(packages, 3), EXPECTED);
+    }
+
+    #[test]
+    fn dump_missing_downloads() {
+        let crates = "id,name,description\n1,tokio,Runtime\n";
+        let archive = dump(&[("data/crates.csv", crates)]);
+        assert!(parse_dump(archive.as_slice()).is_err());
+    }
+
+    #[test]
+    fn formatted_is_deterministic_and_parses() {
+        let packages = || {
+            vec![
+                ("b", 10, "Quoted \"text\"\tand\ttabs"),
+                ("a", 10, ""),
+                ("c", 20, "Multiple\nlines"),
+            ]
+            .into_iter()
+            .map(|(name, downloads, description)| CratesIoPackage {
+                name: name.to_string(),
+                downloads,
+                description: description.to_string(),
+            })
+            .collect::<Vec<_>>()
+        };
+
+        let formatted = format_packages(packages(), 10);
+        let mut reversed = packages();
+        reversed.reverse();
+        assert_eq!(format_packages(reversed, 10), formatted);
+
+        assert_eq!(
+            parse_formatted(&formatted),
+            vec![
+                (String::from("c"), 20, String::from("Multiple lines")),
+                (String::from("a"), 10, String::new()),
+                (
+                    String::from("b"),
+                    10,
+                    String::from("Quoted \"text\" and tabs")
+                ),
+            ]
+        );
+    }
+}
@@ -0,0 +1,31 @@
+use anyhow::Result;
+use clap::{Parser, Subcommand};
+
+mod crates_io;
+mod npm;
+mod refresh;
+mod rokit;
+
+use self::refresh::RefreshCommand;
+
+#[derive(Debug, Clone, Subcommand)]
+pub enum AssetsSubcommand {
+    Refresh(RefreshCommand),
+}
+
+/**
+    Manages the bundled lists of top packages in `crates/deputy-tools/assets`.
+*/
+#[derive(Debug, Clone, Parser)]
+pub struct AssetsCommand {
+    #[clap(subcommand)]
+    subcommand: AssetsSubcommand,
+}
+
+impl AssetsCommand {
+    pub fn run(self) -> Result<()> {
+        match self.subcommand {
+            AssetsSubcommand::Refresh(cmd) => cmd.run(),
+        }
+    }
+}
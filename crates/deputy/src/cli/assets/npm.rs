@@ -0,0 +1,141 @@
+use std::{collections::HashMap, fs, path::Path};
+
+use anyhow::{Context, Result, bail};
+
+#[derive(Debug)]
+pub struct NpmPackage {
+    pub name: String,
+    pub downloads: u64,
+}
+
+/**
+    Reads packages from a file with download counts, stored as:
+
+    ```
+    name downloads
+    @scope/name downloads
+    ```
+
+    Names and counts may be separated by whitespace or a comma,
+    and a leading header line such as `name,downloads` is skipped.
+*/
+pub fn read_downloads(path: &Path) -> Result<Vec<NpmPackage>> {
+    let contents =
+        fs::read_to_string(path).with_context(|| format!("failed to read {}", path.display()))?;
+    parse_downloads(&contents)
+}
+
+fn parse_downloads(contents: &str) -> Result<Vec<NpmPackage>> {
+    // Keep the highest count if a package is listed more than once
+    let mut packages = HashMap::<String, u64>::new();
+    for (index, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let Some((name, downloads)) = line
+            .split_once(|c: char| c == ',' || c.is_whitespace())
+            .map(|(name, downloads)| (name.trim(), downloads.trim()))
+        else {
+            bail!("line {} is missing a download count: '{line}'", index + 1);
+        };
+
+        let downloads = match downloads.parse::<u64>() {
+            Ok(downloads) => downloads,
+            Err(_) if index == 0 => continue,
+            Err(e) => bail!("line {} has an invalid download count: {e}", index + 1),
+        };
+
+        let count = packages.entry(name.to_string()).or_default();
+        *count = (*count).max(downloads);
+    }
+
+    Ok(packages
+        .into_iter()
+        .map(|(name, downloads)| NpmPackage { name, downloads })
+        .collect())
+}
+
+/**
+    Formats packages in the format of `top-npm-packages.txt`,
+    which is one name per line, most downloaded first.
+
+    Packages with the same number of downloads are
+    ordered by name, so that the output is deterministic.
+*/
+pub fn format_packages(mut packages: Vec<NpmPackage>, limit: usize) -> String {
+    packages.sort_by(|a, b| b.downloads.cmp(&a.downloads).then(a.name.cmp(&b.name)));
+    packages.truncate(limit);
+
+    let mut contents = String::new();
+    for package in packages {
+        contents.push_str(&package.name);
+        contents.push('\n');
+    }
+    contents
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fmt::Write as _;
+
+    use super::*;
+
+    /*
+        Same parsing as `parse_npm_packages` in the build script of `deputy-tools`,
+        which reads the formatted file at compile time, without the popularity
+    */
+    fn parse_formatted(contents: &str) -> Vec<&str> {
+        contents
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .collect()
+    }
+
+    #[test]
+    fn downloads() {
+        let contents = "\
+name,downloads
+react,300
+@types/node 500
+
+lodash\t300
+react,100
+";
+        let packages = parse_downloads(contents).unwrap();
+        let formatted = format_packages(packages, 10);
+        assert_eq!(formatted, "@types/node\nlodash\nreact\n");
+        assert_eq!(
+            parse_formatted(&formatted),
+            vec!["@types/node", "lodash", "react"]
+        );
+    }
+
+    #[test]
+    fn downloads_limit() {
+        let packages = parse_downloads("a 1\nb 2\nc 3\n").unwrap();
+        assert_eq!(format_packages(packages, 2), "c\nb\n");
+    }
+
+    #[test]
+    fn downloads_invalid() {
+        assert!(parse_downloads("react\n").is_err());
+        assert!(parse_downloads("react 1\nlodash many\n").is_err());
+    }
+
+    #[test]
+    fn formatted_is_deterministic() {
+        let mut contents = String::new();
+        for index in 0..100 {
+            writeln!(contents, "package-{index} {}", index % 7).unwrap();
+        }
+        let formatted = format_packages(parse_downloads(&contents).unwrap(), 50);
+        for _ in 0..10 {
+            // Packages are collected from a hash map, which is ordered differently each time
+            let packages = parse_downloads(&contents).unwrap();
+            assert_eq!(format_packages(packages, 50), formatted);
+        }
+    }
+}
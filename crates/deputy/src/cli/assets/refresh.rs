@@ -0,0 +1,92 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result, bail};
+use clap::Parser;
+use tracing::info;
+
+use super::{crates_io, npm, rokit};
+
+const DEFAULT_OUTPUT_DIR: &str = "crates/deputy-tools/assets";
+const DEFAULT_LIMIT: usize = 10_000;
+
+/**
+    Regenerates the bundled lists of top packages from local data sources.
+
+    Only the lists for which a source is given are regenerated.
+*/
+#[derive(Debug, Clone, Parser)]
+pub struct RefreshCommand {
+    /**
+        Path to a crates.io database dump (`db-dump.tar.gz`)
+    */
+    #[arg(long)]
+    pub crates_io_dump: Option<PathBuf>,
+    /**
+        Path to a file with npm package names and download counts, one per line
+    */
+    #[arg(long)]
+    pub npm_downloads: Option<PathBuf>,
+    /**
+        Path to a file with Rokit-compatible tools, as `owner/repository`, one per line
+    */
+    #[arg(long)]
+    pub rokit_tools: Option<PathBuf>,
+    /**
+        Directory to write the generated lists to
+    */
+    #[arg(long, default_value = DEFAULT_OUTPUT_DIR)]
+    pub output: PathBuf,
+    /**
+        Maximum number of entries to keep in each list
+    */
+    #[arg(long, default_value_t = DEFAULT_LIMIT)]
+    pub limit: usize,
+}
+
+impl RefreshCommand {
+    pub fn run(self) -> Result<()> {
+        if self.crates_io_dump.is_none()
+            && self.npm_downloads.is_none()
+            && self.rokit_tools.is_none()
+        {
+            bail!(
+                "no sources given - pass at least one of --crates-io-dump, --npm-downloads or --rokit-tools"
+            );
+        }
+
+        if let Some(path) = &self.crates_io_dump {
+            let packages = crates_io::read_dump(path)?;
+            let contents = crates_io::format_packages(packages, self.limit);
+            self.write("top-crates-io-packages.txt", &contents)?;
+        }
+
+        if let Some(path) = &self.npm_downloads {
+            let packages = npm::read_downloads(path)?;
+            let contents = npm::format_packages(packages, self.limit);
+            self.write("top-npm-packages.txt", &contents)?;
+        }
+
+        if let Some(path) = &self.rokit_tools {
+            let tools = rokit::read_tools(path)?;
+            let contents = rokit::format_tools(tools, self.limit);
+            self.write("top-rokit-tools.txt", &contents)?;
+        }
+
+        Ok(())
+    }
+
+    fn write(&self, file_name: &str, contents: &str) -> Result<()> {
+        let path = Path::new(&self.output).join(file_name);
+        fs::write(&path, contents)
+            .with_context(|| format!("failed to write {}", path.display()))?;
+        info!(
+            "Wrote {} entries to {}",
+            contents.lines().count(),
+            path.display()
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,160 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashSet},
+    fs,
+    path::Path,
+};
+
+use anyhow::{Context, Result, bail};
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RokitTool {
+    pub author: String,
+    pub name: String,
+}
+
+/**
+    Reads tools from a list of GitHub repositories, stored as:
+
+    ```
+    owner/repository
+    ```
+
+    Lines in the bundled `author:a-tool,some-other-tool` format are
+    also accepted, so that an existing list can be used as a source.
+*/
+pub fn read_tools(path: &Path) -> Result<Vec<RokitTool>> {
+    let contents =
+        fs::read_to_string(path).with_context(|| format!("failed to read {}", path.display()))?;
+    parse_tools(&contents)
+}
+
+fn parse_tools(contents: &str) -> Result<Vec<RokitTool>> {
+    let mut seen = HashSet::new();
+    let mut tools = Vec::new();
+    for (index, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let Some((author, names)) = line.split_once('/').or_else(|| line.split_once(':')) else {
+            bail!("line {} is not a valid tool: '{line}'", index + 1);
+        };
+
+        for name in names.split(',').map(str::trim).filter(|n| !n.is_empty()) {
+            let tool = RokitTool {
+                author: author.trim().to_string(),
+                name: name.to_string(),
+            };
+            if seen.insert(tool.clone()) {
+                tools.push(tool);
+            }
+        }
+    }
+
+    Ok(tools)
+}
+
+/**
+    Formats tools in the format of `top-rokit-tools.txt`:
+
+    ```
+    author:a-tool,some-other-tool
+    second_author:more_tools
+    ```
+
+    The first `limit` tools are kept, in the order that they were listed,
+    and authors and tools are then sorted alphabetically, ignoring case.
+*/
+pub fn format_tools(mut tools: Vec<RokitTool>, limit: usize) -> String {
+    tools.truncate(limit);
+
+    let mut authors = BTreeMap::<(String, String), BTreeSet<String>>::new();
+    for tool in tools {
+        authors
+            .entry((tool.author.to_ascii_lowercase(), tool.author))
+            .or_default()
+            .insert(tool.name);
+    }
+
+    let mut contents = String::new();
+    for ((_, author), names) in authors {
+        let mut names = names.into_iter().collect::<Vec<_>>();
+        names.sort_by_key(|name| name.to_ascii_lowercase());
+        contents.push_str(&author);
+        contents.push(':');
+        contents.push_str(&names.join(","));
+        contents.push('\n');
+    }
+    contents
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /*
+        Same parsing as the bundled list of tools in `deputy-tools`,
+        which splits each line into an author and its tools
+    */
+    fn parse_formatted(contents: &str) -> Vec<(&str, Vec<&str>)> {
+        contents
+            .lines()
+            .filter_map(|line| {
+                let (author, names) = line.split_once(':')?;
+                Some((author, names.split(',').collect()))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn tools() {
+        let contents = "\
+rojo-rbx/rojo
+UpliftGames/wally
+JohnnyMorganz/StyLua
+rojo-rbx/rojo
+kampfkarren:selene,Moonwave
+UpliftGames:wally-package-types
+";
+        let tools = parse_tools(contents).unwrap();
+        let formatted = format_tools(tools, 10);
+        assert_eq!(
+            formatted,
+            "\
+JohnnyMorganz:StyLua
+kampfkarren:Moonwave,selene
+rojo-rbx:rojo
+UpliftGames:wally,wally-package-types
+"
+        );
+        assert_eq!(
+            parse_formatted(&formatted),
+            vec![
+                ("JohnnyMorganz", vec!["StyLua"]),
+                ("kampfkarren", vec!["Moonwave", "selene"]),
+                ("rojo-rbx", vec!["rojo"]),
+                ("UpliftGames", vec!["wally", "wally-package-types"]),
+            ]
+        );
+    }
+
+    #[test]
+    fn tools_limit() {
+        let tools = parse_tools("b/one\na/two\nc/three\n").unwrap();
+        assert_eq!(format_tools(tools, 2), "a:two\nb:one\n");
+    }
+
+    #[test]
+    fn tools_invalid() {
+        assert!(parse_tools("rojo\n").is_err());
+    }
+
+    #[test]
+    fn formatted_is_deterministic() {
+        let tools = parse_tools("b/y\na/x\nb/x\n").unwrap();
+        let mut reversed = tools.clone();
+        reversed.reverse();
+        assert_eq!(format_tools(reversed, 10), format_tools(tools, 10));
+    }
+}
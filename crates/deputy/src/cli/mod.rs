@@ -1,13 +1,16 @@
 use anyhow::Result;
 use clap::{Parser, Subcommand};
 
+mod assets;
 mod serve;
 
+use self::assets::AssetsCommand;
 use self::serve::ServeCommand;
 
 #[derive(Debug, Clone, Subcommand)]
 pub enum CliSubcommand {
     Serve(ServeCommand),
+    Assets(AssetsCommand),
 }
 
 #[derive(Debug, Clone, Parser)]
@@ -25,6 +28,7 @@ impl Cli {
     pub async fn run(self) -> Result<()> {
         match self.subcommand {
             CliSubcommand::Serve(cmd) => cmd.run().await,
+            CliSubcommand::Assets(cmd) => cmd.run(),
         }
     }
 }
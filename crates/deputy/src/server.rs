@@ -1,11 +1,20 @@
+use std::sync::{
+    Arc, OnceLock,
+    atomic::{AtomicBool, Ordering},
+};
+
 use async_language_server::{
     lsp_types::{
         ClientCapabilities, CodeActionKind, CodeActionOptions, CodeActionParams,
-        CodeActionProviderCapability, CodeActionResponse, CompletionOptions, CompletionParams,
-        CompletionResponse, DiagnosticOptions, DiagnosticServerCapabilities,
+        CodeActionProviderCapability, CodeActionResponse, CompletionItem, CompletionOptions,
+        CompletionParams, CompletionResponse, DiagnosticOptions, DiagnosticServerCapabilities,
+        DidChangeWatchedFilesParams, DidChangeWatchedFilesRegistrationOptions,
         DocumentDiagnosticParams, DocumentDiagnosticReport, DocumentDiagnosticReportResult,
-        FullDocumentDiagnosticReport, Hover, HoverParams, HoverProviderCapability,
+        FileSystemWatcher, FullDocumentDiagnosticReport, GlobPattern, Hover, HoverParams,
+        HoverProviderCapability, Registration, RegistrationParams,
         RelatedFullDocumentDiagnosticReport, ServerCapabilities, ServerInfo,
+        notification::{DidChangeWatchedFiles, Notification},
+        request::{RegisterCapability, WorkspaceDiagnosticRefresh},
     },
     server::{DocumentMatcher, Server, ServerResult, ServerState},
 };
@@ -14,18 +23,93 @@ use deputy_clients::Clients;
 use deputy_parser::{JSON_LANGUAGE, TOML_LANGUAGE};
 use deputy_tools::Tools;
 
+static CLIENT_CAPABILITIES: OnceLock<ClientCapabilities> = OnceLock::new();
+
 #[allow(dead_code)]
 #[derive(Debug, Clone)]
 pub struct DeputyLanguageServer {
     clients: Clients,
     tools: Tools,
+    watchers_registered: Arc<AtomicBool>,
 }
 
 impl DeputyLanguageServer {
     pub fn new() -> Self {
         let clients = Clients::new();
         let tools = Tools::new(&clients);
-        Self { clients, tools }
+        Self {
+            clients,
+            tools,
+            watchers_registered: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /**
+        Asks the client to watch files on disk that tools derive data from, such
+        as Rust sources for Cargo, so that any editor which supports dynamic
+        registration sends changes for them - not only our own extensions.
+
+        Only registers once, during the first request that can reach the client.
+    */
+    fn register_file_watchers(&self, state: &ServerState) {
+        let supported = CLIENT_CAPABILITIES
+            .get()
+            .and_then(|client| client.workspace.as_ref())
+            .and_then(|workspace| workspace.did_change_watched_files.as_ref())
+            .and_then(|watched| watched.dynamic_registration)
+            .unwrap_or_default();
+        if !supported || self.watchers_registered.swap(true, Ordering::Relaxed) {
+            return;
+        }
+
+        let options = DidChangeWatchedFilesRegistrationOptions {
+            watchers: Tools::watched_file_globs()
+                .into_iter()
+                .map(|glob| FileSystemWatcher {
+                    glob_pattern: GlobPattern::String(glob),
+                    kind: None,
+                })
+                .collect(),
+        };
+        let request = state
+            .client()
+            .request::<RegisterCapability>(RegistrationParams {
+                registrations: vec![Registration {
+                    id: String::from("deputy-watched-files"),
+                    method: String::from(DidChangeWatchedFiles::METHOD),
+                    register_options: serde_json::to_value(options).ok(),
+                }],
+            });
+
+        tokio::spawn(async move {
+            if let Err(e) = request.await {
+                tracing::warn!("Failed to register file watchers: {e}");
+            }
+        });
+    }
+
+    /**
+        Asks the client to pull diagnostics again for all open documents,
+        if it supports doing so - used when files on disk have changed.
+    */
+    async fn refresh_diagnostics(state: &ServerState) {
+        let supported = CLIENT_CAPABILITIES
+            .get()
+            .and_then(|client| client.workspace.as_ref())
+            .and_then(|workspace| workspace.diagnostic.as_ref())
+            .and_then(|diagnostic| diagnostic.refresh_support)
+            .unwrap_or_default();
+        if !supported {
+            return;
+        }
+
+        if let Err(e) = state
+            .client()
+            .request::<WorkspaceDiagnosticRefresh>(())
+            .await
+        {
+            tracing::warn!("Failed to refresh diagnostics: {e}");
+        }
     }
 }
 
@@ -43,7 +127,9 @@ impl Server for DeputyLanguageServer {
         })
     }
 
-    fn server_capabilities(_: ClientCapabilities) -> Option<ServerCapabilities> {
+    fn server_capabilities(client: ClientCapabilities) -> Option<ServerCapabilities> {
+        CLIENT_CAPABILITIES.get_or_init(|| client);
+
         Some(ServerCapabilities {
             hover_provider: Some(HoverProviderCapability::Simple(true)),
             completion_provider: Some(CompletionOptions {
@@ -135,11 +221,23 @@ impl Server for DeputyLanguageServer {
         self.tools.completion(&doc, pos, node).await
     }
 
+    async fn completion_resolve(
+        &self,
+        _state: ServerState,
+        item: CompletionItem,
+    ) -> ServerResult<CompletionItem> {
+        tracing::debug!("Resolving completion item: {}", item.label);
+
+        self.tools.completion_resolve(item).await
+    }
+
     async fn document_diagnostics(
         &self,
         state: ServerState,
         params: DocumentDiagnosticParams,
     ) -> ServerResult<DocumentDiagnosticReportResult> {
+        self.register_file_watchers(&state);
+
         let items = match state.document(&params.text_document.uri) {
             Some(doc) => self.tools.diagnostics(&doc, params).await?,
             None => Vec::new(),
@@ -156,13 +254,35 @@ impl Server for DeputyLanguageServer {
         ))
     }
 
+    async fn did_change_watched_files(
+        &self,
+        state: ServerState,
+        params: DidChangeWatchedFilesParams,
+    ) -> ServerResult<()> {
+        tracing::debug!("Watched files changed: {} change(s)", params.changes.len());
+
+        if self.tools.did_change_watched_files(&params) {
+            Self::refresh_diagnostics(&state).await;
+        }
+        Ok(())
+    }
+
     async fn code_action(
         &self,
         state: ServerState,
         params: CodeActionParams,
     ) -> ServerResult<Option<CodeActionResponse>> {
         if let Some(doc) = state.document(&params.text_document.uri) {
-            self.tools.code_action(&doc, params).await.map(Some)
+            let show_changes = CLIENT_CAPABILITIES
+                .get()
+                .and_then(|client| client.experimental.as_ref())
+                .and_then(|experimental| experimental.get("showChanges"))
+                .and_then(serde_json::Value::as_bool)
+                .unwrap_or_default();
+            self.tools
+                .code_action(&doc, params, show_changes)
+                .await
+                .map(Some)
         } else {
             Ok(None)
         }
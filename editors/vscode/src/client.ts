@@ -123,6 +123,20 @@ export const startServer = async () => {
 		clientOptions,
 	);
 
+	// Let the server know which custom commands the extension implements
+
+	client.registerFeature({
+		fillClientCapabilities: (capabilities) => {
+			capabilities.experimental = {
+				...capabilities.experimental,
+				showChanges: true,
+			};
+		},
+		getState: () => ({ kind: "static" }),
+		initialize: () => {},
+		clear: () => {},
+	});
+
 	client.start();
 
 	// Listen for custom requests from server
@@ -0,0 +1,16 @@
+import * as vscode from "vscode";
+
+export const showChanges = async (args: { title: string; content: string }) => {
+	const document = await vscode.workspace.openTextDocument({
+		language: "markdown",
+		content: args.content,
+	});
+	await vscode.window.showTextDocument(document, {
+		viewColumn: vscode.ViewColumn.Beside,
+		preview: true,
+	});
+};
+
+export default {
+	showChanges,
+};